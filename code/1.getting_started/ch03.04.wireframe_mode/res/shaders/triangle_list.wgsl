@@ -7,6 +7,7 @@ struct VertexInput {
 struct VertexOutput {
 	@builtin(position) position: vec4<f32>,
 	@location(0) color: vec4<f32>,
+	@location(1) barycentric: vec3<f32>,
 };
 
 @vertex
@@ -33,9 +34,17 @@ fn vs_main(in: VertexInput) -> VertexOutput {
 		vec3<f32>(0.0, 1.0, 0.0),
 		vec3<f32>(0.0, 0.0, 1.0),
 	);
+	// In a triangle strip, triangle i is made of vertices i, i+1 and i+2,
+	// so `vertex_index % 3` gives a different corner to each of them.
+	var barycentric = array<vec3<f32>, 3> (
+		vec3<f32>(1.0, 0.0, 0.0),
+		vec3<f32>(0.0, 1.0, 0.0),
+		vec3<f32>(0.0, 0.0, 1.0),
+	);
 	var output: VertexOutput;
 	output.position = vec4<f32>(pos[in.vertex_index], 0.0, 1.0);
 	output.color = vec4<f32>(color[in.vertex_index], 1.0);
+	output.barycentric = barycentric[in.vertex_index % 3u];
 
 	return output;
 }
@@ -43,9 +52,34 @@ fn vs_main(in: VertexInput) -> VertexOutput {
 // Fragment Shader
 struct FragmentInput {
 	@location(0) color: vec4<f32>,
+	@location(1) barycentric: vec3<f32>,
 };
 
+const WIREFRAME_COLOR: vec3<f32> = vec3<f32>(1.0, 1.0, 1.0);
+// In pixels.
+const WIREFRAME_WIDTH: f32 = 1.5;
+
 @fragment
 fn fs_main(in: FragmentInput) -> @location(0) vec4<f32> {
 	return in.color;
 }
+
+// Used with PolygonMode::Line.
+@fragment
+fn fs_wireframe(in: FragmentInput) -> @location(0) vec4<f32> {
+	return vec4<f32>(WIREFRAME_COLOR, 1.0);
+}
+
+// Used with PolygonMode::Fill, where POLYGON_MODE_LINE is not supported,
+// like WebGL. A fragment is on an edge if one of its barycentric coordinates
+// is close to 0, `fwidth()` converts the distance to pixels.
+@fragment
+fn fs_wireframe_barycentric(in: FragmentInput) -> @location(0) vec4<f32> {
+	let pixels = in.barycentric / fwidth(in.barycentric);
+	let distance = min(pixels.x, min(pixels.y, pixels.z));
+	let alpha = 1.0 - smoothstep(WIREFRAME_WIDTH - 1.0, WIREFRAME_WIDTH, distance);
+	if alpha <= 0.0 {
+		discard;
+	}
+	return vec4<f32>(WIREFRAME_COLOR, alpha);
+}
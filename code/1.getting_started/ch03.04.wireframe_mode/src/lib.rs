@@ -5,6 +5,7 @@
 mod error;
 mod init;
 mod state;
+mod wireframe;
 
 pub use self::error::Error;
 pub use self::init::run;
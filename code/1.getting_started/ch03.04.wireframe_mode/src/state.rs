@@ -3,9 +3,10 @@
 // in the LICENSE file.
 
 use winit::dpi::PhysicalSize;
-use winit::event::WindowEvent;
+use winit::event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
 use winit::window::Window;
 
+use crate::wireframe::WireframeMode;
 use crate::Error;
 
 const NUM_VERTICES: u32 = 6;
@@ -19,7 +20,9 @@ pub struct State {
     size: PhysicalSize<u32>,
     window: Window,
 
-    render_pipeline: wgpu::RenderPipeline,
+    fill_pipeline: wgpu::RenderPipeline,
+    wireframe_pipeline: wgpu::RenderPipeline,
+    mode: WireframeMode,
 }
 
 impl State {
@@ -57,13 +60,10 @@ impl State {
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    // POLYGON_MODE_LINE feature is requred.
-                    // Note that this feature is not available in webgpu/webgl2
-                    features: if cfg!(target_arch = "wasm32") {
-                        wgpu::Features::empty()
-                    } else {
-                        wgpu::Features::POLYGON_MODE_LINE
-                    },
+                    // POLYGON_MODE_LINE is requested if the adapter supports it.
+                    // It is not available in webgpu/webgl2, where wireframe
+                    // is drawn with barycentric coordinates instead.
+                    features: adapter.features() & wgpu::Features::POLYGON_MODE_LINE,
                     limits: if cfg!(target_arch = "wasm32") {
                         wgpu::Limits::downlevel_webgl2_defaults()
                     } else {
@@ -99,14 +99,12 @@ impl State {
     fn create_render_pipeline(
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
+        shader: &wgpu::ShaderModule,
+        label: &str,
+        fs_entry_point: &str,
+        polygon_mode: wgpu::PolygonMode,
+        blend: wgpu::BlendState,
     ) -> wgpu::RenderPipeline {
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(
-                include_str!("../res/shaders/triangle_list.wgsl").into(),
-            ),
-        });
-
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
@@ -115,19 +113,19 @@ impl State {
             });
 
         device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
+            label: Some(label),
             layout: Some(&render_pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: shader,
                 entry_point: "vs_main",
                 buffers: &[],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
+                module: shader,
+                entry_point: fs_entry_point,
                 targets: &[Some(wgpu::ColorTargetState {
                     format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
+                    blend: Some(blend),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
             }),
@@ -136,11 +134,7 @@ impl State {
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: None,
-                polygon_mode: if cfg!(target_arch = "wasm32") {
-                    wgpu::PolygonMode::Fill
-                } else {
-                    wgpu::PolygonMode::Line
-                },
+                polygon_mode,
                 unclipped_depth: false,
                 conservative: false,
             },
@@ -150,10 +144,60 @@ impl State {
         })
     }
 
+    /// Edges are drawn with `PolygonMode::Line` if the device supports it.
+    /// Otherwise triangles are filled, and fragments far from edges are
+    /// discarded by the shader.
+    fn create_wireframe_pipeline(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        shader: &wgpu::ShaderModule,
+    ) -> wgpu::RenderPipeline {
+        if device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE)
+        {
+            Self::create_render_pipeline(
+                device,
+                config,
+                shader,
+                "Wireframe Render Pipeline",
+                "fs_wireframe",
+                wgpu::PolygonMode::Line,
+                wgpu::BlendState::REPLACE,
+            )
+        } else {
+            log::info!("POLYGON_MODE_LINE is not supported, use barycentric wireframe");
+            Self::create_render_pipeline(
+                device,
+                config,
+                shader,
+                "Barycentric Wireframe Render Pipeline",
+                "fs_wireframe_barycentric",
+                wgpu::PolygonMode::Fill,
+                wgpu::BlendState::ALPHA_BLENDING,
+            )
+        }
+    }
+
     pub async fn new(window: Window) -> Result<Self, Error> {
         let (surface, device, queue, config, size) = Self::create_surface(&window).await?;
 
-        let render_pipeline = Self::create_render_pipeline(&device, &config);
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                include_str!("../res/shaders/triangle_list.wgsl").into(),
+            ),
+        });
+        let fill_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &shader,
+            "Fill Render Pipeline",
+            "fs_main",
+            wgpu::PolygonMode::Fill,
+            wgpu::BlendState::REPLACE,
+        );
+        let wireframe_pipeline = Self::create_wireframe_pipeline(&device, &config, &shader);
 
         Ok(Self {
             window,
@@ -162,7 +206,9 @@ impl State {
             queue,
             config,
             size,
-            render_pipeline,
+            fill_pipeline,
+            wireframe_pipeline,
+            mode: WireframeMode::default(),
         })
     }
 
@@ -183,8 +229,24 @@ impl State {
         }
     }
 
-    pub fn input(&mut self, _event: &WindowEvent) -> bool {
-        false
+    /// Press `W` to switch between fill, line and fill with line.
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::W),
+                        ..
+                    },
+                ..
+            } => {
+                self.mode = self.mode.next();
+                log::info!("Wireframe mode: {:?}", self.mode);
+                true
+            }
+            _ => false,
+        }
     }
 
     pub fn update(&mut self) {}
@@ -219,8 +281,14 @@ impl State {
                 depth_stencil_attachment: None,
             });
 
-            render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.draw(0..NUM_VERTICES, 0..1);
+            if self.mode.has_fill() {
+                render_pass.set_pipeline(&self.fill_pipeline);
+                render_pass.draw(0..NUM_VERTICES, 0..1);
+            }
+            if self.mode.has_line() {
+                render_pass.set_pipeline(&self.wireframe_pipeline);
+                render_pass.draw(0..NUM_VERTICES, 0..1);
+            }
         }
 
         self.queue.submit(std::iter::once(encoder.finish()));
@@ -0,0 +1,38 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+/// How triangles are drawn, switched at runtime.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WireframeMode {
+    /// Filled with vertex colors.
+    Fill,
+
+    /// Edges only.
+    #[default]
+    Line,
+
+    /// Filled, with edges drawn on top.
+    FillAndLine,
+}
+
+impl WireframeMode {
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Fill => Self::Line,
+            Self::Line => Self::FillAndLine,
+            Self::FillAndLine => Self::Fill,
+        }
+    }
+
+    #[must_use]
+    pub const fn has_fill(self) -> bool {
+        matches!(self, Self::Fill | Self::FillAndLine)
+    }
+
+    #[must_use]
+    pub const fn has_line(self) -> bool {
+        matches!(self, Self::Line | Self::FillAndLine)
+    }
+}
@@ -0,0 +1,571 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Matrix4, Rad, Vector4};
+use instant::Instant;
+use wgpu::util::DeviceExt;
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::window::Window;
+
+use crate::camera::Camera;
+use crate::cubes::CUBE_POSITIONS;
+use crate::geometry::create_cube_detail;
+use crate::objects::{Mesh, SceneObject};
+use crate::texture::Texture;
+use crate::uniforms::ModelUniform;
+use crate::vertex::Vertex;
+use crate::Error;
+
+/// Stencil value written by the selected object.
+const SELECTED_STENCIL_REF: u32 = 1;
+
+/// How much the outline is larger than the selected object.
+const OUTLINE_SCALE: f32 = 1.08;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PipelineKind {
+    /// Draw objects without touching stencil buffer.
+    Normal,
+
+    /// Draw the selected object, and write reference value into stencil buffer.
+    StencilWrite,
+
+    /// Draw scaled silhouette of the selected object where stencil != reference.
+    Outline,
+}
+
+#[derive(Debug)]
+pub struct State {
+    surface: wgpu::Surface,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    size: PhysicalSize<u32>,
+    window: Window,
+
+    normal_pipeline: wgpu::RenderPipeline,
+    stencil_write_pipeline: wgpu::RenderPipeline,
+    outline_pipeline: wgpu::RenderPipeline,
+
+    cube_mesh: Mesh,
+    cubes: Vec<SceneObject>,
+    outline: SceneObject,
+    selected: usize,
+
+    camera: Camera,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+
+    depth_texture: Texture,
+
+    start_time: Instant,
+}
+
+impl State {
+    async fn create_surface(
+        window: &Window,
+    ) -> Result<
+        (
+            wgpu::Surface,
+            wgpu::Device,
+            wgpu::Queue,
+            wgpu::SurfaceConfiguration,
+            PhysicalSize<u32>,
+        ),
+        Error,
+    > {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let surface = unsafe { instance.create_surface(&window) }?;
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| {
+                Error::Others("Failed to get an approprivate wgpu adapter".to_owned())
+            })?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    features: if cfg!(target_arch = "wasm32") {
+                        wgpu::Features::empty()
+                    } else {
+                        wgpu::Features::POLYGON_MODE_LINE
+                    },
+                    limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                },
+                None,
+            )
+            .await?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(wgpu::TextureFormat::is_srgb)
+            .unwrap_or(surface_caps.formats[0]);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&device, &config);
+
+        Ok((surface, device, queue, config, size))
+    }
+
+    fn create_render_pipeline(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        kind: PipelineKind,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../res/shaders/stencil.wgsl").into()),
+        });
+
+        let (label, fs_entry_point, stencil_face, stencil_write_mask) = match kind {
+            PipelineKind::Normal => (
+                "Render Pipeline",
+                "fs_main",
+                wgpu::StencilFaceState::IGNORE,
+                0x00,
+            ),
+            PipelineKind::StencilWrite => (
+                "Stencil Write Render Pipeline",
+                "fs_main",
+                wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::Always,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op: wgpu::StencilOperation::Replace,
+                },
+                0xff,
+            ),
+            PipelineKind::Outline => (
+                "Outline Render Pipeline",
+                "fs_outline",
+                wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::NotEqual,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op: wgpu::StencilOperation::Keep,
+                },
+                0x00,
+            ),
+        };
+
+        // Outline is always visible, even if the selected object is hidden
+        // behind other objects.
+        let (depth_write_enabled, depth_compare) = if kind == PipelineKind::Outline {
+            (false, wgpu::CompareFunction::Always)
+        } else {
+            (true, wgpu::CompareFunction::Less)
+        };
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: fs_entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_STENCIL_FORMAT,
+                depth_write_enabled,
+                depth_compare,
+                stencil: wgpu::StencilState {
+                    front: stencil_face,
+                    back: stencil_face,
+                    read_mask: 0xff,
+                    write_mask: stencil_write_mask,
+                },
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        render_pipeline
+    }
+
+    fn create_camera(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+    ) -> Result<(Camera, wgpu::Buffer, wgpu::BindGroupLayout, wgpu::BindGroup), Error> {
+        let eye_pos = (0.0, 0.0, 3.0).into();
+        let aspect = size.width as f32 / size.height as f32;
+        let camera = Camera::new(eye_pos, aspect);
+
+        let uniform_ref = camera.uniform_ref();
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Uniform Buffer"),
+            contents: bytemuck::cast_slice(uniform_ref),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("camera_bind_group_layout"),
+            });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("camera_bind_group"),
+        });
+
+        Ok((
+            camera,
+            camera_buffer,
+            camera_bind_group_layout,
+            camera_bind_group,
+        ))
+    }
+
+    fn create_model_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("model_bind_group_layout"),
+        })
+    }
+
+    fn create_objects(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Result<(Vec<SceneObject>, SceneObject), Error> {
+        let container_bytes = include_bytes!("../res/textures/container.jpg");
+        let container_texture =
+            Texture::from_bytes(device, queue, container_bytes, Some("container"))?;
+
+        let face_bytes = include_bytes!("../res/textures/awesome_face.png");
+        let face_texture = Texture::from_bytes(device, queue, face_bytes, Some("face"))?;
+
+        let textures = [&container_texture, &face_texture];
+        let cubes = CUBE_POSITIONS
+            .iter()
+            .map(|_pos| {
+                SceneObject::new(
+                    device,
+                    bind_group_layout,
+                    &textures,
+                    ModelUniform::default(),
+                )
+            })
+            .collect();
+
+        let outline_uniform = ModelUniform {
+            color: Vector4::new(1.0, 0.6, 0.0, 1.0),
+            ..ModelUniform::default()
+        };
+        let outline = SceneObject::new(device, bind_group_layout, &textures, outline_uniform);
+
+        Ok((cubes, outline))
+    }
+
+    pub async fn new(window: Window) -> Result<Self, Error> {
+        let (surface, device, queue, config, size) = Self::create_surface(&window).await?;
+
+        let cube_mesh = Mesh::new(&device, &create_cube_detail(1.0, 1.0, 1.0), "Cube");
+
+        let (camera, camera_buffer, camera_bind_group_layout, camera_bind_group) =
+            Self::create_camera(&device, size)?;
+
+        let model_bind_group_layout = Self::create_model_bind_group_layout(&device);
+        let (cubes, outline) = Self::create_objects(&device, &queue, &model_bind_group_layout)?;
+
+        let bind_group_layouts = [&camera_bind_group_layout, &model_bind_group_layout];
+        let normal_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::Normal,
+        );
+        let stencil_write_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::StencilWrite,
+        );
+        let outline_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::Outline,
+        );
+
+        let depth_texture = Texture::create_depth_texture_with_format(
+            &device,
+            size,
+            Texture::DEPTH_STENCIL_FORMAT,
+            Some("Depth Stencil Texture"),
+        );
+
+        Ok(Self {
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            size,
+
+            normal_pipeline,
+            stencil_write_pipeline,
+            outline_pipeline,
+
+            cube_mesh,
+            cubes,
+            outline,
+            selected: 0,
+
+            camera,
+            camera_buffer,
+            camera_bind_group,
+
+            depth_texture,
+
+            start_time: Instant::now(),
+        })
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&self.device, &self.config);
+
+            self.depth_texture = Texture::create_depth_texture_with_format(
+                &self.device,
+                self.size,
+                Texture::DEPTH_STENCIL_FORMAT,
+                Some("Depth Stencil Texture"),
+            );
+        }
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            // Press Tab to select the next cube.
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(VirtualKeyCode::Tab),
+                        ..
+                    },
+                ..
+            } => {
+                self.selected = (self.selected + 1) % self.cubes.len();
+                true
+            }
+            _ => self.camera.process_event(event),
+        }
+    }
+
+    pub fn update(&mut self) {
+        let dt = self.start_time.elapsed().as_secs_f32();
+
+        for (index, (cube, pos)) in self.cubes.iter_mut().zip(CUBE_POSITIONS).enumerate() {
+            let translate_model = Matrix4::from_translation(*pos);
+            let angle: f32 = 0.5 * (index + 1) as f32;
+            let radian = Rad(angle) * dt;
+            let rotate_model = Matrix4::from_angle_x(Rad(0.5) * (1.15 - dt.sin()))
+                * Matrix4::from_angle_y(radian)
+                * Matrix4::from_angle_z(Rad(0.0));
+            cube.uniform.model = translate_model * rotate_model;
+            cube.write_uniform(&self.queue);
+        }
+
+        self.outline.uniform.model =
+            self.cubes[self.selected].uniform.model * Matrix4::from_scale(OUTLINE_SCALE);
+        self.outline.write_uniform(&self.queue);
+
+        self.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(self.camera.uniform_ref()),
+        );
+    }
+
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.2,
+                            g: 0.3,
+                            b: 0.3,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_texture.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0),
+                        store: true,
+                    }),
+                }),
+            });
+
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            render_pass.set_stencil_reference(SELECTED_STENCIL_REF);
+
+            // 1. Draw other cubes as usual.
+            render_pass.set_pipeline(&self.normal_pipeline);
+            for (index, cube) in self.cubes.iter().enumerate() {
+                if index != self.selected {
+                    render_pass.set_bind_group(1, &cube.bind_group, &[]);
+                    self.cube_mesh.draw(&mut render_pass);
+                }
+            }
+
+            // 2. Draw the selected cube, marking its pixels in stencil buffer.
+            render_pass.set_pipeline(&self.stencil_write_pipeline);
+            render_pass.set_bind_group(1, &self.cubes[self.selected].bind_group, &[]);
+            self.cube_mesh.draw(&mut render_pass);
+
+            // 3. Draw a scaled up version of selected cube, only outside of the marked pixels.
+            render_pass.set_pipeline(&self.outline_pipeline);
+            render_pass.set_bind_group(1, &self.outline.bind_group, &[]);
+            self.cube_mesh.draw(&mut render_pass);
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+
+// Vertex Shader
+struct VertexInput {
+	@location(0) position: vec3<f32>,
+	@location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) tex_coords: vec2<f32>,
+};
+
+struct CameraUniform {
+	@location(0) view_proj: mat4x4<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> camera_uniform: CameraUniform;
+
+struct ModelUniform {
+	@location(0) model: mat4x4<f32>,
+	@location(1) color: vec4<f32>,
+}
+
+@group(1)
+@binding(0)
+var<uniform> model_uniform: ModelUniform;
+
+@vertex
+fn vs_main(
+	in: VertexInput,
+) -> VertexOutput {
+	var out: VertexOutput;
+	out.position = camera_uniform.view_proj * model_uniform.model * vec4<f32>(in.position, 1.0);
+	out.tex_coords = in.tex_coords;
+	return out;
+}
+
+// Fragment Shader
+struct FragmentInput {
+	@location(0) tex_coords: vec2<f32>,
+};
+
+@group(1)
+@binding(1)
+var object_texture: texture_2d<f32>;
+
+@group(1)
+@binding(2)
+var object_sampler: sampler;
+
+@fragment
+fn fs_main(in: FragmentInput) -> @location(0) vec4<f32> {
+	return textureSample(object_texture, object_sampler, in.tex_coords) * model_uniform.color;
+}
+
+// Used by vegetation, drop fragments which are (almost) fully transparent.
+@fragment
+fn fs_alpha_test(in: FragmentInput) -> @location(0) vec4<f32> {
+	let color = textureSample(object_texture, object_sampler, in.tex_coords);
+	if color.a < 0.1 {
+		discard;
+	}
+	return color * model_uniform.color;
+}
+
+// Used by windows, the color is blended with what is already in color target.
+@fragment
+fn fs_transparent(in: FragmentInput) -> @location(0) vec4<f32> {
+	return model_uniform.color;
+}
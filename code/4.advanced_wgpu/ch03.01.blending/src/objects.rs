@@ -0,0 +1,193 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+#![allow(dead_code)]
+
+use cgmath::{InnerSpace, Vector3, Vector4};
+use std::cmp::Ordering;
+use std::f32::consts::FRAC_PI_2;
+use wgpu::util::DeviceExt;
+
+use crate::geometry::GeometryData;
+use crate::texture::Texture;
+use crate::uniforms::ModelUniform;
+
+pub const CUBE_POSITIONS: &[Vector3<f32>] =
+    &[Vector3::new(-1.0, 0.0, -1.0), Vector3::new(2.0, 0.0, 0.0)];
+
+pub const VEGETATION_POSITIONS: &[Vector3<f32>] = &[
+    Vector3::new(-1.5, 0.0, -0.48),
+    Vector3::new(1.5, 0.0, 0.51),
+    Vector3::new(0.0, 0.0, 0.7),
+    Vector3::new(-0.3, 0.0, -2.3),
+    Vector3::new(0.5, 0.0, -0.6),
+];
+
+/// Semi-transparent windows, placed so that they overlap each other
+/// when viewed from the default camera position.
+pub const WINDOWS: &[(Vector3<f32>, Vector4<f32>)] = &[
+    (
+        Vector3::new(-0.8, 0.0, 1.2),
+        Vector4::new(0.9, 0.2, 0.2, 0.4),
+    ),
+    (
+        Vector3::new(-0.3, 0.0, 1.7),
+        Vector4::new(0.2, 0.9, 0.2, 0.4),
+    ),
+    (
+        Vector3::new(0.2, 0.0, 2.2),
+        Vector4::new(0.2, 0.2, 0.9, 0.4),
+    ),
+    (
+        Vector3::new(1.2, 0.0, 1.4),
+        Vector4::new(0.9, 0.9, 0.2, 0.4),
+    ),
+    (
+        Vector3::new(-1.8, 0.0, 0.3),
+        Vector4::new(0.2, 0.9, 0.9, 0.4),
+    ),
+];
+
+/// The floor is a horizontal plane put under the cubes.
+pub const FLOOR_POSITION: Vector3<f32> = Vector3::new(0.0, -0.5, 0.0);
+
+/// Rotation applied to `create_plane()` to make it stand up, facing +Z.
+pub const QUAD_ROTATION: Vector3<f32> = Vector3::new(FRAC_PI_2, 0.0, 0.0);
+
+#[derive(Debug)]
+pub struct Mesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+}
+
+impl Mesh {
+    pub fn new(device: &wgpu::Device, geometry_data: &GeometryData, label: &str) -> Self {
+        let vertices = geometry_data.vertex_data();
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{label} Vertex Buffer")),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{label} Index Buffer")),
+            contents: bytemuck::cast_slice(&geometry_data.indices16),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+        let num_indices = geometry_data.indices16.len() as u32;
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            num_indices,
+        }
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshKind {
+    Cube,
+    Floor,
+    Quad,
+}
+
+/// How an object is blended into the color target.
+///
+/// Objects are drawn in the order of these variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderMode {
+    /// Replaces the color target and writes depth.
+    Opaque,
+
+    /// Fragments with low alpha are discarded, others are drawn as opaque.
+    ///
+    /// Used for vegetation, which is either fully transparent or fully opaque.
+    AlphaTest,
+
+    /// Blended with `src_alpha`, without depth writes.
+    ///
+    /// These objects have to be drawn after all of the opaque ones,
+    /// sorted from back to front.
+    Transparent,
+}
+
+#[derive(Debug)]
+pub struct SceneObject {
+    pub mesh: MeshKind,
+    pub mode: RenderMode,
+    pub position: Vector3<f32>,
+    pub uniform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl SceneObject {
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        texture: &Texture,
+        mesh: MeshKind,
+        mode: RenderMode,
+        uniform: &ModelUniform,
+        position: Vector3<f32>,
+    ) -> Self {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Model Uniform Buffer"),
+            contents: bytemuck::cast_slice(uniform.as_ref()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                },
+            ],
+            label: Some("Model Bind Group"),
+        });
+
+        Self {
+            mesh,
+            mode,
+            position,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn distance2(&self, eye: Vector3<f32>) -> f32 {
+        (self.position - eye).magnitude2()
+    }
+}
+
+/// Opaque objects go first, then the alpha tested ones, and transparent
+/// objects come last, sorted from back to front relative to camera `eye` position.
+pub fn sort_objects(objects: &mut [SceneObject], eye: Vector3<f32>) {
+    objects.sort_by(|a, b| {
+        a.mode.cmp(&b.mode).then_with(|| {
+            if a.mode == RenderMode::Transparent {
+                b.distance2(eye)
+                    .partial_cmp(&a.distance2(eye))
+                    .unwrap_or(Ordering::Equal)
+            } else {
+                Ordering::Equal
+            }
+        })
+    });
+}
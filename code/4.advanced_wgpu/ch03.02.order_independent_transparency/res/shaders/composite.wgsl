@@ -0,0 +1,42 @@
+
+// Vertex Shader
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+};
+
+// Draw a triangle which covers the whole screen, without any vertex buffer.
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+	let x = f32(i32(vertex_index & 1u) * 4 - 1);
+	let y = f32(i32(vertex_index & 2u) * 2 - 1);
+
+	var out: VertexOutput;
+	out.position = vec4<f32>(x, y, 0.0, 1.0);
+	return out;
+}
+
+// Fragment Shader
+@group(0)
+@binding(0)
+var accum_texture: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var revealage_texture: texture_2d<f32>;
+
+const EPSILON: f32 = 0.00001;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+	let coords = vec2<i32>(in.position.xy);
+	let revealage = textureLoad(revealage_texture, coords, 0).r;
+
+	// Nothing transparent covers this pixel.
+	if revealage >= 1.0 - EPSILON {
+		discard;
+	}
+
+	let accum = textureLoad(accum_texture, coords, 0);
+	let average_color = accum.rgb / max(accum.a, EPSILON);
+	return vec4(average_color, 1.0 - revealage);
+}
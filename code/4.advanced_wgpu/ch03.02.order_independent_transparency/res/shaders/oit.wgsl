@@ -0,0 +1,84 @@
+
+// Vertex Shader
+struct VertexInput {
+	@location(0) position: vec3<f32>,
+	@location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) tex_coords: vec2<f32>,
+};
+
+struct CameraUniform {
+	@location(0) view_proj: mat4x4<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> camera_uniform: CameraUniform;
+
+struct ModelUniform {
+	@location(0) model: mat4x4<f32>,
+	@location(1) color: vec4<f32>,
+}
+
+@group(1)
+@binding(0)
+var<uniform> model_uniform: ModelUniform;
+
+@vertex
+fn vs_main(
+	in: VertexInput,
+) -> VertexOutput {
+	var out: VertexOutput;
+	out.position = camera_uniform.view_proj * model_uniform.model * vec4<f32>(in.position, 1.0);
+	out.tex_coords = in.tex_coords;
+	return out;
+}
+
+// Fragment Shader
+struct FragmentInput {
+	@location(0) tex_coords: vec2<f32>,
+};
+
+@group(1)
+@binding(1)
+var object_texture: texture_2d<f32>;
+
+@group(1)
+@binding(2)
+var object_sampler: sampler;
+
+@fragment
+fn fs_main(in: FragmentInput) -> @location(0) vec4<f32> {
+	return textureSample(object_texture, object_sampler, in.tex_coords) * model_uniform.color;
+}
+
+// Used by the sorted path, the color is blended with what is already in color target.
+@fragment
+fn fs_transparent(in: FragmentInput) -> @location(0) vec4<f32> {
+	return model_uniform.color;
+}
+
+// Weighted blended order independent transparency.
+// See https://jcgt.org/published/0002/02/09/
+struct AccumOutput {
+	@location(0) accum: vec4<f32>,
+	@location(1) revealage: f32,
+};
+
+@fragment
+fn fs_accum(@builtin(position) frag_coord: vec4<f32>, in: FragmentInput) -> AccumOutput {
+	let color = model_uniform.color;
+
+	// Fragments closer to camera and less transparent get a larger weight.
+	let z = frag_coord.z;
+	let weight = clamp(pow(min(1.0, color.a * 10.0) + 0.01, 3.0) * 1e8 *
+		pow(1.0 - z * 0.9, 3.0), 1e-2, 3e3);
+
+	var out: AccumOutput;
+	out.accum = vec4(color.rgb * color.a, color.a) * weight;
+	out.revealage = color.a;
+	return out;
+}
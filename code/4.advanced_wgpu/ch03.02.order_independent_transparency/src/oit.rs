@@ -0,0 +1,350 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Weighted blended order independent transparency.
+//!
+//! Transparent objects are drawn in any order into two offscreen targets:
+//! - accumulation, sum of weighted premultiplied colors
+//! - revealage, product of `1 - alpha`, how much of the background is still visible
+//!
+//! Then a fullscreen composite pass blends the averaged color onto the opaque scene.
+
+use winit::dpi::PhysicalSize;
+
+use crate::texture::Texture;
+use crate::vertex::Vertex;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TransparencyMode {
+    /// Sort objects from back to front by their center, and blend them one by one.
+    ///
+    /// Breaks down for intersecting objects.
+    Sorted,
+
+    /// Weighted blended order independent transparency.
+    #[default]
+    WeightedBlended,
+}
+
+#[derive(Debug)]
+pub struct WeightedBlendedOit {
+    pub accum_pipeline: wgpu::RenderPipeline,
+    pub composite_pipeline: wgpu::RenderPipeline,
+
+    pub accum_view: wgpu::TextureView,
+    pub revealage_view: wgpu::TextureView,
+    pub composite_bind_group: wgpu::BindGroup,
+    composite_bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl WeightedBlendedOit {
+    pub const ACCUM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    pub const REVEALAGE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+
+    /// Accumulation and revealage targets use different blend states, and
+    /// accumulation target is blended as float, which WebGL2 lacks.
+    #[must_use]
+    pub fn is_supported(adapter: &wgpu::Adapter) -> bool {
+        let independent_blend = adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::INDEPENDENT_BLEND);
+        let accum_features = adapter.get_texture_format_features(Self::ACCUM_FORMAT);
+        let blendable_accum = accum_features
+            .allowed_usages
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            && accum_features
+                .flags
+                .contains(wgpu::TextureFormatFeatureFlags::BLENDABLE);
+        independent_blend && blendable_accum
+    }
+
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        size: PhysicalSize<u32>,
+    ) -> Self {
+        let accum_pipeline = Self::create_accum_pipeline(device, bind_group_layouts);
+
+        let composite_bind_group_layout = Self::create_composite_bind_group_layout(device);
+        let composite_pipeline =
+            Self::create_composite_pipeline(device, config, &composite_bind_group_layout);
+
+        let (accum_view, revealage_view, composite_bind_group) =
+            Self::create_targets(device, &composite_bind_group_layout, size);
+
+        Self {
+            accum_pipeline,
+            composite_pipeline,
+
+            accum_view,
+            revealage_view,
+            composite_bind_group,
+            composite_bind_group_layout,
+        }
+    }
+
+    /// Offscreen targets have to follow the size of surface.
+    pub fn resize(&mut self, device: &wgpu::Device, size: PhysicalSize<u32>) {
+        let (accum_view, revealage_view, composite_bind_group) =
+            Self::create_targets(device, &self.composite_bind_group_layout, size);
+        self.accum_view = accum_view;
+        self.revealage_view = revealage_view;
+        self.composite_bind_group = composite_bind_group;
+    }
+
+    /// Clear values of accumulation and revealage targets.
+    pub const fn clear_colors() -> (wgpu::Color, wgpu::Color) {
+        (wgpu::Color::TRANSPARENT, wgpu::Color::WHITE)
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        size: PhysicalSize<u32>,
+    ) -> (wgpu::TextureView, wgpu::TextureView, wgpu::BindGroup) {
+        let create_view = |format: wgpu::TextureFormat, label: &str| {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            texture.create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        let accum_view = create_view(Self::ACCUM_FORMAT, "OIT Accumulation Texture");
+        let revealage_view = create_view(Self::REVEALAGE_FORMAT, "OIT Revealage Texture");
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&accum_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&revealage_view),
+                },
+            ],
+            label: Some("OIT Composite Bind Group"),
+        });
+
+        (accum_view, revealage_view, bind_group)
+    }
+
+    fn create_composite_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        let texture_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            },
+            count: None,
+        };
+
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[texture_entry(0), texture_entry(1)],
+            label: Some("OIT Composite Bind Group Layout"),
+        })
+    }
+
+    fn create_accum_pipeline(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("OIT Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../res/shaders/oit.wgsl").into()),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("OIT Accumulation Pipeline Layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+
+        // accum: sum of all colors.
+        let accum_blend = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+        };
+        // revealage: dst * (1 - src_alpha)
+        let revealage_blend = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Zero,
+                dst_factor: wgpu::BlendFactor::OneMinusSrc,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("OIT Accumulation Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_accum",
+                targets: &[
+                    Some(wgpu::ColorTargetState {
+                        format: Self::ACCUM_FORMAT,
+                        blend: Some(accum_blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                    Some(wgpu::ColorTargetState {
+                        format: Self::REVEALAGE_FORMAT,
+                        blend: Some(revealage_blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                ],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            // Test against depth of opaque objects, without writing to it.
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
+
+    fn create_composite_pipeline(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("OIT Composite Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../res/shaders/composite.wgsl").into()),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("OIT Composite Pipeline Layout"),
+                bind_group_layouts: &[bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("OIT Composite Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
+
+    /// Draw all of the transparent objects into accumulation and revealage targets.
+    pub fn begin_accum_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        depth_view: &'a wgpu::TextureView,
+    ) -> wgpu::RenderPass<'a> {
+        let (accum_clear, revealage_clear) = Self::clear_colors();
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("OIT Accumulation Pass"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &self.accum_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(accum_clear),
+                        store: true,
+                    },
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &self.revealage_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(revealage_clear),
+                        store: true,
+                    },
+                }),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: false,
+                }),
+                stencil_ops: None,
+            }),
+        });
+        render_pass.set_pipeline(&self.accum_pipeline);
+        render_pass
+    }
+
+    /// Blend averaged transparent color onto the opaque scene in `view`.
+    pub fn composite(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("OIT Composite Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.composite_pipeline);
+        render_pass.set_bind_group(0, &self.composite_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,600 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::Vector3;
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use instant::Instant;
+use wgpu::util::DeviceExt;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::window::Window;
+
+use crate::camera::Camera;
+use crate::frames::TransparencyWindow;
+use crate::geometry::{create_cube_detail, create_plane_detail, create_sphere_detail};
+use crate::objects::{
+    sort_objects, Mesh, MeshKind, RenderMode, SceneObject, CUBE_POSITIONS, FLOOR_POSITION, SPHERES,
+};
+use crate::oit::{TransparencyMode, WeightedBlendedOit};
+use crate::texture::Texture;
+use crate::uniforms::ModelUniform;
+use crate::vertex::Vertex;
+use crate::Error;
+
+pub struct State {
+    surface: wgpu::Surface,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    size: PhysicalSize<u32>,
+    window: Window,
+
+    opaque_pipeline: wgpu::RenderPipeline,
+    transparent_pipeline: wgpu::RenderPipeline,
+    /// None if not supported by adapter, like on WebGL2.
+    oit: Option<WeightedBlendedOit>,
+
+    cube_mesh: Mesh,
+    floor_mesh: Mesh,
+    sphere_mesh: Mesh,
+    objects: Vec<SceneObject>,
+
+    camera: Camera,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+
+    depth_texture: Texture,
+
+    start_time: Instant,
+    egui_platform: Platform,
+    egui_render_pass: RenderPass,
+    transparency_window: TransparencyWindow,
+}
+
+impl State {
+    async fn create_surface(
+        window: &Window,
+    ) -> Result<
+        (
+            wgpu::Surface,
+            wgpu::Device,
+            wgpu::Queue,
+            wgpu::SurfaceConfiguration,
+            PhysicalSize<u32>,
+            bool,
+        ),
+        Error,
+    > {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let surface = unsafe { instance.create_surface(&window) }?;
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| {
+                Error::Others("Failed to get an approprivate wgpu adapter".to_owned())
+            })?;
+
+        let oit_supported = WeightedBlendedOit::is_supported(&adapter);
+        if !oit_supported {
+            log::warn!("Weighted blended OIT is not supported, fall back to sorting objects");
+        }
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    features: if cfg!(target_arch = "wasm32") {
+                        wgpu::Features::empty()
+                    } else {
+                        wgpu::Features::POLYGON_MODE_LINE
+                    },
+                    limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                },
+                None,
+            )
+            .await?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(wgpu::TextureFormat::is_srgb)
+            .unwrap_or(surface_caps.formats[0]);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&device, &config);
+
+        Ok((surface, device, queue, config, size, oit_supported))
+    }
+
+    fn create_render_pipeline(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        mode: RenderMode,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../res/shaders/oit.wgsl").into()),
+        });
+
+        let (label, fs_entry_point, blend, depth_write_enabled) = match mode {
+            RenderMode::Opaque => (
+                "Opaque Render Pipeline",
+                "fs_main",
+                wgpu::BlendState::REPLACE,
+                true,
+            ),
+            // Transparent objects still test against the depth buffer,
+            // but do not write to it, or else objects behind them are hidden.
+            RenderMode::Transparent => (
+                "Transparent Render Pipeline",
+                "fs_transparent",
+                wgpu::BlendState::ALPHA_BLENDING,
+                false,
+            ),
+        };
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: fs_entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(blend),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        render_pipeline
+    }
+
+    fn create_meshes(device: &wgpu::Device) -> (Mesh, Mesh, Mesh) {
+        let cube_mesh = Mesh::new(device, &create_cube_detail(1.0, 1.0, 1.0), "Cube");
+        let floor_mesh = Mesh::new(device, &create_plane_detail(10.0, 10.0, 1.0, 1.0), "Floor");
+        let sphere_mesh = Mesh::new(device, &create_sphere_detail(0.5, 30, 30), "Sphere");
+        (cube_mesh, floor_mesh, sphere_mesh)
+    }
+
+    fn create_camera(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+    ) -> Result<(Camera, wgpu::Buffer, wgpu::BindGroupLayout, wgpu::BindGroup), Error> {
+        let eye_pos = (0.0, 1.5, 5.0).into();
+        let aspect = size.width as f32 / size.height as f32;
+        let camera = Camera::new(eye_pos, aspect);
+
+        let uniform_ref = camera.uniform_ref();
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Uniform Buffer"),
+            contents: bytemuck::cast_slice(uniform_ref),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("camera_bind_group_layout"),
+            });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("camera_bind_group"),
+        });
+
+        Ok((
+            camera,
+            camera_buffer,
+            camera_bind_group_layout,
+            camera_bind_group,
+        ))
+    }
+
+    fn create_model_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("model_bind_group_layout"),
+        })
+    }
+
+    fn create_objects(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Result<Vec<SceneObject>, Error> {
+        let container_bytes = include_bytes!("../res/textures/container.jpg");
+        let container_texture =
+            Texture::from_bytes(device, queue, container_bytes, Some("container"))?;
+
+        let no_rotation = Vector3::new(0.0, 0.0, 0.0);
+        let white = (1.0, 1.0, 1.0, 1.0).into();
+        let mut objects = Vec::new();
+
+        objects.push(SceneObject::new(
+            device,
+            bind_group_layout,
+            &container_texture,
+            MeshKind::Floor,
+            RenderMode::Opaque,
+            &ModelUniform::new(FLOOR_POSITION, no_rotation, white),
+            FLOOR_POSITION,
+        ));
+
+        for pos in CUBE_POSITIONS {
+            objects.push(SceneObject::new(
+                device,
+                bind_group_layout,
+                &container_texture,
+                MeshKind::Cube,
+                RenderMode::Opaque,
+                &ModelUniform::new(*pos, no_rotation, white),
+                *pos,
+            ));
+        }
+
+        // The transparent shaders only read the tint color, texture is bound to keep
+        // the same bind group layout.
+        for (pos, color) in SPHERES {
+            objects.push(SceneObject::new(
+                device,
+                bind_group_layout,
+                &container_texture,
+                MeshKind::Sphere,
+                RenderMode::Transparent,
+                &ModelUniform::new(*pos, no_rotation, *color),
+                *pos,
+            ));
+        }
+
+        Ok(objects)
+    }
+
+    fn create_egui_platform(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+        oit_supported: bool,
+    ) -> (Platform, RenderPass, TransparencyWindow) {
+        let platform = Platform::new(PlatformDescriptor {
+            physical_width: size.width,
+            physical_height: size.height,
+            scale_factor,
+            ..Default::default()
+        });
+
+        let render_pass = RenderPass::new(device, surface_format, 1);
+        let transparency_window = TransparencyWindow::new(oit_supported);
+
+        (platform, render_pass, transparency_window)
+    }
+
+    pub async fn new(window: Window) -> Result<Self, Error> {
+        let (surface, device, queue, config, size, oit_supported) =
+            Self::create_surface(&window).await?;
+
+        let (cube_mesh, floor_mesh, sphere_mesh) = Self::create_meshes(&device);
+
+        let (camera, camera_buffer, camera_bind_group_layout, camera_bind_group) =
+            Self::create_camera(&device, size)?;
+
+        let model_bind_group_layout = Self::create_model_bind_group_layout(&device);
+        let mut objects = Self::create_objects(&device, &queue, &model_bind_group_layout)?;
+        sort_objects(&mut objects, camera.position());
+
+        let bind_group_layouts = [&camera_bind_group_layout, &model_bind_group_layout];
+        let opaque_pipeline =
+            Self::create_render_pipeline(&device, &config, &bind_group_layouts, RenderMode::Opaque);
+        let transparent_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            RenderMode::Transparent,
+        );
+
+        let oit = oit_supported
+            .then(|| WeightedBlendedOit::new(&device, &config, &bind_group_layouts, size));
+
+        let depth_texture = Texture::create_depth_texture(&device, size, Some("Depth Texture"));
+
+        let (egui_platform, egui_render_pass, transparency_window) = Self::create_egui_platform(
+            &device,
+            config.format,
+            size,
+            window.scale_factor(),
+            oit_supported,
+        );
+
+        Ok(Self {
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            size,
+
+            opaque_pipeline,
+            transparent_pipeline,
+            oit,
+
+            cube_mesh,
+            floor_mesh,
+            sphere_mesh,
+            objects,
+
+            camera,
+            camera_buffer,
+            camera_bind_group,
+
+            depth_texture,
+
+            start_time: Instant::now(),
+            egui_platform,
+            egui_render_pass,
+            transparency_window,
+        })
+    }
+
+    fn objects_with_mode(&self, mode: RenderMode) -> impl Iterator<Item = &SceneObject> {
+        self.objects
+            .iter()
+            .filter(move |object| object.mode == mode)
+    }
+
+    fn mesh(&self, kind: MeshKind) -> &Mesh {
+        match kind {
+            MeshKind::Cube => &self.cube_mesh,
+            MeshKind::Floor => &self.floor_mesh,
+            MeshKind::Sphere => &self.sphere_mesh,
+        }
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn platform_mut(&mut self) -> &mut Platform {
+        &mut self.egui_platform
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&self.device, &self.config);
+
+            self.depth_texture =
+                Texture::create_depth_texture(&self.device, self.size, Some("Depth Texture"));
+            if let Some(oit) = &mut self.oit {
+                oit.resize(&self.device, self.size);
+            }
+        }
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        self.camera.process_event(event)
+    }
+
+    pub fn update(&mut self) {
+        let dt = self.start_time.elapsed().as_secs_f64();
+        self.egui_platform.update_time(dt);
+
+        // Camera may have moved, so the transparent objects are sorted again.
+        // Weighted blended OIT does not depend on drawing order.
+        if self.transparency_window.mode == TransparencyMode::Sorted {
+            sort_objects(&mut self.objects, self.camera.position());
+        }
+
+        self.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(self.camera.uniform_ref()),
+        );
+    }
+
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.2,
+                            g: 0.3,
+                            b: 0.3,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_texture.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+
+            render_pass.set_pipeline(&self.opaque_pipeline);
+            for object in self.objects_with_mode(RenderMode::Opaque) {
+                render_pass.set_bind_group(1, &object.bind_group, &[]);
+                self.mesh(object.mesh).draw(&mut render_pass);
+            }
+
+            if self.transparency_window.mode == TransparencyMode::Sorted {
+                render_pass.set_pipeline(&self.transparent_pipeline);
+                for object in self.objects_with_mode(RenderMode::Transparent) {
+                    render_pass.set_bind_group(1, &object.bind_group, &[]);
+                    self.mesh(object.mesh).draw(&mut render_pass);
+                }
+            }
+        }
+
+        if let Some(oit) = self
+            .oit
+            .as_ref()
+            .filter(|_| self.transparency_window.mode == TransparencyMode::WeightedBlended)
+        {
+            {
+                let mut render_pass = oit.begin_accum_pass(&mut encoder, &self.depth_texture.view);
+                render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+                for object in self.objects_with_mode(RenderMode::Transparent) {
+                    render_pass.set_bind_group(1, &object.bind_group, &[]);
+                    self.mesh(object.mesh).draw(&mut render_pass);
+                }
+            }
+            oit.composite(&mut encoder, &view);
+        }
+
+        // Draw the egui UI frame.
+        {
+            self.egui_platform.begin_frame();
+            self.transparency_window.ui(&self.egui_platform.context());
+            let full_output = self.egui_platform.end_frame(Some(&self.window));
+            let paint_jobs = self.egui_platform.context().tessellate(full_output.shapes);
+            // Upload all resources for the GPU.
+            let screen_descriptor = ScreenDescriptor {
+                physical_width: self.config.width,
+                physical_height: self.config.height,
+                scale_factor: self.window.scale_factor() as f32,
+            };
+            let tdelta: egui::TexturesDelta = full_output.textures_delta;
+            self.egui_render_pass
+                .add_textures(&self.device, &self.queue, &tdelta)
+                .expect("add texture ok");
+            self.egui_render_pass.update_buffers(
+                &self.device,
+                &self.queue,
+                &paint_jobs,
+                &screen_descriptor,
+            );
+
+            // Record all render passes.
+            self.egui_render_pass
+                .execute(&mut encoder, &view, &paint_jobs, &screen_descriptor, None)
+                .unwrap();
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+}
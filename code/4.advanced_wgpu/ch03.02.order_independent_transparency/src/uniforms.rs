@@ -0,0 +1,42 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Matrix4, One, Vector3, Vector4};
+use std::mem;
+
+use crate::transforms;
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelUniform {
+    pub model: Matrix4<f32>,
+    pub color: Vector4<f32>,
+}
+
+impl Default for ModelUniform {
+    fn default() -> Self {
+        Self {
+            model: Matrix4::one(),
+            color: Vector4::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl ModelUniform {
+    pub fn new(position: Vector3<f32>, rotation: Vector3<f32>, color: Vector4<f32>) -> Self {
+        let mut model = Matrix4::one();
+        transforms::translate(&mut model, position);
+        transforms::rotate(&mut model, rotation);
+        Self { model, color }
+    }
+}
+
+pub type ModelUniformBytes = [f32; 20];
+pub type ModelUniformRef<'a> = &'a ModelUniformBytes;
+
+impl AsRef<ModelUniformBytes> for ModelUniform {
+    fn as_ref(&self) -> ModelUniformRef {
+        unsafe { mem::transmute(self) }
+    }
+}
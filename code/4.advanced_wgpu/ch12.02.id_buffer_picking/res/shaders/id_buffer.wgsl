@@ -0,0 +1,114 @@
+
+// Vertex Shader
+struct VertexInput {
+	@location(0) position: vec3<f32>,
+	@location(1) tex_coords: vec2<f32>,
+}
+
+struct InstanceInput {
+	@location(2) model_0: vec4<f32>,
+	@location(3) model_1: vec4<f32>,
+	@location(4) model_2: vec4<f32>,
+	@location(5) model_3: vec4<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) tex_coords: vec2<f32>,
+	@location(1) @interpolate(flat) object_id: u32,
+};
+
+struct CameraUniform {
+	@location(0) view_proj: mat4x4<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> camera_uniform: CameraUniform;
+
+// How much the outline is larger than the selected object.
+const OUTLINE_SCALE: f32 = 1.08;
+
+const OUTLINE_COLOR: vec4<f32> = vec4<f32>(1.0, 0.6, 0.0, 1.0);
+
+fn transform(in: VertexInput, instance: InstanceInput, instance_index: u32, scale: f32) -> VertexOutput {
+	let model = mat4x4<f32>(
+		instance.model_0,
+		instance.model_1,
+		instance.model_2,
+		instance.model_3,
+	);
+
+	var out: VertexOutput;
+	out.position = camera_uniform.view_proj * model * vec4<f32>(in.position * scale, 1.0);
+	out.tex_coords = in.tex_coords;
+	// 0 is reserved for background.
+	out.object_id = instance_index + 1u;
+	return out;
+}
+
+@vertex
+fn vs_main(
+	in: VertexInput,
+	instance: InstanceInput,
+	@builtin(instance_index) instance_index: u32,
+) -> VertexOutput {
+	return transform(in, instance, instance_index, 1.0);
+}
+
+@vertex
+fn vs_outline(
+	in: VertexInput,
+	instance: InstanceInput,
+	@builtin(instance_index) instance_index: u32,
+) -> VertexOutput {
+	return transform(in, instance, instance_index, OUTLINE_SCALE);
+}
+
+// Fragment Shader
+struct FragmentInput {
+	@location(0) tex_coords: vec2<f32>,
+	@location(1) @interpolate(flat) object_id: u32,
+};
+
+// Color is written into surface texture and object id into id buffer.
+struct FragmentOutput {
+	@location(0) color: vec4<f32>,
+	@location(1) object_id: u32,
+}
+
+@group(1)
+@binding(0)
+var container_texture: texture_2d<f32>;
+
+@group(1)
+@binding(1)
+var container_sampler: sampler;
+
+@group(1)
+@binding(2)
+var face_texture: texture_2d<f32>;
+
+@group(1)
+@binding(3)
+var face_sampler: sampler;
+
+@fragment
+fn fs_main(in: FragmentInput) -> FragmentOutput {
+	var out: FragmentOutput;
+	out.color =
+		mix(textureSample(container_texture, container_sampler, in.tex_coords),
+			textureSample(face_texture, face_sampler, in.tex_coords),
+			0.2);
+	out.object_id = in.object_id;
+	return out;
+}
+
+// Outline of the selected object, filled with a single color.
+@fragment
+fn fs_outline(in: FragmentInput) -> FragmentOutput {
+	var out: FragmentOutput;
+	out.color = OUTLINE_COLOR;
+	out.object_id = in.object_id;
+	return out;
+}
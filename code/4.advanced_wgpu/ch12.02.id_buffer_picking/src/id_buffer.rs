@@ -0,0 +1,217 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Pixel exact picking with an object ID render target.
+//!
+//! Objects write their ID into an extra `R32Uint` color attachment.
+//! When a pixel is requested, it is copied into a small buffer at the end
+//! of the frame, and the buffer is mapped asynchronously, so that rendering
+//! never waits for the GPU.
+
+use std::sync::{Arc, Mutex};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+type MapResult = Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadbackState {
+    Idle,
+
+    /// Waiting for the next frame to copy this pixel.
+    Requested {
+        x: u32,
+        y: u32,
+    },
+
+    /// Copy command is recorded, buffer will be mapped after submitted.
+    Copied,
+
+    /// `map_async()` is called, waiting for its callback.
+    Mapping,
+}
+
+#[derive(Debug)]
+pub struct IdBuffer {
+    texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    size: PhysicalSize<u32>,
+
+    readback_buffer: wgpu::Buffer,
+    state: ReadbackState,
+    map_result: MapResult,
+}
+
+impl IdBuffer {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+    /// Value of pixels not covered by any object.
+    pub const BACKGROUND_ID: u32 = 0;
+
+    pub fn new(device: &wgpu::Device, size: PhysicalSize<u32>) -> Self {
+        let (texture, view) = Self::create_texture(device, size);
+
+        // `bytes_per_row` has to be aligned, even if only one pixel is copied.
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Object ID Readback Buffer"),
+            size: u64::from(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            texture,
+            view,
+            size,
+
+            readback_buffer,
+            state: ReadbackState::Idle,
+            map_result: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Object ID Texture"),
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[Self::FORMAT],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, size: PhysicalSize<u32>) {
+        let (texture, view) = Self::create_texture(device, size);
+        self.texture = texture;
+        self.view = view;
+        self.size = size;
+    }
+
+    /// Shader writes `instance_index + 1` as object ID of each instance.
+    #[must_use]
+    pub const fn instance_index(object_id: u32) -> Option<u32> {
+        if object_id == Self::BACKGROUND_ID {
+            None
+        } else {
+            Some(object_id - 1)
+        }
+    }
+
+    pub fn color_attachment(&self) -> wgpu::RenderPassColorAttachment {
+        wgpu::RenderPassColorAttachment {
+            view: &self.view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                store: true,
+            },
+        }
+    }
+
+    /// Request to read object ID at `cursor` position.
+    ///
+    /// Returns false if previous request is still in progress.
+    pub fn request(&mut self, cursor: PhysicalPosition<f64>) -> bool {
+        if self.state != ReadbackState::Idle {
+            return false;
+        }
+        if cursor.x < 0.0 || cursor.y < 0.0 {
+            return false;
+        }
+        self.state = ReadbackState::Requested {
+            x: cursor.x as u32,
+            y: cursor.y as u32,
+        };
+        true
+    }
+
+    /// Record copy command of the requested pixel.
+    ///
+    /// Shall be called after the render pass which writes object IDs.
+    pub fn copy_pixel(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let ReadbackState::Requested { x, y } = self.state else {
+            return;
+        };
+        // Window may be resized after the request.
+        if x >= self.size.width || y >= self.size.height {
+            self.state = ReadbackState::Idle;
+            return;
+        }
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.state = ReadbackState::Copied;
+    }
+
+    /// Start mapping readback buffer.
+    ///
+    /// Shall be called after the command buffer is submitted to queue.
+    pub fn map_async(&mut self) {
+        if self.state != ReadbackState::Copied {
+            return;
+        }
+
+        let map_result = Arc::clone(&self.map_result);
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                if let Ok(mut map_result) = map_result.lock() {
+                    *map_result = Some(result);
+                }
+            });
+        self.state = ReadbackState::Mapping;
+    }
+
+    /// Check whether the requested object ID is available, without blocking.
+    pub fn poll(&mut self, device: &wgpu::Device) -> Option<u32> {
+        if self.state != ReadbackState::Mapping {
+            return None;
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+        let result = self.map_result.lock().ok()?.take()?;
+        self.state = ReadbackState::Idle;
+        if let Err(err) = result {
+            log::warn!("Failed to map object ID buffer: {err:?}");
+            return None;
+        }
+
+        let object_id = {
+            let data = self.readback_buffer.slice(..).get_mapped_range();
+            u32::from_ne_bytes([data[0], data[1], data[2], data[3]])
+        };
+        self.readback_buffer.unmap();
+        Some(object_id)
+    }
+}
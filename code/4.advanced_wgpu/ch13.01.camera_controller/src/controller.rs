@@ -0,0 +1,37 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use winit::event::{DeviceEvent, WindowEvent};
+
+use crate::camera::Camera;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Rotate around target point.
+    #[default]
+    Orbit,
+
+    /// First person camera, flying freely in the scene.
+    Fly,
+}
+
+/// Moves camera around according to user input.
+pub trait CameraController {
+    /// Called when this controller becomes the active one, so that its internal
+    /// state can be synced with the current camera.
+    fn activate(&mut self, camera: &Camera);
+
+    /// Returns true if event is consumed.
+    fn process_event(&mut self, camera: &mut Camera, event: &WindowEvent) -> bool;
+
+    /// Raw device events, like relative mouse motion when cursor is grabbed.
+    fn process_device_event(&mut self, _camera: &mut Camera, _event: &DeviceEvent) -> bool {
+        false
+    }
+
+    /// Whether cursor shall be grabbed and hidden in window.
+    fn cursor_grabbed(&self) -> bool {
+        false
+    }
+}
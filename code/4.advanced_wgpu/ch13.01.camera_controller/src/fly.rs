@@ -0,0 +1,136 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Deg, InnerSpace, Point3, Rad, Vector3};
+use winit::event::{
+    DeviceEvent, ElementState, KeyboardInput, MouseButton, MouseScrollDelta, TouchPhase,
+    VirtualKeyCode, WindowEvent,
+};
+
+use crate::camera::Camera;
+use crate::controller::CameraController;
+
+/// Looking straight up or down makes view matrix degenerate.
+const MAX_PITCH: Deg<f32> = Deg(89.0);
+
+/// First person camera.
+///
+/// Hold right mouse button to look around, move with A/D/W/S keys,
+/// Space to fly up and Ctrl to fly down.
+#[derive(Debug)]
+pub struct FlyController {
+    yaw: Rad<f32>,
+    pitch: Rad<f32>,
+
+    move_speed: f32,
+    scroll_speed: f32,
+    mouse_sensitivity: f32,
+    looking: bool,
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        Self {
+            yaw: Rad(0.0),
+            pitch: Rad(0.0),
+
+            move_speed: 0.10,
+            scroll_speed: 0.12,
+            mouse_sensitivity: 0.003,
+            looking: false,
+        }
+    }
+}
+
+impl CameraController for FlyController {
+    fn activate(&mut self, camera: &Camera) {
+        let forward = camera.forward();
+        self.yaw = Rad(forward.z.atan2(forward.x));
+        let max_pitch: Rad<f32> = MAX_PITCH.into();
+        let pitch = forward.y.clamp(-1.0, 1.0).asin();
+        self.pitch = Rad(pitch.clamp(-max_pitch.0, max_pitch.0));
+        self.looking = false;
+    }
+
+    fn process_event(&mut self, camera: &mut Camera, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state: ElementState::Pressed,
+                        virtual_keycode: Some(keycode),
+                        ..
+                    },
+                ..
+            } => self.process_key_event(camera, *keycode),
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::LineDelta(_horizontal, vertical),
+                phase: TouchPhase::Moved,
+                ..
+            } => {
+                let eye = camera.eye() + self.forward() * self.scroll_speed * *vertical;
+                self.apply(camera, eye);
+                true
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Right,
+                ..
+            } => {
+                self.looking = *state == ElementState::Pressed;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn process_device_event(&mut self, camera: &mut Camera, event: &DeviceEvent) -> bool {
+        match event {
+            DeviceEvent::MouseMotion { delta: (dx, dy) } if self.looking => {
+                self.yaw += Rad(*dx as f32 * self.mouse_sensitivity);
+                self.pitch -= Rad(*dy as f32 * self.mouse_sensitivity);
+                let max_pitch: Rad<f32> = MAX_PITCH.into();
+                self.pitch = Rad(self.pitch.0.clamp(-max_pitch.0, max_pitch.0));
+                self.apply(camera, camera.eye());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn cursor_grabbed(&self) -> bool {
+        self.looking
+    }
+}
+
+impl FlyController {
+    fn forward(&self) -> Vector3<f32> {
+        let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
+        Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize()
+    }
+
+    fn apply(&self, camera: &mut Camera, eye: Point3<f32>) {
+        camera.look_at(eye, eye + self.forward());
+    }
+
+    fn process_key_event(&mut self, camera: &mut Camera, keycode: VirtualKeyCode) -> bool {
+        let forward = self.forward();
+        let right = forward.cross(camera.up()).normalize();
+        let up = Vector3::unit_y();
+
+        let direction = match keycode {
+            VirtualKeyCode::W | VirtualKeyCode::Up => forward,
+            VirtualKeyCode::S | VirtualKeyCode::Down => -forward,
+            VirtualKeyCode::A | VirtualKeyCode::Left => -right,
+            VirtualKeyCode::D | VirtualKeyCode::Right => right,
+            VirtualKeyCode::Space => up,
+            VirtualKeyCode::LControl | VirtualKeyCode::RControl => -up,
+            _ => return false,
+        };
+        let eye = camera.eye() + direction * self.move_speed;
+        self.apply(camera, eye);
+        true
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::InnerSpace;
+use winit::dpi::PhysicalPosition;
+use winit::event::{
+    ElementState, KeyboardInput, MouseButton, MouseScrollDelta, TouchPhase, VirtualKeyCode,
+    WindowEvent,
+};
+
+use crate::camera::Camera;
+use crate::controller::CameraController;
+
+/// Stop rotating vertically if view direction is this close to up vector,
+/// or else the camera flips over.
+const MAX_UP_DOT: f32 = 0.99;
+
+/// Rotates eye around target with A/D/W/S keys or by dragging mouse.
+#[derive(Debug)]
+pub struct OrbitController {
+    keyboard_speed: f32,
+    scroll_speed: f32,
+    mouse_pressed: bool,
+    cursor_speed: f32,
+    last_cursor_pos: PhysicalPosition<f64>,
+    first_cursor_moved: bool,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self {
+            keyboard_speed: 0.10,
+            scroll_speed: 0.12,
+            mouse_pressed: false,
+            cursor_speed: 0.02,
+            last_cursor_pos: PhysicalPosition::new(0.0, 0.0),
+            first_cursor_moved: false,
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn activate(&mut self, _camera: &Camera) {
+        self.mouse_pressed = false;
+        self.first_cursor_moved = false;
+    }
+
+    fn process_event(&mut self, camera: &mut Camera, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(keycode),
+                        ..
+                    },
+                ..
+            } => self.process_key_event(camera, *keycode),
+            WindowEvent::MouseWheel {
+                delta,
+                phase: TouchPhase::Moved,
+                ..
+            } => self.process_wheel_event(camera, *delta),
+            WindowEvent::CursorMoved { position, .. } => {
+                self.process_cursor_move_event(camera, *position)
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.mouse_pressed = *state == ElementState::Pressed;
+                self.first_cursor_moved = !self.mouse_pressed;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl OrbitController {
+    fn process_cursor_move_event(
+        &mut self,
+        camera: &mut Camera,
+        position: PhysicalPosition<f64>,
+    ) -> bool {
+        if !self.mouse_pressed {
+            return false;
+        }
+        if !self.first_cursor_moved {
+            self.first_cursor_moved = true;
+            self.last_cursor_pos = position;
+        }
+
+        let x_offset = (position.x - self.last_cursor_pos.x) as f32;
+        let y_offset = (self.last_cursor_pos.y - position.y) as f32;
+        self.last_cursor_pos = position;
+
+        let target = camera.target();
+        let up = camera.up();
+        let mut eye = camera.eye();
+        {
+            let forward = target - eye;
+            let forward_norm = forward.normalize();
+            let forward_mag = forward.magnitude();
+            let right = forward_norm.cross(up);
+            eye =
+                target - (forward + x_offset * right * self.cursor_speed).normalize() * forward_mag;
+        }
+
+        {
+            let forward = target - eye;
+            let forward_mag = forward.magnitude();
+            let new_forward = (forward + y_offset * up * self.cursor_speed).normalize();
+            if new_forward.dot(up).abs() < MAX_UP_DOT {
+                eye = target - new_forward * forward_mag;
+            }
+        }
+        camera.look_at(eye, target);
+        true
+    }
+
+    fn process_wheel_event(&mut self, camera: &mut Camera, delta: MouseScrollDelta) -> bool {
+        let distance = match delta {
+            MouseScrollDelta::LineDelta(_horizontal, vertical) => vertical,
+            // TODO(Shaohua): rotate view
+            MouseScrollDelta::PixelDelta(pos) => pos.y as f32,
+        };
+        let eye = camera.eye() + camera.forward() * self.scroll_speed * distance;
+        camera.look_at(eye, camera.target());
+        true
+    }
+
+    fn process_key_event(&mut self, camera: &mut Camera, keycode: VirtualKeyCode) -> bool {
+        let target = camera.target();
+        let forward = target - camera.eye();
+        let forward_norm = forward.normalize();
+        let forward_mag = forward.magnitude();
+        let right = forward_norm.cross(camera.up());
+
+        let eye = match keycode {
+            VirtualKeyCode::A | VirtualKeyCode::Left => {
+                target - (forward - right * self.keyboard_speed).normalize() * forward_mag
+            }
+            VirtualKeyCode::D | VirtualKeyCode::Right => {
+                target - (forward + right * self.keyboard_speed).normalize() * forward_mag
+            }
+            VirtualKeyCode::S | VirtualKeyCode::Down => {
+                camera.eye() - forward_norm * self.keyboard_speed
+            }
+            VirtualKeyCode::W | VirtualKeyCode::Up => {
+                camera.eye() + forward_norm * self.keyboard_speed
+            }
+            _ => return false,
+        };
+        camera.look_at(eye, target);
+        true
+    }
+}
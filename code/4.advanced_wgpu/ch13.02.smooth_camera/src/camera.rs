@@ -0,0 +1,141 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{perspective, Deg, InnerSpace, Matrix4, One, Point3, SquareMatrix, Vector3, Vector4};
+use std::mem;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+use crate::picking::Ray;
+
+#[rustfmt::skip]
+pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+/// Camera only holds view and projection parameters.
+///
+/// It is moved around by one of the camera controllers.
+#[derive(Debug)]
+pub struct Camera {
+    target: Point3<f32>,
+    eye: Point3<f32>,
+    up: Vector3<f32>,
+    aspect: f32,
+    fovy: f32,
+    zoom_near: f32,
+    zoom_far: f32,
+
+    uniform: CameraUniform,
+}
+
+impl Camera {
+    pub fn new(eye: Point3<f32>, aspect: f32) -> Self {
+        let mut instance = Self {
+            // Default target is the origin point.
+            target: (0.0, 0.0, 0.0).into(),
+            eye,
+            up: Vector3::unit_y(),
+            aspect,
+            fovy: 45.0,
+            zoom_near: 0.1,
+            zoom_far: 100.0,
+
+            uniform: CameraUniform::default(),
+        };
+        instance.update_uniform();
+        instance
+    }
+
+    #[must_use]
+    pub const fn eye(&self) -> Point3<f32> {
+        self.eye
+    }
+
+    #[must_use]
+    pub const fn target(&self) -> Point3<f32> {
+        self.target
+    }
+
+    #[must_use]
+    pub const fn up(&self) -> Vector3<f32> {
+        self.up
+    }
+
+    /// Normalized direction from eye to target.
+    #[must_use]
+    pub fn forward(&self) -> Vector3<f32> {
+        (self.target - self.eye).normalize()
+    }
+
+    /// Move camera to `eye`, looking at `target`.
+    pub fn look_at(&mut self, eye: Point3<f32>, target: Point3<f32>) {
+        self.eye = eye;
+        self.target = target;
+        self.update_uniform();
+    }
+
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+        self.update_uniform();
+    }
+
+    fn update_uniform(&mut self) {
+        let view = Matrix4::look_at_rh(self.eye, self.target, self.up);
+        let proj = perspective(Deg(self.fovy), self.aspect, self.zoom_near, self.zoom_far);
+        self.uniform.view_proj = OPENGL_TO_WGPU_MATRIX * proj * view;
+    }
+
+    /// Convert cursor position in window to a ray in world space.
+    ///
+    /// The inverse of `view_proj` also undoes `OPENGL_TO_WGPU_MATRIX`,
+    /// so the near and far planes are at depth 0.0 and 1.0 in NDC.
+    pub fn screen_to_ray(&self, cursor: PhysicalPosition<f64>, size: PhysicalSize<u32>) -> Ray {
+        let x = (2.0 * cursor.x / f64::from(size.width) - 1.0) as f32;
+        // Y axis of window points down, while in NDC it points up.
+        let y = (1.0 - 2.0 * cursor.y / f64::from(size.height)) as f32;
+
+        let inv_view_proj = self
+            .uniform
+            .view_proj
+            .invert()
+            .unwrap_or_else(Matrix4::identity);
+        let unproject = |depth: f32| {
+            let point = inv_view_proj * Vector4::new(x, y, depth, 1.0);
+            Point3::from_homogeneous(point)
+        };
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        Ray::new(near, far - near)
+    }
+
+    pub fn uniform_ref(&self) -> CameraUniformRef {
+        self.uniform.as_ref()
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraUniform {
+    pub view_proj: Matrix4<f32>,
+}
+
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self {
+            view_proj: Matrix4::one(),
+        }
+    }
+}
+
+pub type CameraUniformBytes = [f32; 16];
+pub type CameraUniformRef<'a> = &'a CameraUniformBytes;
+
+impl AsRef<CameraUniformBytes> for CameraUniform {
+    fn as_ref(&self) -> CameraUniformRef {
+        unsafe { mem::transmute(self) }
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Deg, InnerSpace, Rad, Vector2, Vector3};
+use winit::dpi::PhysicalPosition;
+use winit::event::{
+    ElementState, MouseButton, MouseScrollDelta, TouchPhase, VirtualKeyCode, WindowEvent,
+};
+
+use crate::camera::Camera;
+use crate::controller::{smooth_factor, CameraController, KeyState};
+
+/// Looking straight up or down makes view matrix degenerate.
+const MAX_PITCH: Deg<f32> = Deg(89.0);
+
+const MIN_DISTANCE: f32 = 0.2;
+
+const LEFT_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::A, VirtualKeyCode::Left];
+const RIGHT_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::D, VirtualKeyCode::Right];
+const ZOOM_IN_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::W, VirtualKeyCode::Up];
+const ZOOM_OUT_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::S, VirtualKeyCode::Down];
+
+/// Rotates eye around target with A/D keys or by dragging mouse,
+/// and zoom with W/S keys or mouse wheel.
+///
+/// Eye position is stored in spherical coordinates around target.
+#[derive(Debug)]
+pub struct OrbitController {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+
+    /// Yaw and pitch speed, in radians per second.
+    angular_velocity: Vector2<f32>,
+
+    /// Zoom speed, distance is scaled by `exp(-zoom_velocity)` each second.
+    zoom_velocity: f32,
+
+    /// Max rotation speed by keyboard, in radians per second.
+    key_angular_speed: f32,
+    /// Max zoom speed by keyboard.
+    key_zoom_speed: f32,
+    /// Radians per pixel when dragging.
+    cursor_speed: f32,
+    /// Zoom velocity added by each line of mouse wheel.
+    scroll_speed: f32,
+    /// How fast velocity reaches its target while keys are held down.
+    acceleration: f32,
+    /// How fast velocity decays after keys or mouse button released.
+    damping: f32,
+
+    keys: KeyState,
+    mouse_pressed: bool,
+    last_cursor_pos: Option<PhysicalPosition<f64>>,
+    /// Cursor offset in pixels since previous frame.
+    drag_delta: Vector2<f32>,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 1.0,
+
+            angular_velocity: Vector2::new(0.0, 0.0),
+            zoom_velocity: 0.0,
+
+            key_angular_speed: 1.5,
+            key_zoom_speed: 1.2,
+            cursor_speed: 0.006,
+            scroll_speed: 0.8,
+            acceleration: 10.0,
+            damping: 4.0,
+
+            keys: KeyState::default(),
+            mouse_pressed: false,
+            last_cursor_pos: None,
+            drag_delta: Vector2::new(0.0, 0.0),
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn activate(&mut self, camera: &Camera) {
+        let offset = camera.eye() - camera.target();
+        self.distance = offset.magnitude().max(MIN_DISTANCE);
+        let dir = offset / self.distance;
+        self.yaw = dir.x.atan2(dir.z);
+        self.pitch = dir.y.clamp(-1.0, 1.0).asin();
+
+        self.angular_velocity = Vector2::new(0.0, 0.0);
+        self.zoom_velocity = 0.0;
+        self.mouse_pressed = false;
+        self.last_cursor_pos = None;
+        self.drag_delta = Vector2::new(0.0, 0.0);
+    }
+
+    fn process_event(&mut self, event: &WindowEvent) -> bool {
+        if self.keys.process_event(event) {
+            return true;
+        }
+
+        match event {
+            WindowEvent::MouseWheel {
+                delta,
+                phase: TouchPhase::Moved,
+                ..
+            } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_horizontal, vertical) => *vertical,
+                    // TODO(Shaohua): rotate view
+                    MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 20.0,
+                };
+                self.zoom_velocity += self.scroll_speed * lines;
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                if !self.mouse_pressed {
+                    return false;
+                }
+                if let Some(last_pos) = self.last_cursor_pos {
+                    self.drag_delta.x += (position.x - last_pos.x) as f32;
+                    self.drag_delta.y += (position.y - last_pos.y) as f32;
+                }
+                self.last_cursor_pos = Some(*position);
+                true
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.mouse_pressed = *state == ElementState::Pressed;
+                self.last_cursor_pos = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        if dt <= 0.0 {
+            return;
+        }
+
+        if self.mouse_pressed {
+            // Follow cursor exactly, and remember its speed, so that camera
+            // keeps spinning for a while after mouse button released.
+            let delta = self.drag_delta * self.cursor_speed;
+            self.drag_delta = Vector2::new(0.0, 0.0);
+            self.yaw -= delta.x;
+            self.pitch += delta.y;
+            self.angular_velocity += (Vector2::new(-delta.x, delta.y) / dt - self.angular_velocity)
+                * smooth_factor(self.acceleration, dt);
+        } else {
+            // Eye moves to the right while view turns left.
+            let yaw_axis = self.keys.axis(RIGHT_KEYS, LEFT_KEYS);
+            let target = Vector2::new(yaw_axis * self.key_angular_speed, 0.0);
+            let rate = if yaw_axis == 0.0 {
+                self.damping
+            } else {
+                self.acceleration
+            };
+            self.angular_velocity += (target - self.angular_velocity) * smooth_factor(rate, dt);
+            self.yaw += self.angular_velocity.x * dt;
+            self.pitch += self.angular_velocity.y * dt;
+        }
+
+        let zoom_axis = self.keys.axis(ZOOM_OUT_KEYS, ZOOM_IN_KEYS);
+        let rate = if zoom_axis == 0.0 {
+            self.damping
+        } else {
+            self.acceleration
+        };
+        let target = zoom_axis * self.key_zoom_speed;
+        self.zoom_velocity += (target - self.zoom_velocity) * smooth_factor(rate, dt);
+        self.distance = (self.distance * (-self.zoom_velocity * dt).exp()).max(MIN_DISTANCE);
+
+        let max_pitch: Rad<f32> = MAX_PITCH.into();
+        self.pitch = self.pitch.clamp(-max_pitch.0, max_pitch.0);
+
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let dir = Vector3::new(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw);
+        let target = camera.target();
+        camera.look_at(target + dir * self.distance, target);
+    }
+}
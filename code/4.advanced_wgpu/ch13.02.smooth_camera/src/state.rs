@@ -0,0 +1,758 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Matrix4, Rad, Vector4};
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use instant::Instant;
+use wgpu::util::DeviceExt;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{DeviceEvent, ElementState, MouseButton, WindowEvent};
+use winit::window::{CursorGrabMode, Window};
+
+use crate::camera::Camera;
+use crate::controller::{CameraController, CameraMode};
+use crate::cubes::CUBE_POSITIONS;
+use crate::fly::FlyController;
+use crate::frames::{CameraWindow, PickingWindow};
+use crate::geometry::{create_cube_detail, GeometryData};
+use crate::objects::{Mesh, SceneObject};
+use crate::orbit::OrbitController;
+use crate::picking;
+use crate::texture::Texture;
+use crate::uniforms::ModelUniform;
+use crate::vertex::Vertex;
+use crate::Error;
+
+/// Stencil value written by the selected object.
+const SELECTED_STENCIL_REF: u32 = 1;
+
+/// How much the outline is larger than the selected object.
+const OUTLINE_SCALE: f32 = 1.08;
+
+/// If cursor moved less than this distance in pixels between mouse button
+/// pressed and released, it is a click, otherwise the camera is dragged.
+const CLICK_DISTANCE: f64 = 4.0;
+
+/// Max time step in seconds used to move camera.
+const MAX_FRAME_TIME: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PipelineKind {
+    /// Draw objects without touching stencil buffer.
+    Normal,
+
+    /// Draw the selected object, and write reference value into stencil buffer.
+    StencilWrite,
+
+    /// Draw scaled silhouette of the selected object where stencil != reference.
+    Outline,
+}
+
+pub struct State {
+    surface: wgpu::Surface,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    size: PhysicalSize<u32>,
+    window: Window,
+
+    normal_pipeline: wgpu::RenderPipeline,
+    stencil_write_pipeline: wgpu::RenderPipeline,
+    outline_pipeline: wgpu::RenderPipeline,
+
+    cube_geometry: GeometryData,
+    cube_mesh: Mesh,
+    cubes: Vec<SceneObject>,
+    outline: SceneObject,
+
+    camera: Camera,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    camera_mode: CameraMode,
+    orbit_controller: OrbitController,
+    fly_controller: FlyController,
+    cursor_grabbed: bool,
+
+    depth_texture: Texture,
+
+    cursor_pos: PhysicalPosition<f64>,
+    pressed_pos: Option<PhysicalPosition<f64>>,
+
+    start_time: Instant,
+    last_frame_time: Instant,
+    egui_platform: Platform,
+    egui_render_pass: RenderPass,
+    picking_window: PickingWindow,
+    camera_window: CameraWindow,
+}
+
+impl State {
+    async fn create_surface(
+        window: &Window,
+    ) -> Result<
+        (
+            wgpu::Surface,
+            wgpu::Device,
+            wgpu::Queue,
+            wgpu::SurfaceConfiguration,
+            PhysicalSize<u32>,
+        ),
+        Error,
+    > {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let surface = unsafe { instance.create_surface(&window) }?;
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| {
+                Error::Others("Failed to get an approprivate wgpu adapter".to_owned())
+            })?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    features: if cfg!(target_arch = "wasm32") {
+                        wgpu::Features::empty()
+                    } else {
+                        wgpu::Features::POLYGON_MODE_LINE
+                    },
+                    limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                },
+                None,
+            )
+            .await?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(wgpu::TextureFormat::is_srgb)
+            .unwrap_or(surface_caps.formats[0]);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&device, &config);
+
+        Ok((surface, device, queue, config, size))
+    }
+
+    fn create_render_pipeline(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        kind: PipelineKind,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../res/shaders/camera.wgsl").into()),
+        });
+
+        let (label, fs_entry_point, stencil_face, stencil_write_mask) = match kind {
+            PipelineKind::Normal => (
+                "Render Pipeline",
+                "fs_main",
+                wgpu::StencilFaceState::IGNORE,
+                0x00,
+            ),
+            PipelineKind::StencilWrite => (
+                "Stencil Write Render Pipeline",
+                "fs_main",
+                wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::Always,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op: wgpu::StencilOperation::Replace,
+                },
+                0xff,
+            ),
+            PipelineKind::Outline => (
+                "Outline Render Pipeline",
+                "fs_outline",
+                wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::NotEqual,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op: wgpu::StencilOperation::Keep,
+                },
+                0x00,
+            ),
+        };
+
+        // Outline is always visible, even if the selected object is hidden
+        // behind other objects.
+        let (depth_write_enabled, depth_compare) = if kind == PipelineKind::Outline {
+            (false, wgpu::CompareFunction::Always)
+        } else {
+            (true, wgpu::CompareFunction::Less)
+        };
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: fs_entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_STENCIL_FORMAT,
+                depth_write_enabled,
+                depth_compare,
+                stencil: wgpu::StencilState {
+                    front: stencil_face,
+                    back: stencil_face,
+                    read_mask: 0xff,
+                    write_mask: stencil_write_mask,
+                },
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        render_pipeline
+    }
+
+    fn create_camera(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+    ) -> Result<(Camera, wgpu::Buffer, wgpu::BindGroupLayout, wgpu::BindGroup), Error> {
+        let eye_pos = (0.0, 0.0, 3.0).into();
+        let aspect = size.width as f32 / size.height as f32;
+        let camera = Camera::new(eye_pos, aspect);
+
+        let uniform_ref = camera.uniform_ref();
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Uniform Buffer"),
+            contents: bytemuck::cast_slice(uniform_ref),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("camera_bind_group_layout"),
+            });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("camera_bind_group"),
+        });
+
+        Ok((
+            camera,
+            camera_buffer,
+            camera_bind_group_layout,
+            camera_bind_group,
+        ))
+    }
+
+    fn create_model_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("model_bind_group_layout"),
+        })
+    }
+
+    fn create_objects(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Result<(Vec<SceneObject>, SceneObject), Error> {
+        let container_bytes = include_bytes!("../res/textures/container.jpg");
+        let container_texture =
+            Texture::from_bytes(device, queue, container_bytes, Some("container"))?;
+
+        let face_bytes = include_bytes!("../res/textures/awesome_face.png");
+        let face_texture = Texture::from_bytes(device, queue, face_bytes, Some("face"))?;
+
+        let textures = [&container_texture, &face_texture];
+        let cubes = CUBE_POSITIONS
+            .iter()
+            .map(|_pos| {
+                SceneObject::new(
+                    device,
+                    bind_group_layout,
+                    &textures,
+                    ModelUniform::default(),
+                )
+            })
+            .collect();
+
+        let outline_uniform = ModelUniform {
+            color: Vector4::new(1.0, 0.6, 0.0, 1.0),
+            ..ModelUniform::default()
+        };
+        let outline = SceneObject::new(device, bind_group_layout, &textures, outline_uniform);
+
+        Ok((cubes, outline))
+    }
+
+    fn create_egui_platform(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> (Platform, RenderPass, PickingWindow, CameraWindow) {
+        let platform = Platform::new(PlatformDescriptor {
+            physical_width: size.width,
+            physical_height: size.height,
+            scale_factor,
+            ..Default::default()
+        });
+
+        let render_pass = RenderPass::new(device, surface_format, 1);
+        let picking_window = PickingWindow::default();
+        let camera_window = CameraWindow::default();
+
+        (platform, render_pass, picking_window, camera_window)
+    }
+
+    pub async fn new(window: Window) -> Result<Self, Error> {
+        let (surface, device, queue, config, size) = Self::create_surface(&window).await?;
+
+        // Geometry data is kept in CPU side for ray casting.
+        let cube_geometry = create_cube_detail(1.0, 1.0, 1.0);
+        let cube_mesh = Mesh::new(&device, &cube_geometry, "Cube");
+
+        let (camera, camera_buffer, camera_bind_group_layout, camera_bind_group) =
+            Self::create_camera(&device, size)?;
+
+        let model_bind_group_layout = Self::create_model_bind_group_layout(&device);
+        let (cubes, outline) = Self::create_objects(&device, &queue, &model_bind_group_layout)?;
+
+        let bind_group_layouts = [&camera_bind_group_layout, &model_bind_group_layout];
+        let normal_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::Normal,
+        );
+        let stencil_write_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::StencilWrite,
+        );
+        let outline_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::Outline,
+        );
+
+        let depth_texture = Texture::create_depth_texture_with_format(
+            &device,
+            size,
+            Texture::DEPTH_STENCIL_FORMAT,
+            Some("Depth Stencil Texture"),
+        );
+
+        let mut orbit_controller = OrbitController::default();
+        orbit_controller.activate(&camera);
+
+        let (egui_platform, egui_render_pass, picking_window, camera_window) =
+            Self::create_egui_platform(&device, config.format, size, window.scale_factor());
+
+        Ok(Self {
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            size,
+
+            normal_pipeline,
+            stencil_write_pipeline,
+            outline_pipeline,
+
+            cube_geometry,
+            cube_mesh,
+            cubes,
+            outline,
+
+            camera,
+            camera_buffer,
+            camera_bind_group,
+            camera_mode: CameraMode::default(),
+            orbit_controller,
+            fly_controller: FlyController::default(),
+            cursor_grabbed: false,
+
+            depth_texture,
+
+            cursor_pos: PhysicalPosition::new(0.0, 0.0),
+            pressed_pos: None,
+
+            start_time: Instant::now(),
+            last_frame_time: Instant::now(),
+            egui_platform,
+            egui_render_pass,
+            picking_window,
+            camera_window,
+        })
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn platform_mut(&mut self) -> &mut Platform {
+        &mut self.egui_platform
+    }
+
+    fn controller_mut(&mut self) -> (&mut dyn CameraController, &mut Camera) {
+        let controller: &mut dyn CameraController = match self.camera_mode {
+            CameraMode::Orbit => &mut self.orbit_controller,
+            CameraMode::Fly => &mut self.fly_controller,
+        };
+        (controller, &mut self.camera)
+    }
+
+    /// Grab and hide cursor if active camera controller requires it.
+    fn update_cursor_grab(&mut self) {
+        let (controller, _camera) = self.controller_mut();
+        let grabbed = controller.cursor_grabbed();
+        if grabbed == self.cursor_grabbed {
+            return;
+        }
+        self.cursor_grabbed = grabbed;
+
+        let result = if grabbed {
+            // Locked mode is not supported on some platforms.
+            self.window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_err| self.window.set_cursor_grab(CursorGrabMode::Confined))
+        } else {
+            self.window.set_cursor_grab(CursorGrabMode::None)
+        };
+        if let Err(err) = result {
+            log::warn!("Failed to grab cursor: {err:?}");
+        }
+        self.window.set_cursor_visible(!grabbed);
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&self.device, &self.config);
+            self.camera
+                .set_aspect(new_size.width as f32 / new_size.height as f32);
+
+            self.depth_texture = Texture::create_depth_texture_with_format(
+                &self.device,
+                self.size,
+                Texture::DEPTH_STENCIL_FORMAT,
+                Some("Depth Stencil Texture"),
+            );
+        }
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_pos = *position;
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => match state {
+                ElementState::Pressed => self.pressed_pos = Some(self.cursor_pos),
+                ElementState::Released => {
+                    if let Some(pressed_pos) = self.pressed_pos.take() {
+                        let dx = self.cursor_pos.x - pressed_pos.x;
+                        let dy = self.cursor_pos.y - pressed_pos.y;
+                        if dx.hypot(dy) < CLICK_DISTANCE {
+                            self.pick();
+                        }
+                    }
+                }
+            },
+            _ => (),
+        }
+
+        let (controller, _camera) = self.controller_mut();
+        let consumed = controller.process_event(event);
+        self.update_cursor_grab();
+        consumed
+    }
+
+    pub fn device_input(&mut self, event: &DeviceEvent) -> bool {
+        let (controller, _camera) = self.controller_mut();
+        controller.process_device_event(event)
+    }
+
+    /// Select the nearest cube under cursor.
+    fn pick(&mut self) {
+        // Clicks on egui windows are not passed to the scene.
+        if self.egui_platform.context().is_pointer_over_area() {
+            return;
+        }
+
+        let ray = self.camera.screen_to_ray(self.cursor_pos, self.size);
+        let objects = self
+            .cubes
+            .iter()
+            .map(|cube| (&self.cube_geometry, &cube.uniform.model));
+        self.picking_window.hit = picking::pick(&ray, objects);
+    }
+
+    pub fn update(&mut self) {
+        self.egui_platform
+            .update_time(self.start_time.elapsed().as_secs_f64());
+
+        if self.camera_window.mode != self.camera_mode {
+            self.camera_mode = self.camera_window.mode;
+            let (controller, camera) = self.controller_mut();
+            controller.activate(camera);
+            self.update_cursor_grab();
+        }
+
+        // Limit time step, in case that rendering is paused for a while.
+        let now = Instant::now();
+        let frame_dt = (now - self.last_frame_time)
+            .as_secs_f32()
+            .min(MAX_FRAME_TIME);
+        self.last_frame_time = now;
+        let (controller, camera) = self.controller_mut();
+        controller.update(camera, frame_dt);
+
+        let dt = self.start_time.elapsed().as_secs_f32();
+
+        for (index, (cube, pos)) in self.cubes.iter_mut().zip(CUBE_POSITIONS).enumerate() {
+            let translate_model = Matrix4::from_translation(*pos);
+            let angle: f32 = 0.5 * (index + 1) as f32;
+            let radian = Rad(angle) * dt;
+            let rotate_model = Matrix4::from_angle_x(Rad(0.5) * (1.15 - dt.sin()))
+                * Matrix4::from_angle_y(radian)
+                * Matrix4::from_angle_z(Rad(0.0));
+            cube.uniform.model = translate_model * rotate_model;
+            cube.write_uniform(&self.queue);
+        }
+
+        if let Some(selected) = self.picking_window.selected() {
+            self.outline.uniform.model =
+                self.cubes[selected].uniform.model * Matrix4::from_scale(OUTLINE_SCALE);
+            self.outline.write_uniform(&self.queue);
+        }
+
+        self.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(self.camera.uniform_ref()),
+        );
+    }
+
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.2,
+                            g: 0.3,
+                            b: 0.3,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_texture.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0),
+                        store: true,
+                    }),
+                }),
+            });
+
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            render_pass.set_stencil_reference(SELECTED_STENCIL_REF);
+
+            // 1. Draw other cubes as usual.
+            let selected = self.picking_window.selected();
+            render_pass.set_pipeline(&self.normal_pipeline);
+            for (index, cube) in self.cubes.iter().enumerate() {
+                if Some(index) != selected {
+                    render_pass.set_bind_group(1, &cube.bind_group, &[]);
+                    self.cube_mesh.draw(&mut render_pass);
+                }
+            }
+
+            if let Some(selected) = selected {
+                // 2. Draw the selected cube, marking its pixels in stencil buffer.
+                render_pass.set_pipeline(&self.stencil_write_pipeline);
+                render_pass.set_bind_group(1, &self.cubes[selected].bind_group, &[]);
+                self.cube_mesh.draw(&mut render_pass);
+
+                // 3. Draw a scaled up version of selected cube, only outside of the marked pixels.
+                render_pass.set_pipeline(&self.outline_pipeline);
+                render_pass.set_bind_group(1, &self.outline.bind_group, &[]);
+                self.cube_mesh.draw(&mut render_pass);
+            }
+        }
+
+        // Draw the egui UI frame.
+        {
+            self.egui_platform.begin_frame();
+            self.picking_window.ui(&self.egui_platform.context());
+            self.camera_window.ui(&self.egui_platform.context());
+            let full_output = self.egui_platform.end_frame(Some(&self.window));
+            let paint_jobs = self.egui_platform.context().tessellate(full_output.shapes);
+            // Upload all resources for the GPU.
+            let screen_descriptor = ScreenDescriptor {
+                physical_width: self.config.width,
+                physical_height: self.config.height,
+                scale_factor: self.window.scale_factor() as f32,
+            };
+            let tdelta: egui::TexturesDelta = full_output.textures_delta;
+            self.egui_render_pass
+                .add_textures(&self.device, &self.queue, &tdelta)
+                .expect("add texture ok");
+            self.egui_render_pass.update_buffers(
+                &self.device,
+                &self.queue,
+                &paint_jobs,
+                &screen_descriptor,
+            );
+
+            // Record all render passes.
+            self.egui_render_pass
+                .execute(&mut encoder, &view, &paint_jobs, &screen_descriptor, None)
+                .unwrap();
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Matrix4, One, Vector4};
+use std::mem;
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelUniform {
+    pub model: Matrix4<f32>,
+    pub color: Vector4<f32>,
+}
+
+impl Default for ModelUniform {
+    fn default() -> Self {
+        Self {
+            model: Matrix4::one(),
+            color: Vector4::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+}
+
+pub type ModelUniformBytes = [f32; 20];
+pub type ModelUniformRef<'a> = &'a ModelUniformBytes;
+
+impl AsRef<ModelUniformBytes> for ModelUniform {
+    fn as_ref(&self) -> ModelUniformRef {
+        unsafe { mem::transmute(self) }
+    }
+}
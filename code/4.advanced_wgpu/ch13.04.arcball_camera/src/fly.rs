@@ -0,0 +1,164 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Deg, InnerSpace, Rad, Vector3, Zero};
+use winit::event::{
+    DeviceEvent, ElementState, MouseButton, MouseScrollDelta, TouchPhase, VirtualKeyCode,
+    WindowEvent,
+};
+
+use crate::camera::Camera;
+use crate::controller::{smooth_factor, CameraController, KeyState};
+
+/// Looking straight up or down makes view matrix degenerate.
+const MAX_PITCH: Deg<f32> = Deg(89.0);
+
+const FORWARD_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::W, VirtualKeyCode::Up];
+const BACKWARD_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::S, VirtualKeyCode::Down];
+const LEFT_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::A, VirtualKeyCode::Left];
+const RIGHT_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::D, VirtualKeyCode::Right];
+const UP_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::Space];
+const DOWN_KEYS: &[VirtualKeyCode] = &[VirtualKeyCode::LControl, VirtualKeyCode::RControl];
+
+/// First person camera.
+///
+/// Hold right mouse button to look around, move with A/D/W/S keys,
+/// Space to fly up and Ctrl to fly down.
+#[derive(Debug)]
+pub struct FlyController {
+    yaw: Rad<f32>,
+    pitch: Rad<f32>,
+    velocity: Vector3<f32>,
+    /// Distance from eye to target, which is kept so that orthographic view
+    /// height does not change.
+    focus_distance: f32,
+
+    /// Max speed, in units per second.
+    move_speed: f32,
+    /// Forward speed added by each line of mouse wheel.
+    scroll_speed: f32,
+    mouse_sensitivity: f32,
+    /// How fast velocity reaches its target while keys are held down.
+    acceleration: f32,
+    /// How fast velocity decays after keys released.
+    damping: f32,
+
+    keys: KeyState,
+    looking: bool,
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        Self {
+            yaw: Rad(0.0),
+            pitch: Rad(0.0),
+            velocity: Vector3::zero(),
+            focus_distance: 1.0,
+
+            move_speed: 3.0,
+            scroll_speed: 2.0,
+            mouse_sensitivity: 0.003,
+            acceleration: 8.0,
+            damping: 6.0,
+
+            keys: KeyState::default(),
+            looking: false,
+        }
+    }
+}
+
+impl CameraController for FlyController {
+    fn activate(&mut self, camera: &Camera) {
+        let forward = camera.forward();
+        // Top and bottom views look straight along Y axis, where heading
+        // comes from up vector of camera, which points forward when looking
+        // down and backward when looking up.
+        let heading = if forward.x.hypot(forward.z) < 1e-4 {
+            camera.up() * -forward.y.signum()
+        } else {
+            forward
+        };
+        self.yaw = Rad(heading.z.atan2(heading.x));
+        let max_pitch: Rad<f32> = MAX_PITCH.into();
+        let pitch = forward.y.clamp(-1.0, 1.0).asin();
+        self.pitch = Rad(pitch.clamp(-max_pitch.0, max_pitch.0));
+        self.velocity = Vector3::zero();
+        self.focus_distance = (camera.target() - camera.eye()).magnitude();
+        self.looking = false;
+    }
+
+    fn process_event(&mut self, event: &WindowEvent) -> bool {
+        if self.keys.process_event(event) {
+            return true;
+        }
+
+        match event {
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::LineDelta(_horizontal, vertical),
+                phase: TouchPhase::Moved,
+                ..
+            } => {
+                self.velocity += self.forward() * self.scroll_speed * *vertical;
+                true
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Right,
+                ..
+            } => {
+                self.looking = *state == ElementState::Pressed;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn process_device_event(&mut self, event: &DeviceEvent) -> bool {
+        match event {
+            DeviceEvent::MouseMotion { delta: (dx, dy) } if self.looking => {
+                self.yaw += Rad(*dx as f32 * self.mouse_sensitivity);
+                self.pitch -= Rad(*dy as f32 * self.mouse_sensitivity);
+                let max_pitch: Rad<f32> = MAX_PITCH.into();
+                self.pitch = Rad(self.pitch.0.clamp(-max_pitch.0, max_pitch.0));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        let forward = self.forward();
+        let up = Vector3::unit_y();
+        let right = forward.cross(up).normalize();
+
+        let mut direction = forward * self.keys.axis(BACKWARD_KEYS, FORWARD_KEYS)
+            + right * self.keys.axis(LEFT_KEYS, RIGHT_KEYS)
+            + up * self.keys.axis(DOWN_KEYS, UP_KEYS);
+        let rate = if direction.is_zero() {
+            self.damping
+        } else {
+            // Moving diagonally is not faster.
+            direction = direction.normalize();
+            self.acceleration
+        };
+
+        let target = direction * self.move_speed;
+        self.velocity += (target - self.velocity) * smooth_factor(rate, dt);
+
+        let eye = camera.eye() + self.velocity * dt;
+        camera.set_view(eye, eye + forward * self.focus_distance, up);
+    }
+
+    fn cursor_grabbed(&self) -> bool {
+        self.looking
+    }
+}
+
+impl FlyController {
+    fn forward(&self) -> Vector3<f32> {
+        let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
+        Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize()
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+#![allow(dead_code)]
+
+use cgmath::{Point3, Vector3};
+use instant::Instant;
+
+use crate::camera::{Projection, ViewPreset};
+use crate::controller::CameraMode;
+use crate::picking::Hit;
+
+#[derive(Debug, Clone)]
+pub struct UserWindow {
+    name: String,
+    age: u32,
+}
+
+impl Default for UserWindow {
+    fn default() -> Self {
+        Self {
+            name: "Author".to_owned(),
+            age: 42,
+        }
+    }
+}
+
+impl UserWindow {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub const fn age(&self) -> u32 {
+        self.age
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("User")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let name_label = ui.label("Your name: ");
+                    ui.text_edit_singleline(&mut self.name)
+                        .labelled_by(name_label.id);
+                });
+                ui.add(egui::Slider::new(&mut self.age, 0..=120).text("age"));
+                if ui.button("Click each year").clicked() {
+                    self.age += 1;
+                }
+                ui.label(format!("Hello '{}', age {}", self.name, self.age));
+            });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ColorWindow {
+    color: Vector3<f32>,
+}
+
+impl Default for ColorWindow {
+    fn default() -> Self {
+        Self {
+            color: Vector3::new(0.3, 0.4, 0.5),
+        }
+    }
+}
+
+impl ColorWindow {
+    pub fn color(&self) -> &Vector3<f32> {
+        &self.color
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Color")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.heading("Select vertex color:");
+                ui.color_edit_button_rgb(self.color.as_mut());
+            });
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FpsWindow {
+    fps: u32,
+    frames: u32,
+    timer: Instant,
+}
+
+impl Default for FpsWindow {
+    fn default() -> Self {
+        Self {
+            fps: 0,
+            frames: 0,
+            timer: Instant::now(),
+        }
+    }
+}
+
+impl FpsWindow {
+    pub fn update(&mut self) {
+        let dt = self.timer.elapsed().as_secs_f64();
+        let fps = (f64::from(self.frames) / dt).round() as u32;
+        //log::info!("fps: {fps}");
+        if dt > 1.0 {
+            self.frames = 0;
+            self.timer = Instant::now();
+            self.fps = fps;
+        }
+        self.frames += 1;
+    }
+
+    #[must_use]
+    pub const fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("FPS")
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                ui.heading(format!("FPS: {}", self.fps));
+            });
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PickingWindow {
+    pub hit: Option<Hit>,
+}
+
+impl PickingWindow {
+    #[must_use]
+    pub fn selected(&self) -> Option<usize> {
+        self.hit.map(|hit| hit.object)
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Picking")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                if let Some(hit) = &self.hit {
+                    ui.heading(format!("Selected cube: {}", hit.object));
+                    ui.label(format!("Triangle: {}", hit.triangle));
+                    ui.label(format!("Distance: {:.3}", hit.distance));
+                    ui.label(format!(
+                        "Barycentric: ({:.3}, {:.3}, {:.3})",
+                        hit.barycentric.x, hit.barycentric.y, hit.barycentric.z
+                    ));
+                    if ui.button("Clear selection").clicked() {
+                        self.hit = None;
+                    }
+                } else {
+                    ui.heading("Click on a cube to select it");
+                }
+            });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CameraWindow {
+    pub mode: CameraMode,
+    pub projection: Projection,
+
+    /// Preset view clicked by user, taken by `State::update()`.
+    pub preset: Option<ViewPreset>,
+
+    /// Pivot of arcball camera.
+    pub pivot: Point3<f32>,
+    /// Pivot is changed by user, taken by `State::update()`.
+    pub pivot_edited: bool,
+}
+
+impl Default for CameraWindow {
+    fn default() -> Self {
+        Self {
+            mode: CameraMode::default(),
+            projection: Projection::default(),
+            preset: None,
+            pivot: Point3::new(0.0, 0.0, 0.0),
+            pivot_edited: false,
+        }
+    }
+}
+
+impl CameraWindow {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Camera")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.radio_value(&mut self.mode, CameraMode::Orbit, "Orbit");
+                ui.radio_value(&mut self.mode, CameraMode::Fly, "Fly");
+                ui.radio_value(&mut self.mode, CameraMode::Arcball, "Arcball");
+                match self.mode {
+                    CameraMode::Orbit => {
+                        ui.label("Drag with left mouse button or press A/D/W/S to orbit");
+                    }
+                    CameraMode::Fly => {
+                        ui.label("Hold right mouse button to look around");
+                        ui.label("A/D/W/S to move, Space/Ctrl to fly up/down");
+                    }
+                    CameraMode::Arcball => {
+                        ui.label("Drag with left mouse button to rotate");
+                        ui.label("Drag with middle mouse button to pan");
+                        ui.label("Double click on an object to rotate around it");
+                        ui.horizontal(|ui| {
+                            ui.label("Pivot:");
+                            for value in [&mut self.pivot.x, &mut self.pivot.y, &mut self.pivot.z] {
+                                if ui.add(egui::DragValue::new(value).speed(0.05)).changed() {
+                                    self.pivot_edited = true;
+                                }
+                            }
+                        });
+                        if ui.button("Reset pivot").clicked() {
+                            self.pivot = Point3::new(0.0, 0.0, 0.0);
+                            self.pivot_edited = true;
+                        }
+                    }
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.projection, Projection::Perspective, "Perspective");
+                    ui.radio_value(
+                        &mut self.projection,
+                        Projection::Orthographic,
+                        "Orthographic",
+                    );
+                });
+                ui.label("Press 5 to switch projection");
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    for (preset, name) in [
+                        (ViewPreset::Front, "Front"),
+                        (ViewPreset::Right, "Right"),
+                        (ViewPreset::Top, "Top"),
+                    ] {
+                        if ui.button(name).clicked() {
+                            self.preset = Some(preset);
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    for (preset, name) in [
+                        (ViewPreset::Back, "Back"),
+                        (ViewPreset::Left, "Left"),
+                        (ViewPreset::Bottom, "Bottom"),
+                    ] {
+                        if ui.button(name).clicked() {
+                            self.preset = Some(preset);
+                        }
+                    }
+                });
+                ui.label("Press 1/3/7 for front/right/top view, with Ctrl for the opposite");
+            });
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{
+    ortho, perspective, Deg, InnerSpace, Matrix4, One, Point3, Rad, SquareMatrix, Vector3, Vector4,
+};
+use std::mem;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+use crate::bounds::BoundingSphere;
+use crate::controller::smooth_factor;
+use crate::picking::Ray;
+
+#[rustfmt::skip]
+pub const OPENGL_TO_WGPU_MATRIX: Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+/// How fast projection matrix changes from perspective to orthographic.
+const PROJECTION_BLEND_RATE: f32 = 8.0;
+
+/// Leave some space around framed object.
+const FRAME_MARGIN: f32 = 1.1;
+
+/// Tiny objects are framed as if they have this radius, or else eye is
+/// moved into them.
+const MIN_FRAME_RADIUS: f32 = 0.05;
+
+/// Clip planes are moved a bit out of scene bounds.
+const CLIP_MARGIN: f32 = 1.05;
+
+/// Near clip plane is not closer than this ratio of far one, to keep
+/// enough depth precision when eye is inside of scene bounds.
+const MIN_NEAR_RATIO: f32 = 1.0e-3;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    #[default]
+    Perspective,
+
+    /// Parallel lines stay parallel, and sizes do not depend on distance,
+    /// so that geometry can be measured on screen.
+    Orthographic,
+}
+
+/// Views aligned to world axes, like those in CAD programs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewPreset {
+    Front,
+    Back,
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl ViewPreset {
+    /// Direction from target to eye.
+    #[must_use]
+    pub fn direction(self) -> Vector3<f32> {
+        match self {
+            Self::Front => Vector3::unit_z(),
+            Self::Back => -Vector3::unit_z(),
+            Self::Left => -Vector3::unit_x(),
+            Self::Right => Vector3::unit_x(),
+            Self::Top => Vector3::unit_y(),
+            Self::Bottom => -Vector3::unit_y(),
+        }
+    }
+
+    /// Up vector of view, which shall not be parallel to `direction()`.
+    #[must_use]
+    pub fn up(self) -> Vector3<f32> {
+        match self {
+            Self::Top => -Vector3::unit_z(),
+            Self::Bottom => Vector3::unit_z(),
+            _ => Vector3::unit_y(),
+        }
+    }
+}
+
+/// Camera only holds view and projection parameters.
+///
+/// It is moved around by one of the camera controllers.
+#[derive(Debug)]
+pub struct Camera {
+    target: Point3<f32>,
+    eye: Point3<f32>,
+    up: Vector3<f32>,
+    aspect: f32,
+    fovy: f32,
+    zoom_near: f32,
+    zoom_far: f32,
+
+    projection: Projection,
+    /// 0.0 is perspective and 1.0 is orthographic projection,
+    /// values between them are used while switching.
+    ortho_factor: f32,
+
+    uniform: CameraUniform,
+}
+
+impl Camera {
+    pub fn new(eye: Point3<f32>, aspect: f32) -> Self {
+        let mut instance = Self {
+            // Default target is the origin point.
+            target: (0.0, 0.0, 0.0).into(),
+            eye,
+            up: Vector3::unit_y(),
+            aspect,
+            fovy: 45.0,
+            zoom_near: 0.1,
+            zoom_far: 100.0,
+
+            projection: Projection::default(),
+            ortho_factor: 0.0,
+
+            uniform: CameraUniform::default(),
+        };
+        instance.update_uniform();
+        instance
+    }
+
+    #[must_use]
+    pub const fn eye(&self) -> Point3<f32> {
+        self.eye
+    }
+
+    #[must_use]
+    pub const fn target(&self) -> Point3<f32> {
+        self.target
+    }
+
+    #[must_use]
+    pub const fn up(&self) -> Vector3<f32> {
+        self.up
+    }
+
+    /// Vertical field of view, in degrees.
+    #[must_use]
+    pub const fn fovy(&self) -> f32 {
+        self.fovy
+    }
+
+    pub fn set_fovy(&mut self, fovy: f32) {
+        self.fovy = fovy;
+        self.update_uniform();
+    }
+
+    /// Normalized direction from eye to target.
+    #[must_use]
+    pub fn forward(&self) -> Vector3<f32> {
+        (self.target - self.eye).normalize()
+    }
+
+    /// Move camera to `eye`, looking at `target`.
+    pub fn look_at(&mut self, eye: Point3<f32>, target: Point3<f32>) {
+        self.eye = eye;
+        self.target = target;
+        self.update_uniform();
+    }
+
+    /// Move camera to `eye`, looking at `target`, with a new `up` vector.
+    pub fn set_view(&mut self, eye: Point3<f32>, target: Point3<f32>, up: Vector3<f32>) {
+        self.up = up.normalize();
+        self.look_at(eye, target);
+    }
+
+    /// Snap to one of the preset views, keeping distance to target.
+    pub fn snap_to(&mut self, preset: ViewPreset) {
+        let distance = (self.eye - self.target).magnitude();
+        let eye = self.target + preset.direction() * distance;
+        self.set_view(eye, self.target, preset.up());
+    }
+
+    /// Move eye along current view direction, so that `bounds` fits
+    /// in both width and height of view.
+    pub fn frame(&mut self, bounds: &BoundingSphere) {
+        let half_fovy: Rad<f32> = (Deg(self.fovy) / 2.0).into();
+        let half_fovx = (half_fovy.0.tan() * self.aspect).atan();
+        let half_fov = half_fovy.0.min(half_fovx);
+
+        let radius = bounds.radius.max(MIN_FRAME_RADIUS) * FRAME_MARGIN;
+        let distance = radius / half_fov.sin();
+        let eye = bounds.center - self.forward() * distance;
+        self.look_at(eye, bounds.center);
+    }
+
+    /// Move clip planes close to `scene` bounds, so that nothing is clipped
+    /// and depth buffer precision is not wasted.
+    pub fn fit_clip_planes(&mut self, scene: &BoundingSphere) {
+        let distance = (scene.center - self.eye).magnitude();
+        let far = (distance + scene.radius) * CLIP_MARGIN;
+        let near = ((distance - scene.radius) / CLIP_MARGIN).max(far * MIN_NEAR_RATIO);
+        if near != self.zoom_near || far != self.zoom_far {
+            self.zoom_near = near;
+            self.zoom_far = far;
+            self.update_uniform();
+        }
+    }
+
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+        self.update_uniform();
+    }
+
+    #[must_use]
+    pub const fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// Projection is not switched immediately, see `update()`.
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    /// Blend projection matrix towards the selected one.
+    pub fn update(&mut self, dt: f32) {
+        let target = match self.projection {
+            Projection::Perspective => 0.0,
+            Projection::Orthographic => 1.0,
+        };
+        if self.ortho_factor == target {
+            return;
+        }
+        self.ortho_factor +=
+            (target - self.ortho_factor) * smooth_factor(PROJECTION_BLEND_RATE, dt);
+        if (self.ortho_factor - target).abs() < 1.0e-3 {
+            self.ortho_factor = target;
+        }
+        self.update_uniform();
+    }
+
+    /// Orthographic projection has the same view height as perspective one
+    /// at target point, so that zooming in and out changes view height.
+    fn orthographic_matrix(&self) -> Matrix4<f32> {
+        let distance = (self.target - self.eye).magnitude();
+        let half_fovy: Rad<f32> = (Deg(self.fovy) / 2.0).into();
+        let half_height = distance * half_fovy.0.tan();
+        let half_width = half_height * self.aspect;
+        ortho(
+            -half_width,
+            half_width,
+            -half_height,
+            half_height,
+            self.zoom_near,
+            self.zoom_far,
+        )
+    }
+
+    fn update_uniform(&mut self) {
+        let view = Matrix4::look_at_rh(self.eye, self.target, self.up);
+        let proj = if self.ortho_factor <= 0.0 {
+            perspective(Deg(self.fovy), self.aspect, self.zoom_near, self.zoom_far)
+        } else if self.ortho_factor >= 1.0 {
+            self.orthographic_matrix()
+        } else {
+            let perspective_proj =
+                perspective(Deg(self.fovy), self.aspect, self.zoom_near, self.zoom_far);
+            perspective_proj * (1.0 - self.ortho_factor)
+                + self.orthographic_matrix() * self.ortho_factor
+        };
+        self.uniform.view_proj = OPENGL_TO_WGPU_MATRIX * proj * view;
+    }
+
+    /// Convert cursor position in window to a ray in world space.
+    ///
+    /// The inverse of `view_proj` also undoes `OPENGL_TO_WGPU_MATRIX`,
+    /// so the near and far planes are at depth 0.0 and 1.0 in NDC.
+    pub fn screen_to_ray(&self, cursor: PhysicalPosition<f64>, size: PhysicalSize<u32>) -> Ray {
+        let x = (2.0 * cursor.x / f64::from(size.width) - 1.0) as f32;
+        // Y axis of window points down, while in NDC it points up.
+        let y = (1.0 - 2.0 * cursor.y / f64::from(size.height)) as f32;
+
+        let inv_view_proj = self
+            .uniform
+            .view_proj
+            .invert()
+            .unwrap_or_else(Matrix4::identity);
+        let unproject = |depth: f32| {
+            let point = inv_view_proj * Vector4::new(x, y, depth, 1.0);
+            Point3::from_homogeneous(point)
+        };
+        let near = unproject(0.0);
+        let far = unproject(1.0);
+        Ray::new(near, far - near)
+    }
+
+    pub fn uniform_ref(&self) -> CameraUniformRef {
+        self.uniform.as_ref()
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraUniform {
+    pub view_proj: Matrix4<f32>,
+}
+
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self {
+            view_proj: Matrix4::one(),
+        }
+    }
+}
+
+pub type CameraUniformBytes = [f32; 16];
+pub type CameraUniformRef<'a> = &'a CameraUniformBytes;
+
+impl AsRef<CameraUniformBytes> for CameraUniform {
+    fn as_ref(&self) -> CameraUniformRef {
+        unsafe { mem::transmute(self) }
+    }
+}
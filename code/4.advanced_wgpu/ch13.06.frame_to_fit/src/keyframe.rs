@@ -0,0 +1,272 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector3, Zero};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, path::Path};
+
+use crate::camera::Camera;
+use crate::Error;
+
+/// Sampled field of view is kept in this range, as splines may overshoot.
+const MIN_FOVY: f32 = 1.0;
+const MAX_FOVY: f32 = 170.0;
+
+/// Camera pose captured at one point of animation path.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    pub eye: Point3<f32>,
+    pub target: Point3<f32>,
+    pub up: Vector3<f32>,
+    /// Vertical field of view, in degrees.
+    pub fovy: f32,
+}
+
+impl Keyframe {
+    #[must_use]
+    pub fn from_camera(camera: &Camera) -> Self {
+        Self {
+            eye: camera.eye(),
+            target: camera.target(),
+            up: camera.up(),
+            fovy: camera.fovy(),
+        }
+    }
+
+    pub fn apply(&self, camera: &mut Camera) {
+        camera.set_fovy(self.fovy);
+        camera.set_view(self.eye, self.target, self.up);
+    }
+
+    /// Weighted sum of keyframes.
+    ///
+    /// Both Catmull-Rom and Bezier curves are linear combinations of
+    /// their control points, so all the fields are blended the same way.
+    fn blend(weighted: impl Iterator<Item = (f32, Self)>) -> Self {
+        let mut eye = Vector3::zero();
+        let mut target = Vector3::zero();
+        let mut up = Vector3::zero();
+        let mut fovy = 0.0;
+        for (weight, frame) in weighted {
+            eye += frame.eye.to_vec() * weight;
+            target += frame.target.to_vec() * weight;
+            up += frame.up * weight;
+            fovy += frame.fovy * weight;
+        }
+
+        let up = if up.magnitude2() > f32::EPSILON {
+            up.normalize()
+        } else {
+            Vector3::unit_y()
+        };
+        Self {
+            eye: Point3::from_vec(eye),
+            target: Point3::from_vec(target),
+            up,
+            fovy: fovy.clamp(MIN_FOVY, MAX_FOVY),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// Passes through every keyframe.
+    #[default]
+    CatmullRom,
+
+    /// Only passes through the first and the last keyframe, other keyframes
+    /// pull the curve towards them, which makes a smoother path.
+    Bezier,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    /// Remap progress `t` in range `[0.0, 1.0]`.
+    #[must_use]
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Keyframes are spread evenly over a fixed duration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraPath {
+    pub keyframes: Vec<Keyframe>,
+    /// Playback time of the whole path, in seconds.
+    pub duration: f32,
+    pub interpolation: Interpolation,
+    pub easing: Easing,
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        Self {
+            keyframes: Vec::new(),
+            duration: 8.0,
+            interpolation: Interpolation::default(),
+            easing: Easing::default(),
+        }
+    }
+}
+
+impl CameraPath {
+    /// Camera pose at `time` seconds, returns None if path is empty.
+    #[must_use]
+    pub fn sample(&self, time: f32) -> Option<Keyframe> {
+        let progress = if self.duration > 0.0 {
+            self.easing.apply(time / self.duration)
+        } else {
+            1.0
+        };
+        match self.keyframes.as_slice() {
+            [] => None,
+            [frame] => Some(*frame),
+            frames => Some(match self.interpolation {
+                Interpolation::CatmullRom => catmull_rom(frames, progress),
+                Interpolation::Bezier => bezier(frames, progress),
+            }),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+/// Uniform Catmull-Rom spline, the first and last keyframes are repeated
+/// to provide tangents at both ends.
+fn catmull_rom(frames: &[Keyframe], progress: f32) -> Keyframe {
+    let last = frames.len() - 1;
+    let position = progress * last as f32;
+    let index = (position.floor() as usize).min(last - 1);
+    let t = position - index as f32;
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    let weights = [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+    ];
+    let points = [
+        frames[index.saturating_sub(1)],
+        frames[index],
+        frames[index + 1],
+        frames[(index + 2).min(last)],
+    ];
+    Keyframe::blend(weights.into_iter().zip(points))
+}
+
+/// Bezier curve using all keyframes as control points, weighted by
+/// Bernstein polynomials.
+fn bezier(frames: &[Keyframe], t: f32) -> Keyframe {
+    let degree = frames.len() - 1;
+    let mut coefficient = 1.0;
+    let weighted = frames.iter().enumerate().map(|(i, frame)| {
+        let weight = coefficient * t.powi(i as i32) * (1.0 - t).powi((degree - i) as i32);
+        // Binomial coefficient of the next control point.
+        coefficient = coefficient * (degree - i) as f32 / (i + 1) as f32;
+        (weight, *frame)
+    });
+    Keyframe::blend(weighted)
+}
+
+/// Plays a camera path, and records new keyframes into it.
+#[derive(Debug, Clone, Default)]
+pub struct CameraAnimation {
+    pub path: CameraPath,
+    /// Current playback time, in seconds.
+    pub time: f32,
+    pub looping: bool,
+    playing: bool,
+}
+
+impl CameraAnimation {
+    #[must_use]
+    pub const fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Start playing from current time, or from the beginning if it is
+    /// at the end of path.
+    pub fn play(&mut self) {
+        if self.path.keyframes.is_empty() {
+            return;
+        }
+        if self.time >= self.path.duration {
+            self.time = 0.0;
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn stop(&mut self) {
+        self.playing = false;
+        self.time = 0.0;
+    }
+
+    pub fn toggle(&mut self) {
+        if self.playing {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    /// Append current camera pose to the end of path.
+    pub fn capture(&mut self, camera: &Camera) {
+        self.path.keyframes.push(Keyframe::from_camera(camera));
+    }
+
+    /// Advance playback time, returns camera pose if playing.
+    pub fn update(&mut self, dt: f32) -> Option<Keyframe> {
+        if !self.playing {
+            return None;
+        }
+        self.time += dt;
+        if self.time >= self.path.duration {
+            if self.looping && self.path.duration > 0.0 {
+                self.time %= self.path.duration;
+            } else {
+                self.time = self.path.duration;
+                self.playing = false;
+            }
+        }
+        self.path.sample(self.time)
+    }
+}
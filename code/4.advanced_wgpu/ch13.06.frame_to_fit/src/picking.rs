@@ -0,0 +1,200 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Ray casting against geometry data, used by mouse picking.
+
+#![allow(dead_code)]
+
+use cgmath::{InnerSpace, Matrix4, Point3, Transform, Vector3};
+
+use crate::bounds::Aabb;
+use crate::geometry::GeometryData;
+
+/// Rays nearly parallel to a triangle are considered as missing it.
+const EPSILON: f32 = 1.0e-6;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+
+    /// Always normalized, so that distances along the ray are in world units.
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    #[must_use]
+    pub fn new(origin: Point3<f32>, direction: Vector3<f32>) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    #[must_use]
+    pub fn at(&self, distance: f32) -> Point3<f32> {
+        self.origin + self.direction * distance
+    }
+
+    /// Slab test, returns distance to the nearest intersection point.
+    ///
+    /// If ray origin is inside of the box, 0.0 is returned.
+    #[must_use]
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let mut t_min = 0.0_f32;
+        let mut t_max = f32::INFINITY;
+
+        for axis in 0..3 {
+            let origin = self.origin[axis];
+            let direction = self.direction[axis];
+            if direction.abs() < EPSILON {
+                // Parallel to this slab, and it must be inside of it.
+                if origin < aabb.min[axis] || origin > aabb.max[axis] {
+                    return None;
+                }
+                continue;
+            }
+
+            let inv_direction = 1.0 / direction;
+            let mut t0 = (aabb.min[axis] - origin) * inv_direction;
+            let mut t1 = (aabb.max[axis] - origin) * inv_direction;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_min > t_max {
+                return None;
+            }
+        }
+
+        Some(t_min)
+    }
+
+    /// Möller–Trumbore algorithm.
+    ///
+    /// Returns distance to intersection point and its barycentric coordinates,
+    /// which are weights of `v0`, `v1` and `v2`. Both sides of triangle are hit.
+    #[must_use]
+    pub fn intersect_triangle(
+        &self,
+        v0: Point3<f32>,
+        v1: Point3<f32>,
+        v2: Point3<f32>,
+    ) -> Option<(f32, Vector3<f32>)> {
+        let edge1 = v1 - v0;
+        let edge2 = v2 - v0;
+        let p = self.direction.cross(edge2);
+        let det = edge1.dot(p);
+        if det.abs() < EPSILON {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let s = self.origin - v0;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(edge1);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge2.dot(q) * inv_det;
+        if distance < 0.0 {
+            return None;
+        }
+
+        Some((distance, Vector3::new(1.0 - u - v, u, v)))
+    }
+}
+
+/// Nearest triangle of scene objects hit by a ray.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// Index of object in the list passed to `pick()`.
+    pub object: usize,
+
+    /// Index of triangle in the geometry data of object.
+    pub triangle: usize,
+
+    /// Distance from ray origin to intersection point, in world space.
+    pub distance: f32,
+
+    /// Barycentric coordinates of intersection point in the triangle.
+    pub barycentric: Vector3<f32>,
+}
+
+/// Find the nearest triangle of `geometry` hit by `ray`.
+///
+/// Vertices are transformed into world space with `model` matrix first,
+/// and the bounding box is checked before testing each triangle.
+///
+/// Returns index of triangle, distance and barycentric coordinates.
+#[must_use]
+pub fn intersect_geometry(
+    ray: &Ray,
+    geometry: &GeometryData,
+    model: &Matrix4<f32>,
+) -> Option<(usize, f32, Vector3<f32>)> {
+    let vertices: Vec<Point3<f32>> = geometry
+        .vertices
+        .iter()
+        .map(|vertex| model.transform_point(Point3::from(*vertex)))
+        .collect();
+
+    let aabb = Aabb::from_points(vertices.iter().copied())?;
+    ray.intersect_aabb(&aabb)?;
+
+    let indices: Vec<usize> = if geometry.indices32.is_empty() {
+        geometry
+            .indices16
+            .iter()
+            .map(|index| *index as usize)
+            .collect()
+    } else {
+        geometry
+            .indices32
+            .iter()
+            .map(|index| *index as usize)
+            .collect()
+    };
+
+    let mut nearest: Option<(usize, f32, Vector3<f32>)> = None;
+    for (triangle, face) in indices.chunks_exact(3).enumerate() {
+        let hit = ray.intersect_triangle(vertices[face[0]], vertices[face[1]], vertices[face[2]]);
+        if let Some((distance, barycentric)) = hit {
+            if nearest.is_none_or(|(_, nearest_distance, _)| distance < nearest_distance) {
+                nearest = Some((triangle, distance, barycentric));
+            }
+        }
+    }
+    nearest
+}
+
+/// Find the nearest object hit by `ray`.
+///
+/// Each object is described by its geometry data and model matrix.
+#[must_use]
+pub fn pick<'a, I>(ray: &Ray, objects: I) -> Option<Hit>
+where
+    I: IntoIterator<Item = (&'a GeometryData, &'a Matrix4<f32>)>,
+{
+    let mut nearest: Option<Hit> = None;
+    for (object, (geometry, model)) in objects.into_iter().enumerate() {
+        if let Some((triangle, distance, barycentric)) = intersect_geometry(ray, geometry, model) {
+            if nearest.is_none_or(|hit| distance < hit.distance) {
+                nearest = Some(Hit {
+                    object,
+                    triangle,
+                    distance,
+                    barycentric,
+                });
+            }
+        }
+    }
+    nearest
+}
@@ -0,0 +1,229 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{
+    Deg, InnerSpace, Matrix3, One, Point3, Quaternion, Rad, Rotation, Vector2, Vector3, Zero,
+};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent};
+
+use crate::camera::Camera;
+use crate::controller::{CameraController, PIXELS_PER_LINE, PIXEL_SCROLL_ROTATES};
+use crate::touch::Gesture;
+
+const MIN_DISTANCE: f32 = 0.2;
+
+/// Trackball camera based on quaternions.
+///
+/// Drag with left mouse button to rotate freely around pivot, without gimbal
+/// lock or flipping at poles. Drag with middle mouse button to pan,
+/// and scroll to zoom.
+///
+/// On touch screens, drag with one finger to rotate, pinch with two
+/// fingers to zoom and pan.
+#[derive(Debug)]
+pub struct ArcballController {
+    pivot: Point3<f32>,
+    /// Rotates from camera space to world space.
+    rotation: Quaternion<f32>,
+    distance: f32,
+
+    /// Distance is scaled by `exp(-scroll_speed)` for each line of mouse wheel.
+    scroll_speed: f32,
+
+    viewport: PhysicalSize<u32>,
+    cursor_pos: PhysicalPosition<f64>,
+    rotating: bool,
+    panning: bool,
+
+    /// Rotation in camera space accumulated since previous frame.
+    pending_rotation: Quaternion<f32>,
+    /// Cursor offset in pixels accumulated since previous frame.
+    pending_pan: Vector2<f32>,
+    pending_zoom: f32,
+}
+
+impl Default for ArcballController {
+    fn default() -> Self {
+        Self {
+            pivot: Point3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            distance: 1.0,
+
+            scroll_speed: 0.1,
+
+            viewport: PhysicalSize::new(1, 1),
+            cursor_pos: PhysicalPosition::new(0.0, 0.0),
+            rotating: false,
+            panning: false,
+
+            pending_rotation: Quaternion::one(),
+            pending_pan: Vector2::zero(),
+            pending_zoom: 0.0,
+        }
+    }
+}
+
+impl CameraController for ArcballController {
+    fn activate(&mut self, camera: &Camera) {
+        self.pivot = camera.target();
+        let offset = camera.eye() - camera.target();
+        self.distance = offset.magnitude().max(MIN_DISTANCE);
+
+        // Columns are camera axes in world space.
+        let z_axis = offset.normalize();
+        let x_axis = camera.up().cross(z_axis).normalize();
+        let y_axis = z_axis.cross(x_axis);
+        self.rotation = Quaternion::from(Matrix3::from_cols(x_axis, y_axis, z_axis)).normalize();
+
+        self.rotating = false;
+        self.panning = false;
+        self.pending_rotation = Quaternion::one();
+        self.pending_pan = Vector2::zero();
+        self.pending_zoom = 0.0;
+    }
+
+    fn process_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseWheel {
+                delta,
+                phase: TouchPhase::Moved,
+                ..
+            } => {
+                match delta {
+                    MouseScrollDelta::LineDelta(_horizontal, vertical) => {
+                        self.pending_zoom += *vertical;
+                    }
+                    // Scrolling with two fingers on trackpad rotates view,
+                    // as if dragging from center of window.
+                    MouseScrollDelta::PixelDelta(pos) if PIXEL_SCROLL_ROTATES => {
+                        let center = PhysicalPosition::new(
+                            f64::from(self.viewport.width) / 2.0,
+                            f64::from(self.viewport.height) / 2.0,
+                        );
+                        let to = PhysicalPosition::new(center.x + pos.x, center.y + pos.y);
+                        self.rotate(center, to);
+                    }
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        self.pending_zoom += pos.y as f32 / PIXELS_PER_LINE;
+                    }
+                }
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let last_pos = self.cursor_pos;
+                self.cursor_pos = *position;
+                if self.rotating {
+                    self.rotate(last_pos, *position);
+                }
+                if self.panning {
+                    self.pending_pan.x += (position.x - last_pos.x) as f32;
+                    self.pending_pan.y += (position.y - last_pos.y) as f32;
+                }
+                self.rotating || self.panning
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let pressed = *state == ElementState::Pressed;
+                match button {
+                    MouseButton::Left => self.rotating = pressed,
+                    MouseButton::Middle => self.panning = pressed,
+                    _ => return false,
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn process_gesture(&mut self, gesture: &Gesture) -> bool {
+        match gesture {
+            Gesture::Drag { from, to } => self.rotate(*from, *to),
+            Gesture::Pinch { scale, pan } => {
+                self.pending_zoom += scale.ln() / self.scroll_speed;
+                self.pending_pan += pan * self.viewport.height as f32;
+            }
+            Gesture::DragEnded | Gesture::DoubleTap(_) => return false,
+        }
+        true
+    }
+
+    fn update(&mut self, camera: &mut Camera, _dt: f32) {
+        // Scene follows cursor, so camera rotates the opposite way.
+        self.rotation = (self.rotation * self.pending_rotation.invert()).normalize();
+        self.pending_rotation = Quaternion::one();
+
+        if !self.pending_pan.is_zero() {
+            // Size of one pixel at the pivot plane, in world units.
+            let half_fovy: Rad<f32> = (Deg(camera.fovy()) / 2.0).into();
+            let pixel_size =
+                2.0 * self.distance * half_fovy.0.tan() / self.viewport.height.max(1) as f32;
+            let right = self.rotation.rotate_vector(Vector3::unit_x());
+            let up = self.rotation.rotate_vector(Vector3::unit_y());
+            self.pivot += (-right * self.pending_pan.x + up * self.pending_pan.y) * pixel_size;
+            self.pending_pan = Vector2::zero();
+        }
+
+        if self.pending_zoom != 0.0 {
+            self.distance =
+                (self.distance * (-self.pending_zoom * self.scroll_speed).exp()).max(MIN_DISTANCE);
+            self.pending_zoom = 0.0;
+        }
+
+        self.apply(camera);
+    }
+}
+
+impl ArcballController {
+    pub fn set_viewport(&mut self, size: PhysicalSize<u32>) {
+        self.viewport = size;
+    }
+
+    #[must_use]
+    pub const fn pivot(&self) -> Point3<f32> {
+        self.pivot
+    }
+
+    /// Rotate around a new pivot point.
+    ///
+    /// Orientation and distance are kept, so the view is moved to make
+    /// the pivot at center of window.
+    pub fn set_pivot(&mut self, camera: &mut Camera, pivot: Point3<f32>) {
+        self.pivot = pivot;
+        self.apply(camera);
+    }
+
+    fn apply(&self, camera: &mut Camera) {
+        let eye = self.pivot + self.rotation.rotate_vector(Vector3::unit_z()) * self.distance;
+        let up = self.rotation.rotate_vector(Vector3::unit_y());
+        camera.set_view(eye, self.pivot, up);
+    }
+
+    /// Accumulate rotation of trackball when cursor moves from `from` to `to`.
+    fn rotate(&mut self, from: PhysicalPosition<f64>, to: PhysicalPosition<f64>) {
+        let from = self.project_to_sphere(from);
+        let to = self.project_to_sphere(to);
+        self.pending_rotation =
+            (Quaternion::from_arc(from, to, None) * self.pending_rotation).normalize();
+    }
+
+    /// Map cursor position onto a virtual trackball in camera space.
+    ///
+    /// Uses Holroyd's hyperbolic sheet outside of the sphere, so that
+    /// rotation is continuous when cursor moves away from center.
+    fn project_to_sphere(&self, pos: PhysicalPosition<f64>) -> Vector3<f32> {
+        let width = self.viewport.width.max(1) as f32;
+        let height = self.viewport.height.max(1) as f32;
+        let radius = width.min(height) / 2.0;
+        let x = (pos.x as f32 - width / 2.0) / radius;
+        let y = (height / 2.0 - pos.y as f32) / radius;
+
+        let d2 = x * x + y * y;
+        let z = if d2 <= 0.5 {
+            (1.0 - d2).sqrt()
+        } else {
+            0.5 / d2.sqrt()
+        };
+        Vector3::new(x, y, z).normalize()
+    }
+}
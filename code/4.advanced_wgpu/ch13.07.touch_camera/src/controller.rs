@@ -0,0 +1,123 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use std::collections::HashSet;
+use winit::event::{DeviceEvent, ElementState, KeyboardInput, VirtualKeyCode, WindowEvent};
+
+use crate::camera::Camera;
+use crate::touch::Gesture;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Rotate around target point.
+    #[default]
+    Orbit,
+
+    /// First person camera, flying freely in the scene.
+    Fly,
+
+    /// Rotate freely around pivot point, like a trackball.
+    Arcball,
+}
+
+/// Moves camera around according to user input.
+pub trait CameraController {
+    /// Called when this controller becomes the active one, so that its internal
+    /// state can be synced with the current camera.
+    fn activate(&mut self, camera: &Camera);
+
+    /// Returns true if event is consumed.
+    ///
+    /// Input events only update internal state of controller,
+    /// camera is moved in `update()`.
+    fn process_event(&mut self, event: &WindowEvent) -> bool;
+
+    /// Raw device events, like relative mouse motion when cursor is grabbed.
+    fn process_device_event(&mut self, _event: &DeviceEvent) -> bool {
+        false
+    }
+
+    /// Touch gestures, recognized from window events by `TouchGestures`.
+    fn process_gesture(&mut self, _gesture: &Gesture) -> bool {
+        false
+    }
+
+    /// Integrate velocity and move camera, `dt` is elapsed time in seconds
+    /// since previous frame.
+    fn update(&mut self, camera: &mut Camera, dt: f32);
+
+    /// Whether cursor shall be grabbed and hidden in window.
+    fn cursor_grabbed(&self) -> bool {
+        false
+    }
+}
+
+/// Tracks which keys are held down, so that movement does not depend
+/// on key repeat rate.
+#[derive(Debug, Default, Clone)]
+pub struct KeyState {
+    pressed: HashSet<VirtualKeyCode>,
+}
+
+impl KeyState {
+    /// Returns true if key state is changed.
+    pub fn process_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(keycode),
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => self.pressed.insert(*keycode),
+                ElementState::Released => self.pressed.remove(keycode),
+            },
+            // Key released events are lost if window is not focused.
+            WindowEvent::Focused(false) => {
+                self.pressed.clear();
+                false
+            }
+            _ => false,
+        }
+    }
+
+    #[must_use]
+    pub fn is_pressed(&self, keys: &[VirtualKeyCode]) -> bool {
+        keys.iter().any(|key| self.pressed.contains(key))
+    }
+
+    /// Returns -1.0, 0.0 or 1.0, depends on which group of keys is pressed.
+    #[must_use]
+    pub fn axis(&self, negative: &[VirtualKeyCode], positive: &[VirtualKeyCode]) -> f32 {
+        let mut value = 0.0;
+        if self.is_pressed(negative) {
+            value -= 1.0;
+        }
+        if self.is_pressed(positive) {
+            value += 1.0;
+        }
+        value
+    }
+}
+
+/// Scrolling in pixels, as much as one line of mouse wheel.
+pub const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Two fingers on macOS trackpads scroll in pixels, which rotates view like
+/// native apps do. Elsewhere wheels also scroll in pixels, like in browsers,
+/// so pixels zoom as lines do.
+pub const PIXEL_SCROLL_ROTATES: bool = cfg!(target_os = "macos");
+
+/// Factor to move from current value towards target value in `dt` seconds,
+/// with exponential decay `rate`.
+///
+/// Unlike a constant factor, the result is the same no matter how many
+/// frames `dt` is split into.
+#[must_use]
+pub fn smooth_factor(rate: f32, dt: f32) -> f32 {
+    1.0 - (-rate * dt).exp()
+}
@@ -0,0 +1,18 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::Vector3;
+
+pub const CUBE_POSITIONS: &[Vector3<f32>] = &[
+    Vector3::new(0.0, 0.0, 0.0),
+    Vector3::new(2.0, 5.0, -15.0),
+    Vector3::new(-1.5, -2.2, -2.5),
+    Vector3::new(-3.8, -2.0, -12.3),
+    Vector3::new(2.4, -0.4, -3.5),
+    Vector3::new(-1.7, 3.0, -7.5),
+    Vector3::new(1.3, -2.0, -2.5),
+    Vector3::new(1.5, 2.0, -2.5),
+    Vector3::new(1.5, 0.2, -1.5),
+    Vector3::new(-1.3, 1.0, -1.5),
+];
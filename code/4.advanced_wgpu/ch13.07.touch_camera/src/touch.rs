@@ -0,0 +1,217 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Recognize gestures from multi-touch events, for tablets and phones.
+
+use cgmath::Vector2;
+use instant::{Duration, Instant};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{Touch, TouchPhase, WindowEvent};
+
+/// Finger moved less than this distance in pixels is a tap, not a drag.
+const TAP_DISTANCE: f64 = 10.0;
+
+/// Finger held longer than this is not a tap.
+const TAP_TIME: Duration = Duration::from_millis(250);
+
+/// Max interval between two taps of a double tap.
+const DOUBLE_TAP_TIME: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// One finger moved, positions are in pixels.
+    Drag {
+        from: PhysicalPosition<f64>,
+        to: PhysicalPosition<f64>,
+    },
+
+    /// One finger drag is finished, by lifting it or by putting down
+    /// another finger.
+    DragEnded,
+
+    /// Two fingers moved, or trackpad pinched.
+    Pinch {
+        /// Ratio of current distance between fingers to the previous one,
+        /// larger than 1.0 when fingers move apart.
+        scale: f32,
+
+        /// Offset of center point between fingers, in fraction of
+        /// viewport height, so it does not depend on screen resolution.
+        pan: Vector2<f32>,
+    },
+
+    DoubleTap(PhysicalPosition<f64>),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TouchPoint {
+    id: u64,
+    position: PhysicalPosition<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TouchGestures {
+    viewport: PhysicalSize<u32>,
+
+    /// Fingers on screen, in the order they are put down.
+    touches: Vec<TouchPoint>,
+
+    /// Position where a one finger drag may start from.
+    drag_origin: Option<PhysicalPosition<f64>>,
+    dragging: bool,
+
+    /// Time and position of the finger down which may become a tap.
+    tap_start: Option<(Instant, PhysicalPosition<f64>)>,
+    last_tap: Option<(Instant, PhysicalPosition<f64>)>,
+}
+
+impl Default for TouchGestures {
+    fn default() -> Self {
+        Self {
+            viewport: PhysicalSize::new(1, 1),
+            touches: Vec::new(),
+            drag_origin: None,
+            dragging: false,
+            tap_start: None,
+            last_tap: None,
+        }
+    }
+}
+
+impl TouchGestures {
+    pub fn set_viewport(&mut self, size: PhysicalSize<u32>) {
+        self.viewport = size;
+    }
+
+    /// Returns a gesture if `event` completes one.
+    pub fn process_event(&mut self, event: &WindowEvent) -> Option<Gesture> {
+        match event {
+            WindowEvent::Touch(Touch {
+                id,
+                phase,
+                location,
+                ..
+            }) => match phase {
+                TouchPhase::Started => self.touch_started(*id, *location),
+                TouchPhase::Moved => self.touch_moved(*id, *location),
+                TouchPhase::Ended => self.touch_ended(*id, true),
+                TouchPhase::Cancelled => self.touch_ended(*id, false),
+            },
+            // Pinch on macOS trackpad.
+            WindowEvent::TouchpadMagnify { delta, .. } => Some(Gesture::Pinch {
+                scale: (1.0 + *delta as f32).max(0.1),
+                pan: Vector2::new(0.0, 0.0),
+            }),
+            _ => None,
+        }
+    }
+
+    fn touch_started(&mut self, id: u64, position: PhysicalPosition<f64>) -> Option<Gesture> {
+        self.touches.push(TouchPoint { id, position });
+        if self.touches.len() == 1 {
+            self.drag_origin = Some(position);
+            self.tap_start = Some((Instant::now(), position));
+            return None;
+        }
+
+        // More fingers, it is not a tap or drag any more.
+        self.drag_origin = None;
+        self.tap_start = None;
+        self.end_drag()
+    }
+
+    fn touch_moved(&mut self, id: u64, position: PhysicalPosition<f64>) -> Option<Gesture> {
+        let index = self.touches.iter().position(|touch| touch.id == id)?;
+        let from = self.touches[index].position;
+        self.touches[index].position = position;
+
+        match self.touches.len() {
+            1 => {
+                if !self.dragging {
+                    let origin = self.drag_origin?;
+                    if distance(origin, position) < TAP_DISTANCE {
+                        return None;
+                    }
+                    self.dragging = true;
+                    self.tap_start = None;
+                }
+                Some(Gesture::Drag { from, to: position })
+            }
+            2 => {
+                let other = self.touches[1 - index].position;
+                let old_distance = distance(from, other);
+                let new_distance = distance(position, other);
+                let scale = if old_distance > 0.0 {
+                    (new_distance / old_distance) as f32
+                } else {
+                    1.0
+                };
+
+                // Center point moves half of the finger offset.
+                let height = f64::from(self.viewport.height.max(1));
+                let pan = Vector2::new(
+                    ((position.x - from.x) / 2.0 / height) as f32,
+                    ((position.y - from.y) / 2.0 / height) as f32,
+                );
+                Some(Gesture::Pinch { scale, pan })
+            }
+            _ => None,
+        }
+    }
+
+    fn touch_ended(&mut self, id: u64, completed: bool) -> Option<Gesture> {
+        let index = self.touches.iter().position(|touch| touch.id == id)?;
+        let touch = self.touches.remove(index);
+
+        if let [remaining] = self.touches.as_slice() {
+            // Back to one finger from pinch, it may start a new drag.
+            self.drag_origin = Some(remaining.position);
+            return None;
+        }
+        if !self.touches.is_empty() {
+            return None;
+        }
+
+        self.drag_origin = None;
+        if self.dragging {
+            return self.end_drag();
+        }
+
+        let tap_start = self.tap_start.take();
+        if !completed {
+            return None;
+        }
+        let now = Instant::now();
+        let is_tap = tap_start.is_some_and(|(time, position)| {
+            now - time < TAP_TIME && distance(position, touch.position) < TAP_DISTANCE
+        });
+        if !is_tap {
+            return None;
+        }
+
+        let is_double_tap = self.last_tap.is_some_and(|(time, position)| {
+            now - time < DOUBLE_TAP_TIME && distance(position, touch.position) < TAP_DISTANCE
+        });
+        if is_double_tap {
+            self.last_tap = None;
+            Some(Gesture::DoubleTap(touch.position))
+        } else {
+            self.last_tap = Some((now, touch.position));
+            None
+        }
+    }
+
+    fn end_drag(&mut self) -> Option<Gesture> {
+        if self.dragging {
+            self.dragging = false;
+            Some(Gesture::DragEnded)
+        } else {
+            None
+        }
+    }
+}
+
+fn distance(a: PhysicalPosition<f64>, b: PhysicalPosition<f64>) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
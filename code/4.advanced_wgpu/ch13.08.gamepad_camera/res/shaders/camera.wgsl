@@ -0,0 +1,73 @@
+
+// Vertex Shader
+struct VertexInput {
+	@location(0) position: vec3<f32>,
+	@location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) tex_coords: vec2<f32>,
+};
+
+struct CameraUniform {
+	@location(0) view_proj: mat4x4<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> camera_uniform: CameraUniform;
+
+struct ModelUniform {
+	@location(0) model: mat4x4<f32>,
+	@location(1) color: vec4<f32>,
+}
+
+@group(1)
+@binding(0)
+var<uniform> model_uniform: ModelUniform;
+
+@vertex
+fn vs_main(
+	in: VertexInput,
+) -> VertexOutput {
+	var out: VertexOutput;
+	out.position = camera_uniform.view_proj * model_uniform.model * vec4<f32>(in.position, 1.0);
+	out.tex_coords = in.tex_coords;
+	return out;
+}
+
+// Fragment Shader
+struct FragmentInput {
+	@location(0) tex_coords: vec2<f32>,
+};
+
+@group(1)
+@binding(1)
+var container_texture: texture_2d<f32>;
+
+@group(1)
+@binding(2)
+var container_sampler: sampler;
+
+@group(1)
+@binding(3)
+var face_texture: texture_2d<f32>;
+
+@group(1)
+@binding(4)
+var face_sampler: sampler;
+
+@fragment
+fn fs_main(in: FragmentInput) -> @location(0) vec4<f32> {
+	return
+		mix(textureSample(container_texture, container_sampler, in.tex_coords),
+			textureSample(face_texture, face_sampler, in.tex_coords),
+			0.2);
+}
+
+// Outline of the selected object, filled with a single color.
+@fragment
+fn fs_outline(in: FragmentInput) -> @location(0) vec4<f32> {
+	return model_uniform.color;
+}
@@ -0,0 +1,268 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{
+    Deg, InnerSpace, Matrix3, One, Point3, Quaternion, Rad, Rotation, Rotation3, Vector2, Vector3,
+    Zero,
+};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{MouseScrollDelta, TouchPhase, WindowEvent};
+
+use crate::bindings::{Action, ActionState};
+use crate::camera::Camera;
+use crate::controller::{CameraController, PIXELS_PER_LINE, PIXEL_SCROLL_ROTATES};
+use crate::gamepad::GamepadAxes;
+use crate::touch::Gesture;
+
+const MIN_DISTANCE: f32 = 0.2;
+
+/// Trackball camera based on quaternions.
+///
+/// Drag with left mouse button to rotate freely around pivot, without gimbal
+/// lock or flipping at poles. Drag with middle mouse button to pan,
+/// and scroll to zoom.
+///
+/// On touch screens, drag with one finger to rotate, pinch with two
+/// fingers to zoom and pan.
+///
+/// On gamepad, left stick rotates, right stick pans, and triggers zoom.
+#[derive(Debug)]
+pub struct ArcballController {
+    pivot: Point3<f32>,
+    /// Rotates from camera space to world space.
+    rotation: Quaternion<f32>,
+    distance: f32,
+
+    /// Distance is scaled by `exp(-scroll_speed)` for each line of mouse wheel.
+    scroll_speed: f32,
+    /// Max rotation speed by gamepad stick, in radians per second.
+    stick_rotate_speed: f32,
+    /// Max pan speed by gamepad stick, in viewport heights per second.
+    stick_pan_speed: f32,
+    /// Max zoom speed by gamepad triggers, in lines of mouse wheel per second.
+    trigger_zoom_speed: f32,
+
+    viewport: PhysicalSize<u32>,
+    cursor_pos: PhysicalPosition<f64>,
+    rotating: bool,
+    panning: bool,
+    gamepad: GamepadAxes,
+
+    /// Rotation in camera space accumulated since previous frame.
+    pending_rotation: Quaternion<f32>,
+    /// Cursor offset in pixels accumulated since previous frame.
+    pending_pan: Vector2<f32>,
+    pending_zoom: f32,
+}
+
+impl Default for ArcballController {
+    fn default() -> Self {
+        Self {
+            pivot: Point3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            distance: 1.0,
+
+            scroll_speed: 0.1,
+            stick_rotate_speed: 1.5,
+            stick_pan_speed: 0.5,
+            trigger_zoom_speed: 12.0,
+
+            viewport: PhysicalSize::new(1, 1),
+            cursor_pos: PhysicalPosition::new(0.0, 0.0),
+            rotating: false,
+            panning: false,
+            gamepad: GamepadAxes::default(),
+
+            pending_rotation: Quaternion::one(),
+            pending_pan: Vector2::zero(),
+            pending_zoom: 0.0,
+        }
+    }
+}
+
+impl CameraController for ArcballController {
+    fn activate(&mut self, camera: &Camera) {
+        self.pivot = camera.target();
+        let offset = camera.eye() - camera.target();
+        self.distance = offset.magnitude().max(MIN_DISTANCE);
+
+        // Columns are camera axes in world space.
+        let z_axis = offset.normalize();
+        let x_axis = camera.up().cross(z_axis).normalize();
+        let y_axis = z_axis.cross(x_axis);
+        self.rotation = Quaternion::from(Matrix3::from_cols(x_axis, y_axis, z_axis)).normalize();
+
+        self.rotating = false;
+        self.panning = false;
+        self.pending_rotation = Quaternion::one();
+        self.pending_pan = Vector2::zero();
+        self.pending_zoom = 0.0;
+    }
+
+    fn process_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseWheel {
+                delta,
+                phase: TouchPhase::Moved,
+                ..
+            } => {
+                match delta {
+                    MouseScrollDelta::LineDelta(_horizontal, vertical) => {
+                        self.pending_zoom += *vertical;
+                    }
+                    // Scrolling with two fingers on trackpad rotates view,
+                    // as if dragging from center of window.
+                    MouseScrollDelta::PixelDelta(pos) if PIXEL_SCROLL_ROTATES => {
+                        let center = PhysicalPosition::new(
+                            f64::from(self.viewport.width) / 2.0,
+                            f64::from(self.viewport.height) / 2.0,
+                        );
+                        let to = PhysicalPosition::new(center.x + pos.x, center.y + pos.y);
+                        self.rotate(center, to);
+                    }
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        self.pending_zoom += pos.y as f32 / PIXELS_PER_LINE;
+                    }
+                }
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let last_pos = self.cursor_pos;
+                self.cursor_pos = *position;
+                if self.rotating {
+                    self.rotate(last_pos, *position);
+                }
+                if self.panning {
+                    self.pending_pan.x += (position.x - last_pos.x) as f32;
+                    self.pending_pan.y += (position.y - last_pos.y) as f32;
+                }
+                self.rotating || self.panning
+            }
+            _ => false,
+        }
+    }
+
+    fn set_actions(&mut self, actions: &ActionState) {
+        self.rotating = actions.is_active(Action::Rotate);
+        self.panning = actions.is_active(Action::Pan);
+    }
+
+    fn process_gesture(&mut self, gesture: &Gesture) -> bool {
+        match gesture {
+            Gesture::Drag { from, to } => self.rotate(*from, *to),
+            Gesture::Pinch { scale, pan } => {
+                self.pending_zoom += scale.ln() / self.scroll_speed;
+                self.pending_pan += pan * self.viewport.height as f32;
+            }
+            Gesture::DragEnded | Gesture::DoubleTap(_) => return false,
+        }
+        true
+    }
+
+    fn set_gamepad_axes(&mut self, axes: &GamepadAxes) {
+        self.gamepad = *axes;
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        self.apply_gamepad(dt);
+
+        // Scene follows cursor, so camera rotates the opposite way.
+        self.rotation = (self.rotation * self.pending_rotation.invert()).normalize();
+        self.pending_rotation = Quaternion::one();
+
+        if !self.pending_pan.is_zero() {
+            // Size of one pixel at the pivot plane, in world units.
+            let half_fovy: Rad<f32> = (Deg(camera.fovy()) / 2.0).into();
+            let pixel_size =
+                2.0 * self.distance * half_fovy.0.tan() / self.viewport.height.max(1) as f32;
+            let right = self.rotation.rotate_vector(Vector3::unit_x());
+            let up = self.rotation.rotate_vector(Vector3::unit_y());
+            self.pivot += (-right * self.pending_pan.x + up * self.pending_pan.y) * pixel_size;
+            self.pending_pan = Vector2::zero();
+        }
+
+        if self.pending_zoom != 0.0 {
+            self.distance =
+                (self.distance * (-self.pending_zoom * self.scroll_speed).exp()).max(MIN_DISTANCE);
+            self.pending_zoom = 0.0;
+        }
+
+        self.apply(camera);
+    }
+}
+
+impl ArcballController {
+    pub fn set_viewport(&mut self, size: PhysicalSize<u32>) {
+        self.viewport = size;
+    }
+
+    #[must_use]
+    pub const fn pivot(&self) -> Point3<f32> {
+        self.pivot
+    }
+
+    /// Rotate around a new pivot point.
+    ///
+    /// Orientation and distance are kept, so the view is moved to make
+    /// the pivot at center of window.
+    pub fn set_pivot(&mut self, camera: &mut Camera, pivot: Point3<f32>) {
+        self.pivot = pivot;
+        self.apply(camera);
+    }
+
+    fn apply(&self, camera: &mut Camera) {
+        let eye = self.pivot + self.rotation.rotate_vector(Vector3::unit_z()) * self.distance;
+        let up = self.rotation.rotate_vector(Vector3::unit_y());
+        camera.set_view(eye, self.pivot, up);
+    }
+
+    /// Sticks and triggers work the same way as dragging mouse and scrolling.
+    fn apply_gamepad(&mut self, dt: f32) {
+        let gamepad = self.gamepad;
+        if !gamepad.left_stick.is_zero() {
+            let angle = self.stick_rotate_speed * dt;
+            let rotation =
+                Quaternion::from_axis_angle(Vector3::unit_y(), Rad(gamepad.left_stick.x * angle))
+                    * Quaternion::from_axis_angle(
+                        Vector3::unit_x(),
+                        Rad(-gamepad.left_stick.y * angle),
+                    );
+            self.pending_rotation = (rotation * self.pending_rotation).normalize();
+        }
+
+        let pan_pixels = self.stick_pan_speed * self.viewport.height as f32 * dt;
+        self.pending_pan +=
+            Vector2::new(-gamepad.right_stick.x, gamepad.right_stick.y) * pan_pixels;
+        self.pending_zoom +=
+            (gamepad.right_trigger - gamepad.left_trigger) * self.trigger_zoom_speed * dt;
+    }
+
+    /// Accumulate rotation of trackball when cursor moves from `from` to `to`.
+    fn rotate(&mut self, from: PhysicalPosition<f64>, to: PhysicalPosition<f64>) {
+        let from = self.project_to_sphere(from);
+        let to = self.project_to_sphere(to);
+        self.pending_rotation =
+            (Quaternion::from_arc(from, to, None) * self.pending_rotation).normalize();
+    }
+
+    /// Map cursor position onto a virtual trackball in camera space.
+    ///
+    /// Uses Holroyd's hyperbolic sheet outside of the sphere, so that
+    /// rotation is continuous when cursor moves away from center.
+    fn project_to_sphere(&self, pos: PhysicalPosition<f64>) -> Vector3<f32> {
+        let width = self.viewport.width.max(1) as f32;
+        let height = self.viewport.height.max(1) as f32;
+        let radius = width.min(height) / 2.0;
+        let x = (pos.x as f32 - width / 2.0) / radius;
+        let y = (height / 2.0 - pos.y as f32) / radius;
+
+        let d2 = x * x + y * y;
+        let z = if d2 <= 0.5 {
+            (1.0 - d2).sqrt()
+        } else {
+            0.5 / d2.sqrt()
+        };
+        Vector3::new(x, y, z).normalize()
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Bounding volumes of geometry, used by ray casting and camera framing.
+
+#![allow(dead_code)]
+
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, Transform};
+
+/// Axis aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    /// Returns `None` if `points` is empty.
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Point3<f32>>,
+    {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut aabb = Self {
+            min: first,
+            max: first,
+        };
+        for point in points {
+            aabb.min.x = aabb.min.x.min(point.x);
+            aabb.min.y = aabb.min.y.min(point.y);
+            aabb.min.z = aabb.min.z.min(point.z);
+            aabb.max.x = aabb.max.x.max(point.x);
+            aabb.max.y = aabb.max.y.max(point.y);
+            aabb.max.z = aabb.max.z.max(point.z);
+        }
+        Some(aabb)
+    }
+
+    #[must_use]
+    pub fn center(&self) -> Point3<f32> {
+        self.min.midpoint(self.max)
+    }
+
+    /// Eight corners of the box.
+    #[must_use]
+    pub fn corners(&self) -> [Point3<f32>; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Point3::new(min.x, min.y, min.z),
+            Point3::new(max.x, min.y, min.z),
+            Point3::new(min.x, max.y, min.z),
+            Point3::new(max.x, max.y, min.z),
+            Point3::new(min.x, min.y, max.z),
+            Point3::new(max.x, min.y, max.z),
+            Point3::new(min.x, max.y, max.z),
+            Point3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// Smallest box containing both boxes.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: Point3::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: Point3::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    /// Box in world space containing this box transformed by `model`.
+    ///
+    /// It is larger than the original one if `model` contains rotation.
+    #[must_use]
+    pub fn transform(&self, model: &Matrix4<f32>) -> Self {
+        let corners = self.corners().map(|corner| model.transform_point(corner));
+        // There are always eight corners.
+        Self::from_points(corners).unwrap_or(*self)
+    }
+
+    /// Sphere passing through all the corners.
+    #[must_use]
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere {
+            center: self.center(),
+            radius: (self.max - self.min).magnitude() / 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /// Centered at bounding box of `points`, which is not the minimal sphere,
+    /// but is close enough for framing the camera.
+    ///
+    /// Returns `None` if `points` is empty.
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Point3<f32>> + Clone,
+    {
+        let center = Aabb::from_points(points.clone())?.center();
+        let radius = points
+            .into_iter()
+            .map(|point| (point - center).magnitude())
+            .fold(0.0, f32::max);
+        Some(Self { center, radius })
+    }
+
+    /// Smallest sphere containing both spheres.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let offset = other.center - self.center;
+        let distance = offset.magnitude();
+        if distance + other.radius <= self.radius {
+            return *self;
+        }
+        if distance + self.radius <= other.radius {
+            return *other;
+        }
+
+        let radius = (distance + self.radius + other.radius) / 2.0;
+        let center = self.center + offset * ((radius - self.radius) / distance);
+        Self { center, radius }
+    }
+
+    /// Sphere in world space containing this sphere transformed by `model`.
+    ///
+    /// Unlike bounding box, it does not grow if `model` contains rotation.
+    #[must_use]
+    pub fn transform(&self, model: &Matrix4<f32>) -> Self {
+        let scale = [model.x, model.y, model.z]
+            .iter()
+            .map(|axis| axis.truncate().magnitude())
+            .fold(0.0, f32::max);
+        Self {
+            center: model.transform_point(self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Deg, InnerSpace, Rad, Vector3, Zero};
+use winit::event::{DeviceEvent, MouseScrollDelta, TouchPhase, WindowEvent};
+
+use crate::bindings::{Action, ActionState};
+use crate::camera::Camera;
+use crate::controller::{smooth_factor, CameraController, PIXELS_PER_LINE, PIXEL_SCROLL_ROTATES};
+use crate::gamepad::GamepadAxes;
+use crate::touch::Gesture;
+
+/// Looking straight up or down makes view matrix degenerate.
+const MAX_PITCH: Deg<f32> = Deg(89.0);
+
+/// First person camera.
+///
+/// Hold right mouse button to look around, move with A/D/W/S keys,
+/// Space to fly up and Ctrl to fly down.
+///
+/// On touch screens, drag with one finger to look around, and pinch
+/// with two fingers to move forward or backward.
+///
+/// On gamepad, left stick moves, right stick looks around, and triggers
+/// move forward or backward.
+#[derive(Debug)]
+pub struct FlyController {
+    yaw: Rad<f32>,
+    pitch: Rad<f32>,
+    velocity: Vector3<f32>,
+    /// Distance from eye to target, which is kept so that orthographic view
+    /// height does not change.
+    focus_distance: f32,
+
+    /// Max speed, in units per second.
+    move_speed: f32,
+    /// Forward speed added by each line of mouse wheel.
+    scroll_speed: f32,
+    /// Forward speed added by pinching, per unit of `ln(scale)`.
+    pinch_speed: f32,
+    mouse_sensitivity: f32,
+    /// Max turning speed by gamepad stick, in radians per second.
+    stick_look_speed: f32,
+    /// How fast velocity reaches its target while keys are held down.
+    acceleration: f32,
+    /// How fast velocity decays after keys released.
+    damping: f32,
+
+    actions: ActionState,
+    gamepad: GamepadAxes,
+    looking: bool,
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        Self {
+            yaw: Rad(0.0),
+            pitch: Rad(0.0),
+            velocity: Vector3::zero(),
+            focus_distance: 1.0,
+
+            move_speed: 3.0,
+            scroll_speed: 2.0,
+            pinch_speed: 20.0,
+            mouse_sensitivity: 0.003,
+            stick_look_speed: 2.0,
+            acceleration: 8.0,
+            damping: 6.0,
+
+            actions: ActionState::default(),
+            gamepad: GamepadAxes::default(),
+            looking: false,
+        }
+    }
+}
+
+impl CameraController for FlyController {
+    fn activate(&mut self, camera: &Camera) {
+        let forward = camera.forward();
+        // Top and bottom views look straight along Y axis, where heading
+        // comes from up vector of camera, which points forward when looking
+        // down and backward when looking up.
+        let heading = if forward.x.hypot(forward.z) < 1e-4 {
+            camera.up() * -forward.y.signum()
+        } else {
+            forward
+        };
+        self.yaw = Rad(heading.z.atan2(heading.x));
+        let max_pitch: Rad<f32> = MAX_PITCH.into();
+        let pitch = forward.y.clamp(-1.0, 1.0).asin();
+        self.pitch = Rad(pitch.clamp(-max_pitch.0, max_pitch.0));
+        self.velocity = Vector3::zero();
+        self.focus_distance = (camera.target() - camera.eye()).magnitude();
+        self.looking = false;
+    }
+
+    fn process_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseWheel {
+                delta,
+                phase: TouchPhase::Moved,
+                ..
+            } => {
+                match delta {
+                    MouseScrollDelta::LineDelta(_horizontal, vertical) => {
+                        self.velocity += self.forward() * self.scroll_speed * *vertical;
+                    }
+                    // Scrolling with two fingers on trackpad rotates view.
+                    MouseScrollDelta::PixelDelta(pos) if PIXEL_SCROLL_ROTATES => {
+                        self.look(pos.x as f32, pos.y as f32);
+                    }
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        let lines = pos.y as f32 / PIXELS_PER_LINE;
+                        self.velocity += self.forward() * self.scroll_speed * lines;
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn process_device_event(&mut self, event: &DeviceEvent) -> bool {
+        match event {
+            DeviceEvent::MouseMotion { delta: (dx, dy) } if self.looking => {
+                self.look(*dx as f32, *dy as f32);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn process_gesture(&mut self, gesture: &Gesture) -> bool {
+        match gesture {
+            // Scene follows finger, so view turns the opposite way.
+            Gesture::Drag { from, to } => {
+                self.look((from.x - to.x) as f32, (from.y - to.y) as f32);
+            }
+            Gesture::Pinch { scale, .. } => {
+                self.velocity += self.forward() * self.pinch_speed * scale.ln();
+            }
+            Gesture::DragEnded | Gesture::DoubleTap(_) => return false,
+        }
+        true
+    }
+
+    fn set_actions(&mut self, actions: &ActionState) {
+        self.looking = actions.is_active(Action::Look);
+        self.actions = actions.clone();
+    }
+
+    fn set_gamepad_axes(&mut self, axes: &GamepadAxes) {
+        self.gamepad = *axes;
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        let look = self.gamepad.right_stick * self.stick_look_speed * dt;
+        self.yaw += Rad(look.x);
+        self.pitch += Rad(look.y);
+        self.clamp_pitch();
+
+        let forward = self.forward();
+        let up = Vector3::unit_y();
+        let right = forward.cross(up).normalize();
+
+        let gamepad = &self.gamepad;
+        let mut direction = forward
+            * (self.actions.axis(Action::MoveBackward, Action::MoveForward)
+                + gamepad.left_stick.y
+                + gamepad.right_trigger
+                - gamepad.left_trigger)
+            + right
+                * (self.actions.axis(Action::MoveLeft, Action::MoveRight) + gamepad.left_stick.x)
+            + up * self.actions.axis(Action::MoveDown, Action::MoveUp);
+        let rate = if direction.is_zero() {
+            self.damping
+        } else {
+            // Moving diagonally is not faster, while stick pushed halfway
+            // moves slower.
+            if direction.magnitude2() > 1.0 {
+                direction = direction.normalize();
+            }
+            self.acceleration
+        };
+
+        let target = direction * self.move_speed;
+        self.velocity += (target - self.velocity) * smooth_factor(rate, dt);
+
+        let eye = camera.eye() + self.velocity * dt;
+        camera.set_view(eye, eye + forward * self.focus_distance, up);
+    }
+
+    fn cursor_grabbed(&self) -> bool {
+        self.looking
+    }
+}
+
+impl FlyController {
+    /// Turn view by offset of pointer in pixels.
+    fn look(&mut self, dx: f32, dy: f32) {
+        self.yaw += Rad(dx * self.mouse_sensitivity);
+        self.pitch -= Rad(dy * self.mouse_sensitivity);
+        self.clamp_pitch();
+    }
+
+    fn clamp_pitch(&mut self) {
+        let max_pitch: Rad<f32> = MAX_PITCH.into();
+        self.pitch = Rad(self.pitch.0.clamp(-max_pitch.0, max_pitch.0));
+    }
+
+    fn forward(&self) -> Vector3<f32> {
+        let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
+        Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize()
+    }
+}
@@ -0,0 +1,31 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+mod arcball;
+mod bindings;
+mod bounds;
+mod camera;
+mod controller;
+mod cubes;
+mod error;
+mod fly;
+mod frames;
+mod gamepad;
+mod geometry;
+mod init;
+mod keyframe;
+mod objects;
+mod orbit;
+mod picking;
+#[cfg(not(target_arch = "wasm32"))]
+mod screenshot;
+mod state;
+mod texture;
+mod touch;
+mod uniforms;
+mod vertex;
+
+pub use self::error::Error;
+pub use self::init::run;
+pub use self::state::State;
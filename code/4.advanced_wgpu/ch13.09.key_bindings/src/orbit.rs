@@ -0,0 +1,250 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Deg, InnerSpace, Rad, Vector2, Vector3, Zero};
+use winit::dpi::PhysicalPosition;
+use winit::event::{MouseScrollDelta, TouchPhase, WindowEvent};
+
+use crate::bindings::{Action, ActionState};
+use crate::camera::Camera;
+use crate::controller::{smooth_factor, CameraController, PIXELS_PER_LINE, PIXEL_SCROLL_ROTATES};
+use crate::gamepad::GamepadAxes;
+use crate::touch::Gesture;
+
+/// Up vector is derived from yaw and pitch, so that looking straight down
+/// from top view does not make view matrix degenerate.
+const MAX_PITCH: Deg<f32> = Deg(90.0);
+
+const MIN_DISTANCE: f32 = 0.2;
+
+/// Rotates eye around target with A/D keys or by dragging mouse,
+/// and zoom with W/S keys or mouse wheel.
+///
+/// On touch screens, drag with one finger to rotate, pinch with two
+/// fingers to zoom and pan.
+///
+/// On gamepad, left stick rotates, right stick pans, and triggers zoom.
+///
+/// Eye position is stored in spherical coordinates around target.
+#[derive(Debug)]
+pub struct OrbitController {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+
+    /// Yaw and pitch speed, in radians per second.
+    angular_velocity: Vector2<f32>,
+
+    /// Zoom speed, distance is scaled by `exp(-zoom_velocity)` each second.
+    zoom_velocity: f32,
+
+    /// Max rotation speed by keyboard, in radians per second.
+    key_angular_speed: f32,
+    /// Max zoom speed by keyboard.
+    key_zoom_speed: f32,
+    /// Radians per pixel when dragging.
+    cursor_speed: f32,
+    /// Zoom velocity added by each line of mouse wheel.
+    scroll_speed: f32,
+    /// Pan speed by gamepad stick, in view heights per second.
+    stick_pan_speed: f32,
+    /// How fast velocity reaches its target while keys are held down.
+    acceleration: f32,
+    /// How fast velocity decays after keys or mouse button released.
+    damping: f32,
+
+    actions: ActionState,
+    gamepad: GamepadAxes,
+    /// Rotate action is held, mouse drag rotates view.
+    rotating: bool,
+    last_cursor_pos: Option<PhysicalPosition<f64>>,
+    /// Cursor offset in pixels since previous frame.
+    drag_delta: Vector2<f32>,
+    /// One finger drag is treated the same way as mouse drag.
+    touch_dragging: bool,
+    /// Target offset since previous frame, in fraction of view height.
+    pending_pan: Vector2<f32>,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 1.0,
+
+            angular_velocity: Vector2::new(0.0, 0.0),
+            zoom_velocity: 0.0,
+
+            key_angular_speed: 1.5,
+            key_zoom_speed: 1.2,
+            cursor_speed: 0.006,
+            scroll_speed: 0.8,
+            stick_pan_speed: 0.5,
+            acceleration: 10.0,
+            damping: 4.0,
+
+            actions: ActionState::default(),
+            gamepad: GamepadAxes::default(),
+            rotating: false,
+            last_cursor_pos: None,
+            drag_delta: Vector2::new(0.0, 0.0),
+            touch_dragging: false,
+            pending_pan: Vector2::new(0.0, 0.0),
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn activate(&mut self, camera: &Camera) {
+        let offset = camera.eye() - camera.target();
+        self.distance = offset.magnitude().max(MIN_DISTANCE);
+        let dir = offset / self.distance;
+        self.yaw = dir.x.atan2(dir.z);
+        self.pitch = dir.y.clamp(-1.0, 1.0).asin();
+
+        self.angular_velocity = Vector2::new(0.0, 0.0);
+        self.zoom_velocity = 0.0;
+        self.rotating = false;
+        self.last_cursor_pos = None;
+        self.drag_delta = Vector2::new(0.0, 0.0);
+        self.touch_dragging = false;
+        self.pending_pan = Vector2::new(0.0, 0.0);
+    }
+
+    fn process_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseWheel {
+                delta,
+                phase: TouchPhase::Moved,
+                ..
+            } => {
+                match delta {
+                    MouseScrollDelta::LineDelta(_horizontal, vertical) => {
+                        self.zoom_velocity += self.scroll_speed * *vertical;
+                    }
+                    // Scrolling with two fingers on trackpad rotates view.
+                    MouseScrollDelta::PixelDelta(pos) if PIXEL_SCROLL_ROTATES => {
+                        self.yaw -= pos.x as f32 * self.cursor_speed;
+                        self.pitch += pos.y as f32 * self.cursor_speed;
+                    }
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        self.zoom_velocity += self.scroll_speed * pos.y as f32 / PIXELS_PER_LINE;
+                    }
+                }
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                if !self.rotating {
+                    return false;
+                }
+                if let Some(last_pos) = self.last_cursor_pos {
+                    self.drag_delta.x += (position.x - last_pos.x) as f32;
+                    self.drag_delta.y += (position.y - last_pos.y) as f32;
+                }
+                self.last_cursor_pos = Some(*position);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn process_gesture(&mut self, gesture: &Gesture) -> bool {
+        match gesture {
+            Gesture::Drag { from, to } => {
+                self.touch_dragging = true;
+                self.drag_delta.x += (to.x - from.x) as f32;
+                self.drag_delta.y += (to.y - from.y) as f32;
+            }
+            Gesture::DragEnded => self.touch_dragging = false,
+            Gesture::Pinch { scale, pan } => {
+                // Follow fingers exactly, without smoothing.
+                self.zoom_velocity = 0.0;
+                self.distance = (self.distance / scale).max(MIN_DISTANCE);
+                self.pending_pan += *pan;
+            }
+            Gesture::DoubleTap(_) => return false,
+        }
+        true
+    }
+
+    fn set_actions(&mut self, actions: &ActionState) {
+        let rotating = actions.is_active(Action::Rotate);
+        if rotating != self.rotating {
+            self.rotating = rotating;
+            self.last_cursor_pos = None;
+        }
+        self.actions = actions.clone();
+    }
+
+    fn set_gamepad_axes(&mut self, axes: &GamepadAxes) {
+        self.gamepad = *axes;
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        if dt <= 0.0 {
+            return;
+        }
+
+        if self.rotating || self.touch_dragging {
+            // Follow cursor exactly, and remember its speed, so that camera
+            // keeps spinning for a while after mouse button released.
+            let delta = self.drag_delta * self.cursor_speed;
+            self.drag_delta = Vector2::new(0.0, 0.0);
+            self.yaw -= delta.x;
+            self.pitch += delta.y;
+            self.angular_velocity += (Vector2::new(-delta.x, delta.y) / dt - self.angular_velocity)
+                * smooth_factor(self.acceleration, dt);
+        } else {
+            // Eye moves to the right while view turns left.
+            let axis = Vector2::new(
+                self.actions.axis(Action::MoveRight, Action::MoveLeft) - self.gamepad.left_stick.x,
+                self.gamepad.left_stick.y,
+            );
+            let target = axis * self.key_angular_speed;
+            let rate = if axis.is_zero() {
+                self.damping
+            } else {
+                self.acceleration
+            };
+            self.angular_velocity += (target - self.angular_velocity) * smooth_factor(rate, dt);
+            self.yaw += self.angular_velocity.x * dt;
+            self.pitch += self.angular_velocity.y * dt;
+        }
+
+        let zoom_axis = self.actions.axis(Action::MoveBackward, Action::MoveForward)
+            + self.gamepad.right_trigger
+            - self.gamepad.left_trigger;
+        let rate = if zoom_axis == 0.0 {
+            self.damping
+        } else {
+            self.acceleration
+        };
+        let target = zoom_axis * self.key_zoom_speed;
+        self.zoom_velocity += (target - self.zoom_velocity) * smooth_factor(rate, dt);
+        self.distance = (self.distance * (-self.zoom_velocity * dt).exp()).max(MIN_DISTANCE);
+
+        let max_pitch: Rad<f32> = MAX_PITCH.into();
+        self.pitch = self.pitch.clamp(-max_pitch.0, max_pitch.0);
+
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let dir = Vector3::new(cos_pitch * sin_yaw, sin_pitch, cos_pitch * cos_yaw);
+        let up = Vector3::new(-sin_pitch * sin_yaw, cos_pitch, -sin_pitch * cos_yaw);
+        // Camera moves along with stick, so scene moves the opposite way.
+        let stick = self.gamepad.right_stick;
+        self.pending_pan += Vector2::new(-stick.x, stick.y) * self.stick_pan_speed * dt;
+
+        let mut target = camera.target();
+        if !self.pending_pan.is_zero() {
+            // Scene follows fingers, so target moves the opposite way.
+            let half_fovy: Rad<f32> = (Deg(camera.fovy()) / 2.0).into();
+            let view_height = 2.0 * self.distance * half_fovy.0.tan();
+            let right = Vector3::new(cos_yaw, 0.0, -sin_yaw);
+            target += (-right * self.pending_pan.x + up * self.pending_pan.y) * view_height;
+            self.pending_pan = Vector2::new(0.0, 0.0);
+        }
+        camera.set_view(target + dir * self.distance, target, up);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Save rendered scene to image file.
+//!
+//! Surface textures may not support being copied from, so the scene is
+//! drawn again into an offscreen texture, without egui windows.
+
+use std::path::Path;
+use std::sync::mpsc;
+
+use crate::Error;
+
+pub struct Screenshot {
+    texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    /// Rows of buffer are aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`.
+    padded_bytes_per_row: u32,
+}
+
+impl Screenshot {
+    const BYTES_PER_PIXEL: u32 = 4;
+
+    /// Returns error if pixels of `config.format` are not 8-bit RGBA or BGRA.
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Result<Self, Error> {
+        if !Self::is_bgra(config.format) && !Self::is_rgba(config.format) {
+            return Err(Error::Others(format!(
+                "Unsupported screenshot format: {:?}",
+                config.format
+            )));
+        }
+
+        let (width, height) = (config.width, config.height);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Screenshot Texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (width * Self::BYTES_PER_PIXEL).div_ceil(align) * align;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Screenshot Buffer"),
+            size: u64::from(padded_bytes_per_row) * u64::from(height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Ok(Self {
+            texture,
+            view,
+            buffer,
+            width,
+            height,
+            format: config.format,
+            padded_bytes_per_row,
+        })
+    }
+
+    const fn is_bgra(format: wgpu::TextureFormat) -> bool {
+        matches!(
+            format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        )
+    }
+
+    const fn is_rgba(format: wgpu::TextureFormat) -> bool {
+        matches!(
+            format,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb
+        )
+    }
+
+    /// Record copying texture to buffer, call it after the scene is drawn.
+    pub fn copy(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &self.buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Wait for GPU to finish copying, and write pixels to a PNG file.
+    ///
+    /// Call it after commands recorded by `copy()` are submitted.
+    pub fn save<P: AsRef<Path>>(&self, device: &wgpu::Device, path: P) -> Result<(), Error> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ret = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .map_err(|err| Error::Others(err.to_string()))?
+            .map_err(|err| Error::Others(err.to_string()))?;
+
+        let bytes_per_row = (self.width * Self::BYTES_PER_PIXEL) as usize;
+        let mut pixels = Vec::with_capacity(bytes_per_row * self.height as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..bytes_per_row]);
+            }
+        }
+        self.buffer.unmap();
+
+        if Self::is_bgra(self.format) {
+            for pixel in pixels.chunks_exact_mut(Self::BYTES_PER_PIXEL as usize) {
+                pixel.swap(0, 2);
+            }
+        }
+        image::save_buffer(
+            path,
+            &pixels,
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,1246 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Matrix4, Rad, Vector4};
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use instant::{Duration, Instant};
+use std::mem;
+use wgpu::util::DeviceExt;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{
+    DeviceEvent, ElementState, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent,
+};
+use winit::window::{CursorGrabMode, Window};
+
+use crate::arcball::ArcballController;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::bindings::KeyBindings;
+use crate::bindings::{Action, BindingCapture, InputMap};
+use crate::bounds::BoundingSphere;
+use crate::camera::{Camera, Projection, ViewPreset};
+use crate::controller::{CameraController, CameraMode};
+use crate::cubes::CUBE_POSITIONS;
+use crate::fly::FlyController;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::frames::BindingsAction;
+use crate::frames::{
+    AnimationAction, AnimationWindow, BindingsWindow, CameraWindow, GamepadWindow, PickingWindow,
+};
+use crate::gamepad::{Gamepad, GamepadButton};
+use crate::geometry::{create_cube_detail, GeometryData};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::keyframe::CameraPath;
+use crate::objects::{Mesh, SceneObject};
+use crate::orbit::OrbitController;
+use crate::picking::{self, Hit, Ray};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::screenshot::Screenshot;
+use crate::texture::Texture;
+use crate::touch::{Gesture, TouchGestures};
+use crate::uniforms::ModelUniform;
+use crate::vertex::Vertex;
+use crate::Error;
+
+/// Stencil value written by the selected object.
+const SELECTED_STENCIL_REF: u32 = 1;
+
+/// How much the outline is larger than the selected object.
+const OUTLINE_SCALE: f32 = 1.08;
+
+/// If cursor moved less than this distance in pixels between mouse button
+/// pressed and released, it is a click, otherwise the camera is dragged.
+const CLICK_DISTANCE: f64 = 4.0;
+
+/// Two clicks within this interval make a double click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(300);
+
+/// Max time step in seconds used to move camera.
+const MAX_FRAME_TIME: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PipelineKind {
+    /// Draw objects without touching stencil buffer.
+    Normal,
+
+    /// Same as `Normal`, but only draw edges of triangles.
+    Wireframe,
+
+    /// Draw the selected object, and write reference value into stencil buffer.
+    StencilWrite,
+
+    /// Draw scaled silhouette of the selected object where stencil != reference.
+    Outline,
+}
+
+pub struct State {
+    surface: wgpu::Surface,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    size: PhysicalSize<u32>,
+    window: Window,
+
+    normal_pipeline: wgpu::RenderPipeline,
+    wireframe_pipeline: wgpu::RenderPipeline,
+    stencil_write_pipeline: wgpu::RenderPipeline,
+    outline_pipeline: wgpu::RenderPipeline,
+
+    cube_geometry: GeometryData,
+    cube_mesh: Mesh,
+    cubes: Vec<SceneObject>,
+    outline: SceneObject,
+
+    camera: Camera,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    camera_mode: CameraMode,
+    orbit_controller: OrbitController,
+    fly_controller: FlyController,
+    arcball_controller: ArcballController,
+    cursor_grabbed: bool,
+    touch_gestures: TouchGestures,
+    gamepad: Gamepad,
+
+    depth_texture: Texture,
+
+    input_map: InputMap,
+    /// Save the next frame to image file.
+    screenshot_requested: bool,
+    cursor_pos: PhysicalPosition<f64>,
+    pressed_pos: Option<PhysicalPosition<f64>>,
+    last_click: Option<(Instant, PhysicalPosition<f64>)>,
+
+    start_time: Instant,
+    last_frame_time: Instant,
+    egui_platform: Platform,
+    egui_render_pass: RenderPass,
+    picking_window: PickingWindow,
+    camera_window: CameraWindow,
+    animation_window: AnimationWindow,
+    gamepad_window: GamepadWindow,
+    bindings_window: BindingsWindow,
+}
+
+impl State {
+    async fn create_surface(
+        window: &Window,
+    ) -> Result<
+        (
+            wgpu::Surface,
+            wgpu::Device,
+            wgpu::Queue,
+            wgpu::SurfaceConfiguration,
+            PhysicalSize<u32>,
+        ),
+        Error,
+    > {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let surface = unsafe { instance.create_surface(&window) }?;
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| {
+                Error::Others("Failed to get an approprivate wgpu adapter".to_owned())
+            })?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    features: if cfg!(target_arch = "wasm32") {
+                        wgpu::Features::empty()
+                    } else {
+                        wgpu::Features::POLYGON_MODE_LINE
+                    },
+                    limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                },
+                None,
+            )
+            .await?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(wgpu::TextureFormat::is_srgb)
+            .unwrap_or(surface_caps.formats[0]);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&device, &config);
+
+        Ok((surface, device, queue, config, size))
+    }
+
+    fn create_render_pipeline(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        kind: PipelineKind,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../res/shaders/camera.wgsl").into()),
+        });
+
+        let (label, fs_entry_point, stencil_face, stencil_write_mask) = match kind {
+            PipelineKind::Normal => (
+                "Render Pipeline",
+                "fs_main",
+                wgpu::StencilFaceState::IGNORE,
+                0x00,
+            ),
+            PipelineKind::Wireframe => (
+                "Wireframe Render Pipeline",
+                "fs_main",
+                wgpu::StencilFaceState::IGNORE,
+                0x00,
+            ),
+            PipelineKind::StencilWrite => (
+                "Stencil Write Render Pipeline",
+                "fs_main",
+                wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::Always,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op: wgpu::StencilOperation::Replace,
+                },
+                0xff,
+            ),
+            PipelineKind::Outline => (
+                "Outline Render Pipeline",
+                "fs_outline",
+                wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::NotEqual,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op: wgpu::StencilOperation::Keep,
+                },
+                0x00,
+            ),
+        };
+
+        // Outline is always visible, even if the selected object is hidden
+        // behind other objects.
+        let (depth_write_enabled, depth_compare) = if kind == PipelineKind::Outline {
+            (false, wgpu::CompareFunction::Always)
+        } else {
+            (true, wgpu::CompareFunction::Less)
+        };
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: fs_entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                // POLYGON_MODE_LINE feature is not available in webgpu/webgl2.
+                polygon_mode: if kind == PipelineKind::Wireframe && !cfg!(target_arch = "wasm32") {
+                    wgpu::PolygonMode::Line
+                } else {
+                    wgpu::PolygonMode::Fill
+                },
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_STENCIL_FORMAT,
+                depth_write_enabled,
+                depth_compare,
+                stencil: wgpu::StencilState {
+                    front: stencil_face,
+                    back: stencil_face,
+                    read_mask: 0xff,
+                    write_mask: stencil_write_mask,
+                },
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        render_pipeline
+    }
+
+    fn create_camera(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+    ) -> Result<(Camera, wgpu::Buffer, wgpu::BindGroupLayout, wgpu::BindGroup), Error> {
+        // Only view direction matters, eye is moved to frame the scene later.
+        let eye_pos = (0.0, 0.0, 3.0).into();
+        let aspect = size.width as f32 / size.height as f32;
+        let camera = Camera::new(eye_pos, aspect);
+
+        let uniform_ref = camera.uniform_ref();
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Uniform Buffer"),
+            contents: bytemuck::cast_slice(uniform_ref),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("camera_bind_group_layout"),
+            });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("camera_bind_group"),
+        });
+
+        Ok((
+            camera,
+            camera_buffer,
+            camera_bind_group_layout,
+            camera_bind_group,
+        ))
+    }
+
+    fn create_model_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("model_bind_group_layout"),
+        })
+    }
+
+    fn create_objects(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Result<(Vec<SceneObject>, SceneObject), Error> {
+        let container_bytes = include_bytes!("../res/textures/container.jpg");
+        let container_texture =
+            Texture::from_bytes(device, queue, container_bytes, Some("container"))?;
+
+        let face_bytes = include_bytes!("../res/textures/awesome_face.png");
+        let face_texture = Texture::from_bytes(device, queue, face_bytes, Some("face"))?;
+
+        let textures = [&container_texture, &face_texture];
+        let cubes = CUBE_POSITIONS
+            .iter()
+            .map(|_pos| {
+                SceneObject::new(
+                    device,
+                    bind_group_layout,
+                    &textures,
+                    ModelUniform::default(),
+                )
+            })
+            .collect();
+
+        let outline_uniform = ModelUniform {
+            color: Vector4::new(1.0, 0.6, 0.0, 1.0),
+            ..ModelUniform::default()
+        };
+        let outline = SceneObject::new(device, bind_group_layout, &textures, outline_uniform);
+
+        Ok((cubes, outline))
+    }
+
+    fn create_egui_platform(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> (
+        Platform,
+        RenderPass,
+        PickingWindow,
+        CameraWindow,
+        AnimationWindow,
+        GamepadWindow,
+        BindingsWindow,
+    ) {
+        let platform = Platform::new(PlatformDescriptor {
+            physical_width: size.width,
+            physical_height: size.height,
+            scale_factor,
+            ..Default::default()
+        });
+
+        let render_pass = RenderPass::new(device, surface_format, 1);
+        let picking_window = PickingWindow::default();
+        let camera_window = CameraWindow::default();
+        let animation_window = AnimationWindow::default();
+        let gamepad_window = GamepadWindow::default();
+        let bindings_window = BindingsWindow::default();
+
+        (
+            platform,
+            render_pass,
+            picking_window,
+            camera_window,
+            animation_window,
+            gamepad_window,
+            bindings_window,
+        )
+    }
+
+    pub async fn new(window: Window) -> Result<Self, Error> {
+        let (surface, device, queue, config, size) = Self::create_surface(&window).await?;
+
+        // Geometry data is kept in CPU side for ray casting.
+        let cube_geometry = create_cube_detail(1.0, 1.0, 1.0);
+        let cube_mesh = Mesh::new(&device, &cube_geometry, "Cube");
+
+        let (camera, camera_buffer, camera_bind_group_layout, camera_bind_group) =
+            Self::create_camera(&device, size)?;
+
+        let model_bind_group_layout = Self::create_model_bind_group_layout(&device);
+        let (cubes, outline) = Self::create_objects(&device, &queue, &model_bind_group_layout)?;
+
+        let bind_group_layouts = [&camera_bind_group_layout, &model_bind_group_layout];
+        let normal_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::Normal,
+        );
+        let wireframe_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::Wireframe,
+        );
+        let stencil_write_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::StencilWrite,
+        );
+        let outline_pipeline = Self::create_render_pipeline(
+            &device,
+            &config,
+            &bind_group_layouts,
+            PipelineKind::Outline,
+        );
+
+        let depth_texture = Texture::create_depth_texture_with_format(
+            &device,
+            size,
+            Texture::DEPTH_STENCIL_FORMAT,
+            Some("Depth Stencil Texture"),
+        );
+
+        let mut orbit_controller = OrbitController::default();
+        orbit_controller.activate(&camera);
+
+        let mut arcball_controller = ArcballController::default();
+        arcball_controller.set_viewport(size);
+        let mut touch_gestures = TouchGestures::default();
+        touch_gestures.set_viewport(size);
+
+        let (
+            egui_platform,
+            egui_render_pass,
+            picking_window,
+            mut camera_window,
+            animation_window,
+            gamepad_window,
+            mut bindings_window,
+        ) = Self::create_egui_platform(&device, config.format, size, window.scale_factor());
+        // Cubes are placed in the first frame, then the whole scene is framed.
+        camera_window.fit = true;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            bindings_window.bindings = KeyBindings::load_or_default(&bindings_window.file_name);
+        }
+
+        Ok(Self {
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            size,
+
+            normal_pipeline,
+            wireframe_pipeline,
+            stencil_write_pipeline,
+            outline_pipeline,
+
+            cube_geometry,
+            cube_mesh,
+            cubes,
+            outline,
+
+            camera,
+            camera_buffer,
+            camera_bind_group,
+            camera_mode: CameraMode::default(),
+            orbit_controller,
+            fly_controller: FlyController::default(),
+            arcball_controller,
+            cursor_grabbed: false,
+            touch_gestures,
+            gamepad: Gamepad::default(),
+
+            depth_texture,
+
+            input_map: InputMap::default(),
+            screenshot_requested: false,
+            cursor_pos: PhysicalPosition::new(0.0, 0.0),
+            pressed_pos: None,
+            last_click: None,
+
+            start_time: Instant::now(),
+            last_frame_time: Instant::now(),
+            egui_platform,
+            egui_render_pass,
+            picking_window,
+            camera_window,
+            animation_window,
+            gamepad_window,
+            bindings_window,
+        })
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn platform_mut(&mut self) -> &mut Platform {
+        &mut self.egui_platform
+    }
+
+    fn controller_mut(&mut self) -> (&mut dyn CameraController, &mut Camera) {
+        let controller: &mut dyn CameraController = match self.camera_mode {
+            CameraMode::Orbit => &mut self.orbit_controller,
+            CameraMode::Fly => &mut self.fly_controller,
+            CameraMode::Arcball => &mut self.arcball_controller,
+        };
+        (controller, &mut self.camera)
+    }
+
+    /// Grab and hide cursor if active camera controller requires it.
+    fn update_cursor_grab(&mut self) {
+        let (controller, _camera) = self.controller_mut();
+        let grabbed = controller.cursor_grabbed();
+        if grabbed == self.cursor_grabbed {
+            return;
+        }
+        self.cursor_grabbed = grabbed;
+
+        let result = if grabbed {
+            // Locked mode is not supported on some platforms.
+            self.window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_err| self.window.set_cursor_grab(CursorGrabMode::Confined))
+        } else {
+            self.window.set_cursor_grab(CursorGrabMode::None)
+        };
+        if let Err(err) = result {
+            log::warn!("Failed to grab cursor: {err:?}");
+        }
+        self.window.set_cursor_visible(!grabbed);
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&self.device, &self.config);
+            self.camera
+                .set_aspect(new_size.width as f32 / new_size.height as f32);
+            self.arcball_controller.set_viewport(new_size);
+            self.touch_gestures.set_viewport(new_size);
+
+            self.depth_texture = Texture::create_depth_texture_with_format(
+                &self.device,
+                self.size,
+                Texture::DEPTH_STENCIL_FORMAT,
+                Some("Depth Stencil Texture"),
+            );
+        }
+    }
+
+    /// Handle actions triggered by keys, mouse buttons or gamepad buttons.
+    ///
+    /// Most of them only update windows, like clicking on them.
+    fn process_action(&mut self, action: Action) {
+        let window = &mut self.camera_window;
+        let preset = match action {
+            Action::ViewFront => ViewPreset::Front,
+            Action::ViewBack => ViewPreset::Back,
+            Action::ViewRight => ViewPreset::Right,
+            Action::ViewLeft => ViewPreset::Left,
+            Action::ViewTop => ViewPreset::Top,
+            Action::ViewBottom => ViewPreset::Bottom,
+            Action::ToggleProjection => {
+                window.projection = match window.projection {
+                    Projection::Perspective => Projection::Orthographic,
+                    Projection::Orthographic => Projection::Perspective,
+                };
+                return;
+            }
+            Action::FrameSelection => {
+                window.fit = true;
+                return;
+            }
+            Action::NextCameraMode => {
+                window.mode = window.mode.next();
+                return;
+            }
+            Action::ToggleWireframe => {
+                window.wireframe = !window.wireframe;
+                return;
+            }
+            Action::CaptureKeyframe => {
+                self.animation_window.animation.capture(&self.camera);
+                return;
+            }
+            Action::TogglePlayback => {
+                self.animation_window.animation.toggle();
+                return;
+            }
+            Action::Screenshot => {
+                self.screenshot_requested = true;
+                return;
+            }
+            // Held actions are handled by camera controllers.
+            Action::MoveForward
+            | Action::MoveBackward
+            | Action::MoveLeft
+            | Action::MoveRight
+            | Action::MoveUp
+            | Action::MoveDown
+            | Action::Rotate
+            | Action::Pan
+            | Action::Look => return,
+        };
+        window.preset = Some(preset);
+    }
+
+    /// Bind the next key or mouse button to the action being edited
+    /// in key bindings window.
+    ///
+    /// Returns true if event is consumed.
+    fn capture_binding(&mut self, capture: BindingCapture, event: &WindowEvent) -> bool {
+        let window = &mut self.bindings_window;
+        if let WindowEvent::KeyboardInput {
+            input:
+                KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::Escape),
+                    ..
+                },
+            ..
+        } = event
+        {
+            window.capture = None;
+            window.status.clear();
+            return true;
+        }
+
+        let Some(binding) = capture.process_event(event, self.input_map.modifiers()) else {
+            return false;
+        };
+        window.capture = None;
+        if let Some(old_binding) = capture.replace {
+            window.bindings.unbind(&old_binding);
+        }
+        window.bindings.bind(binding);
+
+        let conflicts: Vec<String> = window
+            .bindings
+            .conflicts(&binding)
+            .map(|action| format!("{action:?}"))
+            .collect();
+        window.status = if conflicts.is_empty() {
+            format!("{binding} is bound to {:?}", binding.action)
+        } else {
+            format!("{binding} is also bound to {}", conflicts.join(", "))
+        };
+        true
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        if let Some(capture) = self.bindings_window.capture {
+            if self.capture_binding(capture, event) {
+                return true;
+            }
+        }
+
+        let change = self
+            .input_map
+            .process_event(event, &self.bindings_window.bindings);
+        if change.held_changed {
+            let actions = self.input_map.actions().clone();
+            let (controller, _camera) = self.controller_mut();
+            controller.set_actions(&actions);
+            self.update_cursor_grab();
+        }
+        if let Some(action) = change.triggered {
+            self.process_action(action);
+            return true;
+        }
+
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_pos = *position;
+            }
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => match state {
+                ElementState::Pressed => self.pressed_pos = Some(self.cursor_pos),
+                ElementState::Released => {
+                    if let Some(pressed_pos) = self.pressed_pos.take() {
+                        let dx = self.cursor_pos.x - pressed_pos.x;
+                        let dy = self.cursor_pos.y - pressed_pos.y;
+                        if dx.hypot(dy) < CLICK_DISTANCE {
+                            self.click();
+                        }
+                    }
+                }
+            },
+            _ => (),
+        }
+
+        if let Some(gesture) = self.touch_gestures.process_event(event) {
+            return self.process_gesture(gesture);
+        }
+
+        let (controller, _camera) = self.controller_mut();
+        let consumed = controller.process_event(event);
+        self.update_cursor_grab();
+        consumed || change.held_changed
+    }
+
+    fn process_gesture(&mut self, gesture: Gesture) -> bool {
+        match gesture {
+            Gesture::DoubleTap(_) => {
+                self.reset_view();
+                true
+            }
+            // Gestures on egui windows are not passed to the scene.
+            Gesture::Drag { .. } | Gesture::Pinch { .. }
+                if self.egui_platform.context().is_pointer_over_area() =>
+            {
+                false
+            }
+            _ => {
+                let (controller, _camera) = self.controller_mut();
+                controller.process_gesture(&gesture)
+            }
+        }
+    }
+
+    /// Clear selection, and frame the whole scene from front view.
+    fn reset_view(&mut self) {
+        self.picking_window.hit = None;
+        self.camera_window.preset = Some(ViewPreset::Front);
+        self.camera_window.fit = true;
+    }
+
+    pub fn device_input(&mut self, event: &DeviceEvent) -> bool {
+        let (controller, _camera) = self.controller_mut();
+        controller.process_device_event(event)
+    }
+
+    fn click(&mut self) {
+        // Clicks on egui windows are not passed to the scene.
+        if self.egui_platform.context().is_pointer_over_area() {
+            return;
+        }
+
+        let now = Instant::now();
+        let is_double_click = self.last_click.is_some_and(|(time, pos)| {
+            let dx = self.cursor_pos.x - pos.x;
+            let dy = self.cursor_pos.y - pos.y;
+            now - time < DOUBLE_CLICK_TIME && dx.hypot(dy) < CLICK_DISTANCE
+        });
+        if is_double_click {
+            self.last_click = None;
+            self.recenter();
+        } else {
+            self.last_click = Some((now, self.cursor_pos));
+            self.pick();
+        }
+    }
+
+    /// Find the nearest cube under cursor.
+    fn cast_ray(&self) -> (Ray, Option<Hit>) {
+        let ray = self.camera.screen_to_ray(self.cursor_pos, self.size);
+        let objects = self
+            .cubes
+            .iter()
+            .map(|cube| (&self.cube_geometry, &cube.uniform.model));
+        let hit = picking::pick(&ray, objects);
+        (ray, hit)
+    }
+
+    /// Select the nearest cube under cursor.
+    fn pick(&mut self) {
+        let (_ray, hit) = self.cast_ray();
+        self.picking_window.hit = hit;
+    }
+
+    /// Move pivot of arcball camera to the surface under cursor.
+    fn recenter(&mut self) {
+        if self.camera_mode != CameraMode::Arcball {
+            return;
+        }
+        let (ray, hit) = self.cast_ray();
+        if let Some(hit) = hit {
+            self.arcball_controller
+                .set_pivot(&mut self.camera, ray.at(hit.distance));
+        }
+    }
+
+    fn process_animation_action(&mut self, action: AnimationAction) {
+        let window = &mut self.animation_window;
+        match action {
+            AnimationAction::Capture => window.animation.capture(&self.camera),
+            #[cfg(not(target_arch = "wasm32"))]
+            AnimationAction::Save => {
+                window.status = match window.animation.path.save(&window.file_name) {
+                    Ok(()) => format!("Saved to {}", window.file_name),
+                    Err(err) => format!("Failed to save: {err:?}"),
+                };
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            AnimationAction::Load => {
+                window.status = match CameraPath::load(&window.file_name) {
+                    Ok(path) => {
+                        window.animation.path = path;
+                        window.animation.stop();
+                        format!("Loaded from {}", window.file_name)
+                    }
+                    Err(err) => format!("Failed to load: {err:?}"),
+                };
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn process_bindings_action(&mut self, action: BindingsAction) {
+        let window = &mut self.bindings_window;
+        match action {
+            BindingsAction::Save => {
+                window.status = match window.bindings.save(&window.file_name) {
+                    Ok(()) => format!("Saved to {}", window.file_name),
+                    Err(err) => format!("Failed to save: {err:?}"),
+                };
+            }
+            BindingsAction::Load => {
+                window.status = match KeyBindings::load(&window.file_name) {
+                    Ok(bindings) => {
+                        window.bindings = bindings;
+                        window.capture = None;
+                        format!("Loaded from {}", window.file_name)
+                    }
+                    Err(err) => format!("Failed to load: {err:?}"),
+                };
+            }
+        }
+    }
+
+    /// Bounds of the selected cube, or of all cubes if nothing is selected.
+    fn focus_bounds(&self) -> Option<BoundingSphere> {
+        match self.picking_window.selected() {
+            Some(selected) => {
+                let sphere = self.cube_geometry.bounding_sphere()?;
+                Some(sphere.transform(&self.cubes[selected].uniform.model))
+            }
+            None => self.scene_bounds(),
+        }
+    }
+
+    /// Bounds of all cubes in world space.
+    ///
+    /// Bounding sphere of each cube does not change while it is rotating,
+    /// so the result is more stable than merging bounding boxes.
+    fn scene_bounds(&self) -> Option<BoundingSphere> {
+        let sphere = self.cube_geometry.bounding_sphere()?;
+        self.cubes
+            .iter()
+            .map(|cube| sphere.transform(&cube.uniform.model))
+            .reduce(|scene, sphere| scene.union(&sphere))
+    }
+
+    const fn gamepad_action(button: GamepadButton) -> Option<Action> {
+        let action = match button {
+            GamepadButton::South => Action::ToggleWireframe,
+            GamepadButton::East => Action::NextCameraMode,
+            GamepadButton::North => Action::FrameSelection,
+            GamepadButton::West => Action::ToggleProjection,
+            GamepadButton::DPadUp => Action::ViewTop,
+            GamepadButton::DPadDown => Action::ViewFront,
+            GamepadButton::DPadLeft => Action::ViewLeft,
+            GamepadButton::DPadRight => Action::ViewRight,
+            GamepadButton::Start => Action::TogglePlayback,
+            GamepadButton::Select => Action::CaptureKeyframe,
+            GamepadButton::LeftShoulder | GamepadButton::RightShoulder => return None,
+        };
+        Some(action)
+    }
+
+    fn update_gamepad(&mut self) {
+        for event in self.gamepad_window.synthetic_events.drain(..) {
+            self.gamepad.push_event(event);
+        }
+        self.gamepad.settings = self.gamepad_window.settings;
+        for button in self.gamepad.poll() {
+            if let Some(action) = Self::gamepad_action(button) {
+                self.process_action(action);
+            }
+        }
+
+        let axes = self.gamepad.axes();
+        self.gamepad_window.connected = self.gamepad.is_connected();
+        self.gamepad_window.axes = axes;
+        // Sticks of inactive controllers are released.
+        self.orbit_controller.set_gamepad_axes(&axes);
+        self.fly_controller.set_gamepad_axes(&axes);
+        self.arcball_controller.set_gamepad_axes(&axes);
+    }
+
+    pub fn update(&mut self) {
+        self.egui_platform
+            .update_time(self.start_time.elapsed().as_secs_f64());
+        self.update_gamepad();
+
+        if self.camera_window.mode != self.camera_mode {
+            self.camera_mode = self.camera_window.mode;
+            let (controller, camera) = self.controller_mut();
+            controller.activate(camera);
+            self.update_cursor_grab();
+        }
+
+        if let Some(preset) = self.camera_window.preset.take() {
+            self.camera.snap_to(preset);
+            let (controller, camera) = self.controller_mut();
+            controller.activate(camera);
+        }
+        if self.camera_window.pivot_edited {
+            self.camera_window.pivot_edited = false;
+            self.arcball_controller
+                .set_pivot(&mut self.camera, self.camera_window.pivot);
+        } else {
+            self.camera_window.pivot = self.arcball_controller.pivot();
+        }
+        // Controller may be activated above, which releases its drag state.
+        let actions = self.input_map.actions().clone();
+        let (controller, _camera) = self.controller_mut();
+        controller.set_actions(&actions);
+
+        if let Some(action) = self.animation_window.action.take() {
+            self.process_animation_action(action);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(action) = self.bindings_window.action.take() {
+            self.process_bindings_action(action);
+        }
+        if self.camera.projection() != self.camera_window.projection {
+            self.camera.set_projection(self.camera_window.projection);
+        }
+
+        // Limit time step, in case that rendering is paused for a while.
+        let now = Instant::now();
+        let frame_dt = (now - self.last_frame_time)
+            .as_secs_f32()
+            .min(MAX_FRAME_TIME);
+        self.last_frame_time = now;
+
+        let scrubbed = mem::take(&mut self.animation_window.scrubbed);
+        let animation = &mut self.animation_window.animation;
+        let mut pose = animation.update(frame_dt);
+        if pose.is_none() && scrubbed {
+            pose = animation.path.sample(animation.time);
+        }
+        let (controller, camera) = self.controller_mut();
+        if let Some(pose) = pose {
+            // Camera is driven by animation, and controller follows it.
+            pose.apply(camera);
+            controller.activate(camera);
+        } else {
+            controller.update(camera, frame_dt);
+        }
+        self.camera.update(frame_dt);
+
+        let dt = self.start_time.elapsed().as_secs_f32();
+
+        for (index, (cube, pos)) in self.cubes.iter_mut().zip(CUBE_POSITIONS).enumerate() {
+            let translate_model = Matrix4::from_translation(*pos);
+            let angle: f32 = 0.5 * (index + 1) as f32;
+            let radian = Rad(angle) * dt;
+            let rotate_model = Matrix4::from_angle_x(Rad(0.5) * (1.15 - dt.sin()))
+                * Matrix4::from_angle_y(radian)
+                * Matrix4::from_angle_z(Rad(0.0));
+            cube.uniform.model = translate_model * rotate_model;
+            cube.write_uniform(&self.queue);
+        }
+
+        if mem::take(&mut self.camera_window.fit) {
+            if let Some(bounds) = self.focus_bounds() {
+                self.camera.frame(&bounds);
+                let (controller, camera) = self.controller_mut();
+                controller.activate(camera);
+            }
+        }
+        if let Some(scene) = self.scene_bounds() {
+            self.camera.fit_clip_planes(&scene);
+        }
+
+        if let Some(selected) = self.picking_window.selected() {
+            self.outline.uniform.model =
+                self.cubes[selected].uniform.model * Matrix4::from_scale(OUTLINE_SCALE);
+            self.outline.write_uniform(&self.queue);
+        }
+
+        self.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(self.camera.uniform_ref()),
+        );
+    }
+
+    /// Draw cubes and outline of the selected one into `view`.
+    fn draw_scene(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.2,
+                        g: 0.3,
+                        b: 0.3,
+                        a: 1.0,
+                    }),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: true,
+                }),
+            }),
+        });
+
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_stencil_reference(SELECTED_STENCIL_REF);
+
+        // 1. Draw other cubes as usual.
+        let selected = self.picking_window.selected();
+        let pipeline = if self.camera_window.wireframe {
+            &self.wireframe_pipeline
+        } else {
+            &self.normal_pipeline
+        };
+        render_pass.set_pipeline(pipeline);
+        for (index, cube) in self.cubes.iter().enumerate() {
+            if Some(index) != selected {
+                render_pass.set_bind_group(1, &cube.bind_group, &[]);
+                self.cube_mesh.draw(&mut render_pass);
+            }
+        }
+
+        if let Some(selected) = selected {
+            // 2. Draw the selected cube, marking its pixels in stencil buffer.
+            render_pass.set_pipeline(&self.stencil_write_pipeline);
+            render_pass.set_bind_group(1, &self.cubes[selected].bind_group, &[]);
+            self.cube_mesh.draw(&mut render_pass);
+
+            // 3. Draw a scaled up version of selected cube, only outside of the marked pixels.
+            render_pass.set_pipeline(&self.outline_pipeline);
+            render_pass.set_bind_group(1, &self.outline.bind_group, &[]);
+            self.cube_mesh.draw(&mut render_pass);
+        }
+    }
+
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        self.draw_scene(&mut encoder, &view);
+
+        // Scene is drawn again for screenshot, without egui windows.
+        #[cfg(not(target_arch = "wasm32"))]
+        let screenshot = if mem::take(&mut self.screenshot_requested) {
+            match Screenshot::new(&self.device, &self.config) {
+                Ok(screenshot) => {
+                    self.draw_scene(&mut encoder, &screenshot.view);
+                    screenshot.copy(&mut encoder);
+                    Some(screenshot)
+                }
+                Err(err) => {
+                    log::warn!("Failed to take screenshot: {err:?}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Draw the egui UI frame.
+        {
+            self.egui_platform.begin_frame();
+            self.picking_window.ui(&self.egui_platform.context());
+            self.camera_window.ui(&self.egui_platform.context());
+            self.animation_window.ui(&self.egui_platform.context());
+            self.gamepad_window.ui(&self.egui_platform.context());
+            self.bindings_window.ui(&self.egui_platform.context());
+            let full_output = self.egui_platform.end_frame(Some(&self.window));
+            let paint_jobs = self.egui_platform.context().tessellate(full_output.shapes);
+            // Upload all resources for the GPU.
+            let screen_descriptor = ScreenDescriptor {
+                physical_width: self.config.width,
+                physical_height: self.config.height,
+                scale_factor: self.window.scale_factor() as f32,
+            };
+            let tdelta: egui::TexturesDelta = full_output.textures_delta;
+            self.egui_render_pass
+                .add_textures(&self.device, &self.queue, &tdelta)
+                .expect("add texture ok");
+            self.egui_render_pass.update_buffers(
+                &self.device,
+                &self.queue,
+                &paint_jobs,
+                &screen_descriptor,
+            );
+
+            // Record all render passes.
+            self.egui_render_pass
+                .execute(&mut encoder, &view, &paint_jobs, &screen_descriptor, None)
+                .unwrap();
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(screenshot) = screenshot {
+            let file_name = format!("screenshot-{}.png", self.start_time.elapsed().as_millis());
+            match screenshot.save(&self.device, &file_name) {
+                Ok(()) => log::info!("Screenshot saved to {file_name}"),
+                Err(err) => log::warn!("Failed to save screenshot: {err:?}"),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use winit::event::{DeviceEvent, WindowEvent};
+
+use crate::bindings::ActionState;
+use crate::camera::Camera;
+use crate::gamepad::GamepadAxes;
+use crate::touch::Gesture;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Rotate around target point.
+    #[default]
+    Orbit,
+
+    /// First person camera, flying freely in the scene.
+    Fly,
+
+    /// Rotate freely around pivot point, like a trackball.
+    Arcball,
+}
+
+impl CameraMode {
+    /// Cycle through all modes, used by gamepad.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Orbit => Self::Fly,
+            Self::Fly => Self::Arcball,
+            Self::Arcball => Self::Orbit,
+        }
+    }
+}
+
+/// Moves camera around according to user input.
+pub trait CameraController {
+    /// Called when this controller becomes the active one, so that its internal
+    /// state can be synced with the current camera.
+    fn activate(&mut self, camera: &Camera);
+
+    /// Returns true if event is consumed.
+    ///
+    /// Input events only update internal state of controller,
+    /// camera is moved in `update()`.
+    fn process_event(&mut self, event: &WindowEvent) -> bool;
+
+    /// Raw device events, like relative mouse motion when cursor is grabbed.
+    fn process_device_event(&mut self, _event: &DeviceEvent) -> bool {
+        false
+    }
+
+    /// Held actions mapped from keys and mouse buttons by `InputMap`,
+    /// called whenever they change.
+    fn set_actions(&mut self, _actions: &ActionState) {}
+
+    /// Touch gestures, recognized from window events by `TouchGestures`.
+    fn process_gesture(&mut self, _gesture: &Gesture) -> bool {
+        false
+    }
+
+    /// Latest values of gamepad sticks and triggers, which are used
+    /// together with keyboard in `update()`.
+    fn set_gamepad_axes(&mut self, _axes: &GamepadAxes) {}
+
+    /// Integrate velocity and move camera, `dt` is elapsed time in seconds
+    /// since previous frame.
+    fn update(&mut self, camera: &mut Camera, dt: f32);
+
+    /// Whether cursor shall be grabbed and hidden in window.
+    fn cursor_grabbed(&self) -> bool {
+        false
+    }
+}
+
+/// Scrolling in pixels, as much as one line of mouse wheel.
+pub const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Two fingers on macOS trackpads scroll in pixels, which rotates view like
+/// native apps do. Elsewhere wheels also scroll in pixels, like in browsers,
+/// so pixels zoom as lines do.
+pub const PIXEL_SCROLL_ROTATES: bool = cfg!(target_os = "macos");
+
+/// Factor to move from current value towards target value in `dt` seconds,
+/// with exponential decay `rate`.
+///
+/// Unlike a constant factor, the result is the same no matter how many
+/// frames `dt` is split into.
+#[must_use]
+pub fn smooth_factor(rate: f32, dt: f32) -> f32 {
+    1.0 - (-rate * dt).exp()
+}
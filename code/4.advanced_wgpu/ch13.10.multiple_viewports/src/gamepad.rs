@@ -0,0 +1,402 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Gamepad input, read with gilrs or injected as synthetic events.
+//!
+//! gilrs is only used with the `gilrs` cargo feature, as it requires
+//! libudev on Linux. Synthetic events work without it.
+
+#![allow(dead_code)]
+
+use cgmath::{InnerSpace, Vector2, Zero};
+#[cfg(feature = "gilrs")]
+use gilrs::{Axis, Button, EventType, Gilrs};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+/// Buttons are named by position, as labels differ between vendors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftShoulder,
+    RightShoulder,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Gamepad events independent of backend, so that they can also be
+/// created by tests or by a remote control.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadEvent {
+    /// Sticks are in range [-1.0, 1.0], with Y axis pointing up.
+    /// Triggers are in range [0.0, 1.0].
+    Axis(GamepadAxis, f32),
+    ButtonPressed(GamepadButton),
+    ButtonReleased(GamepadButton),
+    Connected,
+    Disconnected,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadSettings {
+    /// Stick and trigger values below this are treated as zero,
+    /// as worn sticks do not return to center exactly.
+    pub dead_zone: f32,
+
+    /// Multiplier of stick and trigger values.
+    pub sensitivity: f32,
+
+    /// Push stick up to look down, like flight simulators.
+    pub invert_y: bool,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self {
+            dead_zone: 0.15,
+            sensitivity: 1.0,
+            invert_y: false,
+        }
+    }
+}
+
+/// Analog input after dead zone and sensitivity are applied.
+///
+/// Camera controllers add these values to the axes of keyboard keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GamepadAxes {
+    pub left_stick: Vector2<f32>,
+    pub right_stick: Vector2<f32>,
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+}
+
+impl Default for GamepadAxes {
+    fn default() -> Self {
+        Self {
+            left_stick: Vector2::zero(),
+            right_stick: Vector2::zero(),
+            left_trigger: 0.0,
+            right_trigger: 0.0,
+        }
+    }
+}
+
+pub struct Gamepad {
+    /// None if gamepad is not supported on this platform.
+    #[cfg(feature = "gilrs")]
+    gilrs: Option<Gilrs>,
+    synthetic_events: VecDeque<GamepadEvent>,
+
+    pub settings: GamepadSettings,
+    connected: bool,
+    raw_axes: HashMap<GamepadAxis, f32>,
+    pressed: HashSet<GamepadButton>,
+}
+
+// Derivable without gilrs.
+#[cfg_attr(not(feature = "gilrs"), allow(clippy::derivable_impls))]
+impl Default for Gamepad {
+    fn default() -> Self {
+        #[cfg(feature = "gilrs")]
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(err) => {
+                log::warn!("Gamepad is not available: {err:?}");
+                None
+            }
+        };
+        Self {
+            #[cfg(feature = "gilrs")]
+            gilrs,
+            synthetic_events: VecDeque::new(),
+            settings: GamepadSettings::default(),
+            connected: false,
+            raw_axes: HashMap::new(),
+            pressed: HashSet::new(),
+        }
+    }
+}
+
+impl Gamepad {
+    /// Queue an event as if it is sent by a real gamepad.
+    ///
+    /// It is processed in the next `poll()`.
+    pub fn push_event(&mut self, event: GamepadEvent) {
+        self.synthetic_events.push_back(event);
+    }
+
+    #[must_use]
+    pub const fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Process pending events, returns buttons pressed since previous call.
+    pub fn poll(&mut self) -> Vec<GamepadButton> {
+        let mut events = Vec::new();
+        #[cfg(feature = "gilrs")]
+        if let Some(gilrs) = &mut self.gilrs {
+            while let Some(event) = gilrs.next_event() {
+                events.extend(convert_event(event.event));
+            }
+        }
+        events.extend(self.synthetic_events.drain(..));
+
+        let mut pressed = Vec::new();
+        for event in events {
+            match event {
+                GamepadEvent::Axis(axis, value) => {
+                    self.raw_axes.insert(axis, value);
+                }
+                GamepadEvent::ButtonPressed(button) => {
+                    // Ignore repeated events.
+                    if self.pressed.insert(button) {
+                        pressed.push(button);
+                    }
+                }
+                GamepadEvent::ButtonReleased(button) => {
+                    self.pressed.remove(&button);
+                }
+                GamepadEvent::Connected => self.connected = true,
+                GamepadEvent::Disconnected => {
+                    // Stop moving if gamepad is unplugged while stick is pushed.
+                    self.connected = false;
+                    self.raw_axes.clear();
+                    self.pressed.clear();
+                }
+            }
+        }
+        pressed
+    }
+
+    #[must_use]
+    pub fn axes(&self) -> GamepadAxes {
+        let raw = |axis| self.raw_axes.get(&axis).copied().unwrap_or(0.0);
+        let settings = &self.settings;
+        let stick = |x, y, y_sign| {
+            let value = Vector2::new(raw(x), raw(y) * y_sign);
+            apply_dead_zone(value, settings.dead_zone) * settings.sensitivity
+        };
+        // Only look stick is inverted, movement stays the same.
+        let look_y_sign = if settings.invert_y { -1.0 } else { 1.0 };
+        let trigger = |axis| {
+            apply_dead_zone_1d(raw(axis).clamp(0.0, 1.0), settings.dead_zone) * settings.sensitivity
+        };
+
+        GamepadAxes {
+            left_stick: stick(GamepadAxis::LeftStickX, GamepadAxis::LeftStickY, 1.0),
+            right_stick: stick(
+                GamepadAxis::RightStickX,
+                GamepadAxis::RightStickY,
+                look_y_sign,
+            ),
+            left_trigger: trigger(GamepadAxis::LeftTrigger),
+            right_trigger: trigger(GamepadAxis::RightTrigger),
+        }
+    }
+}
+
+/// Radial dead zone, and remap the rest to [0.0, 1.0], so that values
+/// start from zero at the edge of dead zone instead of jumping.
+fn apply_dead_zone(stick: Vector2<f32>, dead_zone: f32) -> Vector2<f32> {
+    let magnitude = stick.magnitude();
+    if magnitude <= dead_zone || magnitude == 0.0 {
+        return Vector2::zero();
+    }
+    let scaled = apply_dead_zone_1d(magnitude.min(1.0), dead_zone);
+    stick * (scaled / magnitude)
+}
+
+fn apply_dead_zone_1d(value: f32, dead_zone: f32) -> f32 {
+    if value.abs() <= dead_zone {
+        0.0
+    } else {
+        value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone).max(f32::EPSILON)
+    }
+}
+
+#[cfg(feature = "gilrs")]
+fn convert_event(event: EventType) -> Option<GamepadEvent> {
+    match event {
+        EventType::AxisChanged(axis, value, _code) => {
+            let axis = match axis {
+                Axis::LeftStickX => GamepadAxis::LeftStickX,
+                Axis::LeftStickY => GamepadAxis::LeftStickY,
+                Axis::RightStickX => GamepadAxis::RightStickX,
+                Axis::RightStickY => GamepadAxis::RightStickY,
+                _ => return None,
+            };
+            Some(GamepadEvent::Axis(axis, value))
+        }
+        // Analog triggers are reported as buttons with value.
+        EventType::ButtonChanged(Button::LeftTrigger2, value, _code) => {
+            Some(GamepadEvent::Axis(GamepadAxis::LeftTrigger, value))
+        }
+        EventType::ButtonChanged(Button::RightTrigger2, value, _code) => {
+            Some(GamepadEvent::Axis(GamepadAxis::RightTrigger, value))
+        }
+        EventType::ButtonPressed(button, _code) => {
+            convert_button(button).map(GamepadEvent::ButtonPressed)
+        }
+        EventType::ButtonReleased(button, _code) => {
+            convert_button(button).map(GamepadEvent::ButtonReleased)
+        }
+        EventType::Connected => Some(GamepadEvent::Connected),
+        EventType::Disconnected => Some(GamepadEvent::Disconnected),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "gilrs")]
+const fn convert_button(button: Button) -> Option<GamepadButton> {
+    let button = match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::North => GamepadButton::North,
+        Button::West => GamepadButton::West,
+        Button::LeftTrigger => GamepadButton::LeftShoulder,
+        Button::RightTrigger => GamepadButton::RightShoulder,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        _ => return None,
+    };
+    Some(button)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    fn gamepad(settings: GamepadSettings) -> Gamepad {
+        let mut gamepad = Gamepad {
+            settings,
+            ..Default::default()
+        };
+        gamepad.push_event(GamepadEvent::Connected);
+        gamepad.poll();
+        gamepad
+    }
+
+    #[test]
+    fn dead_zone_is_remapped() {
+        assert_close(apply_dead_zone_1d(0.1, 0.2), 0.0);
+        assert_close(apply_dead_zone_1d(0.2, 0.2), 0.0);
+        assert_close(apply_dead_zone_1d(0.6, 0.2), 0.5);
+        assert_close(apply_dead_zone_1d(-0.6, 0.2), -0.5);
+        assert_close(apply_dead_zone_1d(1.0, 0.2), 1.0);
+
+        assert_eq!(
+            apply_dead_zone(Vector2::new(0.1, 0.1), 0.2),
+            Vector2::zero()
+        );
+        let stick = apply_dead_zone(Vector2::new(0.0, -0.6), 0.2);
+        assert_close(stick.x, 0.0);
+        assert_close(stick.y, -0.5);
+        // Direction is kept, magnitude is remapped.
+        let stick = apply_dead_zone(Vector2::new(0.6, 0.8), 0.2);
+        assert_close(stick.magnitude(), 1.0);
+        assert_close(stick.x / stick.y, 0.75);
+    }
+
+    #[test]
+    fn axes_are_scaled_by_sensitivity() {
+        let mut gamepad = gamepad(GamepadSettings {
+            dead_zone: 0.2,
+            sensitivity: 2.0,
+            invert_y: false,
+        });
+        gamepad.push_event(GamepadEvent::Axis(GamepadAxis::LeftStickX, 0.6));
+        gamepad.push_event(GamepadEvent::Axis(GamepadAxis::RightTrigger, 1.0));
+        gamepad.push_event(GamepadEvent::Axis(GamepadAxis::LeftTrigger, 0.1));
+        gamepad.poll();
+
+        let axes = gamepad.axes();
+        assert_close(axes.left_stick.x, 1.0);
+        assert_close(axes.left_stick.y, 0.0);
+        assert_close(axes.right_trigger, 2.0);
+        assert_close(axes.left_trigger, 0.0);
+    }
+
+    #[test]
+    fn invert_y_only_affects_look_stick() {
+        let mut gamepad = gamepad(GamepadSettings {
+            dead_zone: 0.0,
+            sensitivity: 1.0,
+            invert_y: true,
+        });
+        gamepad.push_event(GamepadEvent::Axis(GamepadAxis::LeftStickY, 1.0));
+        gamepad.push_event(GamepadEvent::Axis(GamepadAxis::RightStickY, 1.0));
+        gamepad.poll();
+
+        let axes = gamepad.axes();
+        assert_close(axes.left_stick.y, 1.0);
+        assert_close(axes.right_stick.y, -1.0);
+
+        gamepad.settings.invert_y = false;
+        assert_close(gamepad.axes().right_stick.y, 1.0);
+    }
+
+    #[test]
+    fn repeated_presses_are_ignored() {
+        let mut gamepad = gamepad(GamepadSettings::default());
+        gamepad.push_event(GamepadEvent::ButtonPressed(GamepadButton::South));
+        gamepad.push_event(GamepadEvent::ButtonPressed(GamepadButton::South));
+        gamepad.push_event(GamepadEvent::ButtonPressed(GamepadButton::East));
+        assert_eq!(gamepad.poll(), [GamepadButton::South, GamepadButton::East]);
+
+        gamepad.push_event(GamepadEvent::ButtonPressed(GamepadButton::South));
+        assert!(gamepad.poll().is_empty());
+
+        gamepad.push_event(GamepadEvent::ButtonReleased(GamepadButton::South));
+        gamepad.push_event(GamepadEvent::ButtonPressed(GamepadButton::South));
+        assert_eq!(gamepad.poll(), [GamepadButton::South]);
+    }
+
+    #[test]
+    fn disconnect_clears_held_axes() {
+        let mut gamepad = gamepad(GamepadSettings::default());
+        assert!(gamepad.is_connected());
+        gamepad.push_event(GamepadEvent::Axis(GamepadAxis::LeftStickY, 1.0));
+        gamepad.push_event(GamepadEvent::Axis(GamepadAxis::RightTrigger, 1.0));
+        gamepad.push_event(GamepadEvent::ButtonPressed(GamepadButton::North));
+        gamepad.poll();
+        assert_ne!(gamepad.axes(), GamepadAxes::default());
+
+        gamepad.push_event(GamepadEvent::Disconnected);
+        gamepad.poll();
+        assert!(!gamepad.is_connected());
+        assert_eq!(gamepad.axes(), GamepadAxes::default());
+
+        // Held button is released by disconnect, pressing it again counts.
+        gamepad.push_event(GamepadEvent::ButtonPressed(GamepadButton::North));
+        assert_eq!(gamepad.poll(), [GamepadButton::North]);
+    }
+}
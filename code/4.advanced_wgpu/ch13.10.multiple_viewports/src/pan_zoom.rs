@@ -0,0 +1,133 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Deg, InnerSpace, Rad, Vector2, Zero};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{MouseScrollDelta, TouchPhase, WindowEvent};
+
+use crate::bindings::{Action, ActionState};
+use crate::camera::Camera;
+use crate::controller::CameraController;
+
+const MIN_DISTANCE: f32 = 0.2;
+
+/// Camera of 2D views, like top or front view of a quad view.
+///
+/// View direction never changes. Drag with left or middle mouse button
+/// to pan, and scroll to zoom.
+#[derive(Debug)]
+pub struct PanZoomController {
+    /// Distance is scaled by `exp(-scroll_speed)` for each line of mouse wheel.
+    scroll_speed: f32,
+
+    viewport: PhysicalSize<u32>,
+    panning: bool,
+    last_cursor_pos: Option<PhysicalPosition<f64>>,
+
+    /// Cursor offset in pixels accumulated since previous frame.
+    pending_pan: Vector2<f32>,
+    pending_zoom: f32,
+}
+
+impl Default for PanZoomController {
+    fn default() -> Self {
+        Self {
+            scroll_speed: 0.1,
+
+            viewport: PhysicalSize::new(1, 1),
+            panning: false,
+            last_cursor_pos: None,
+
+            pending_pan: Vector2::zero(),
+            pending_zoom: 0.0,
+        }
+    }
+}
+
+impl CameraController for PanZoomController {
+    fn activate(&mut self, _camera: &Camera) {
+        self.panning = false;
+        self.last_cursor_pos = None;
+        self.pending_pan = Vector2::zero();
+        self.pending_zoom = 0.0;
+    }
+
+    fn process_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseWheel {
+                delta,
+                phase: TouchPhase::Moved,
+                ..
+            } => {
+                match delta {
+                    MouseScrollDelta::LineDelta(_horizontal, vertical) => {
+                        self.pending_zoom += *vertical;
+                    }
+                    // Scrolling with two fingers on trackpad pans view.
+                    MouseScrollDelta::PixelDelta(pos) => {
+                        self.pending_pan.x += pos.x as f32;
+                        self.pending_pan.y += pos.y as f32;
+                    }
+                }
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                if !self.panning {
+                    return false;
+                }
+                if let Some(last_pos) = self.last_cursor_pos {
+                    self.pending_pan.x += (position.x - last_pos.x) as f32;
+                    self.pending_pan.y += (position.y - last_pos.y) as f32;
+                }
+                self.last_cursor_pos = Some(*position);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn set_actions(&mut self, actions: &ActionState) {
+        // There is nothing to rotate, so rotate action also pans.
+        let panning = actions.is_active(Action::Pan) || actions.is_active(Action::Rotate);
+        if panning != self.panning {
+            self.panning = panning;
+            self.last_cursor_pos = None;
+        }
+    }
+
+    fn update(&mut self, camera: &mut Camera, _dt: f32) {
+        let offset = camera.eye() - camera.target();
+        let mut distance = offset.magnitude();
+        let mut target = camera.target();
+
+        if !self.pending_pan.is_zero() {
+            // Size of one pixel at the target plane, in world units.
+            let half_fovy: Rad<f32> = (Deg(camera.fovy()) / 2.0).into();
+            let pixel_size =
+                2.0 * distance * half_fovy.0.tan() / self.viewport.height.max(1) as f32;
+            let right = camera.forward().cross(camera.up()).normalize();
+            let up = right.cross(camera.forward());
+            // Scene follows cursor, so camera moves the opposite way.
+            target += (-right * self.pending_pan.x + up * self.pending_pan.y) * pixel_size;
+            self.pending_pan = Vector2::zero();
+        }
+
+        if self.pending_zoom != 0.0 {
+            distance =
+                (distance * (-self.pending_zoom * self.scroll_speed).exp()).max(MIN_DISTANCE);
+            self.pending_zoom = 0.0;
+        }
+
+        let eye = target + offset.normalize() * distance;
+        if eye != camera.eye() || target != camera.target() {
+            camera.look_at(eye, target);
+        }
+    }
+}
+
+impl PanZoomController {
+    pub fn set_viewport(&mut self, size: PhysicalSize<u32>) {
+        self.viewport = size;
+    }
+}
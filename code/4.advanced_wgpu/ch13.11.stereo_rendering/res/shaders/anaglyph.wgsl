@@ -0,0 +1,37 @@
+
+// Vertex Shader
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+};
+
+// Draw a triangle which covers the whole screen, without any vertex buffer.
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+	let x = f32(i32(vertex_index & 1u) * 4 - 1);
+	let y = f32(i32(vertex_index & 2u) * 2 - 1);
+
+	var out: VertexOutput;
+	out.position = vec4<f32>(x, y, 0.0, 1.0);
+	return out;
+}
+
+// Fragment Shader
+@group(0)
+@binding(0)
+var left_texture: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var right_texture: texture_2d<f32>;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+	let coords = vec2<i32>(in.position.xy);
+	let left = textureLoad(left_texture, coords, 0).rgb;
+	let right = textureLoad(right_texture, coords, 0).rgb;
+
+	// Half color anaglyph: red filter sees luminance of left eye, which is
+	// less tiring than pure red of saturated objects.
+	let left_luma = dot(left, vec3<f32>(0.299, 0.587, 0.114));
+	return vec4<f32>(left_luma, right.g, right.b, 1.0);
+}
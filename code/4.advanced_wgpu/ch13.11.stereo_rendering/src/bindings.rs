@@ -0,0 +1,548 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Map keys and mouse buttons to named actions, so that they can be
+//! changed by user instead of being hard-coded in camera controllers.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, io, path::Path};
+use winit::event::{
+    ElementState, KeyboardInput, ModifiersState, MouseButton, VirtualKeyCode, WindowEvent,
+};
+
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    /// Drag to rotate in orbit and arcball mode.
+    Rotate,
+    /// Drag to pan in arcball mode.
+    Pan,
+    /// Grab cursor and look around in fly mode.
+    Look,
+
+    ViewFront,
+    ViewBack,
+    ViewRight,
+    ViewLeft,
+    ViewTop,
+    ViewBottom,
+    ToggleProjection,
+    FrameSelection,
+    NextCameraMode,
+    NextViewportLayout,
+    NextStereoMode,
+    ToggleWireframe,
+    CaptureKeyframe,
+    TogglePlayback,
+    Screenshot,
+}
+
+impl Action {
+    pub const ALL: [Self; 24] = [
+        Self::MoveForward,
+        Self::MoveBackward,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::Rotate,
+        Self::Pan,
+        Self::Look,
+        Self::ViewFront,
+        Self::ViewBack,
+        Self::ViewRight,
+        Self::ViewLeft,
+        Self::ViewTop,
+        Self::ViewBottom,
+        Self::ToggleProjection,
+        Self::FrameSelection,
+        Self::NextCameraMode,
+        Self::NextViewportLayout,
+        Self::NextStereoMode,
+        Self::ToggleWireframe,
+        Self::CaptureKeyframe,
+        Self::TogglePlayback,
+        Self::Screenshot,
+    ];
+
+    /// Held actions stay active while input is held down, like moving.
+    ///
+    /// Other actions are triggered once when input is pressed.
+    #[must_use]
+    pub const fn is_held(self) -> bool {
+        matches!(
+            self,
+            Self::MoveForward
+                | Self::MoveBackward
+                | Self::MoveLeft
+                | Self::MoveRight
+                | Self::MoveUp
+                | Self::MoveDown
+                | Self::Rotate
+                | Self::Pan
+                | Self::Look
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Input {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, "{key:?}"),
+            Self::Mouse(MouseButton::Other(button)) => write!(f, "Mouse {button}"),
+            Self::Mouse(button) => write!(f, "Mouse {button:?}"),
+        }
+    }
+}
+
+/// Modifier keys required by a binding.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub logo: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        ctrl: false,
+        shift: false,
+        alt: false,
+        logo: false,
+    };
+
+    pub const CTRL: Self = Self {
+        ctrl: true,
+        ..Self::NONE
+    };
+
+    /// Returns true if all modifiers of `other` are also held in `self`.
+    #[must_use]
+    pub const fn contains(&self, other: &Self) -> bool {
+        (self.ctrl || !other.ctrl)
+            && (self.shift || !other.shift)
+            && (self.alt || !other.alt)
+            && (self.logo || !other.logo)
+    }
+
+    #[must_use]
+    pub const fn count(&self) -> usize {
+        self.ctrl as usize + self.shift as usize + self.alt as usize + self.logo as usize
+    }
+}
+
+impl From<ModifiersState> for Modifiers {
+    fn from(state: ModifiersState) -> Self {
+        Self {
+            ctrl: state.ctrl(),
+            shift: state.shift(),
+            alt: state.alt(),
+            logo: state.logo(),
+        }
+    }
+}
+
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.shift, "Shift+"),
+            (self.alt, "Alt+"),
+            (self.logo, "Logo+"),
+        ] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Binding {
+    pub action: Action,
+    pub input: Input,
+    #[serde(default)]
+    pub modifiers: Modifiers,
+}
+
+impl Binding {
+    #[must_use]
+    pub const fn new(action: Action, input: Input, modifiers: Modifiers) -> Self {
+        Self {
+            action,
+            input,
+            modifiers,
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.modifiers, self.input)
+    }
+}
+
+/// Keyboard layout of the default bindings.
+///
+/// Virtual key codes follow the layout, so W/A/S/D keys of QWERTY
+/// are Z/Q/S/D on AZERTY keyboards.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    #[default]
+    Qwerty,
+    Azerty,
+}
+
+/// All bindings, saved to and loaded from a JSON config file.
+///
+/// An action may have several bindings, and an input may be bound to
+/// several actions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub bindings: Vec<Binding>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::with_layout(Layout::Qwerty)
+    }
+}
+
+impl KeyBindings {
+    #[must_use]
+    pub fn with_layout(layout: Layout) -> Self {
+        use Action as A;
+        use Input::{Key, Mouse};
+        use VirtualKeyCode as K;
+
+        let (forward, left) = match layout {
+            Layout::Qwerty => (K::W, K::A),
+            Layout::Azerty => (K::Z, K::Q),
+        };
+        let none = Modifiers::NONE;
+        let ctrl = Modifiers::CTRL;
+        let bindings = [
+            (A::MoveForward, Key(forward), none),
+            (A::MoveForward, Key(K::Up), none),
+            (A::MoveBackward, Key(K::S), none),
+            (A::MoveBackward, Key(K::Down), none),
+            (A::MoveLeft, Key(left), none),
+            (A::MoveLeft, Key(K::Left), none),
+            (A::MoveRight, Key(K::D), none),
+            (A::MoveRight, Key(K::Right), none),
+            (A::MoveUp, Key(K::Space), none),
+            (A::MoveDown, Key(K::LControl), none),
+            (A::MoveDown, Key(K::RControl), none),
+            (A::Rotate, Mouse(MouseButton::Left), none),
+            (A::Pan, Mouse(MouseButton::Middle), none),
+            (A::Look, Mouse(MouseButton::Right), none),
+            (A::ViewFront, Key(K::Key1), none),
+            (A::ViewFront, Key(K::Numpad1), none),
+            (A::ViewBack, Key(K::Key1), ctrl),
+            (A::ViewBack, Key(K::Numpad1), ctrl),
+            (A::ViewRight, Key(K::Key3), none),
+            (A::ViewRight, Key(K::Numpad3), none),
+            (A::ViewLeft, Key(K::Key3), ctrl),
+            (A::ViewLeft, Key(K::Numpad3), ctrl),
+            (A::ViewTop, Key(K::Key7), none),
+            (A::ViewTop, Key(K::Numpad7), none),
+            (A::ViewBottom, Key(K::Key7), ctrl),
+            (A::ViewBottom, Key(K::Numpad7), ctrl),
+            (A::ToggleProjection, Key(K::Key5), none),
+            (A::ToggleProjection, Key(K::Numpad5), none),
+            (A::FrameSelection, Key(K::F), none),
+            (A::NextCameraMode, Key(K::C), none),
+            (A::NextViewportLayout, Key(K::V), none),
+            (A::NextStereoMode, Key(K::B), none),
+            (A::ToggleWireframe, Key(K::L), none),
+            (A::CaptureKeyframe, Key(K::K), none),
+            (A::TogglePlayback, Key(K::P), none),
+            (A::Screenshot, Key(K::F12), none),
+        ];
+        Self {
+            bindings: bindings
+                .into_iter()
+                .map(|(action, input, modifiers)| Binding::new(action, input, modifiers))
+                .collect(),
+        }
+    }
+
+    pub fn for_action(&self, action: Action) -> impl Iterator<Item = &Binding> {
+        self.bindings
+            .iter()
+            .filter(move |binding| binding.action == action)
+    }
+
+    /// Add a binding, returns false if it already exists.
+    pub fn bind(&mut self, binding: Binding) -> bool {
+        if self.bindings.contains(&binding) {
+            return false;
+        }
+        self.bindings.push(binding);
+        true
+    }
+
+    pub fn unbind(&mut self, binding: &Binding) {
+        self.bindings.retain(|item| item != binding);
+    }
+
+    /// Other actions bound to the same input and modifiers.
+    pub fn conflicts<'a>(&'a self, binding: &'a Binding) -> impl Iterator<Item = Action> + 'a {
+        self.bindings
+            .iter()
+            .filter(move |item| {
+                item.action != binding.action
+                    && item.input == binding.input
+                    && item.modifiers == binding.modifiers
+            })
+            .map(|item| item.action)
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Load bindings from config file, or use the default ones if
+    /// the file does not exist or is invalid.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[must_use]
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> Self {
+        match Self::load(&path) {
+            Ok(bindings) => bindings,
+            Err(Error::Io(err)) if err.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(err) => {
+                log::warn!(
+                    "Failed to load key bindings from {}: {err:?}",
+                    path.as_ref().display()
+                );
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Held actions which are currently active, passed to camera controllers.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ActionState {
+    active: HashSet<Action>,
+}
+
+impl ActionState {
+    #[must_use]
+    pub fn is_active(&self, action: Action) -> bool {
+        self.active.contains(&action)
+    }
+
+    /// Returns -1.0, 0.0 or 1.0, depends on which action is active.
+    #[must_use]
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        let mut value = 0.0;
+        if self.is_active(negative) {
+            value -= 1.0;
+        }
+        if self.is_active(positive) {
+            value += 1.0;
+        }
+        value
+    }
+}
+
+/// Result of an input event processed by `InputMap`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InputChange {
+    /// Action triggered by pressing an input.
+    pub triggered: Option<Action>,
+    /// Whether any held action is activated or deactivated.
+    pub held_changed: bool,
+}
+
+/// Tracks which keys and mouse buttons are held down, and maps them
+/// to actions.
+#[derive(Debug, Default, Clone)]
+pub struct InputMap {
+    pressed: HashSet<Input>,
+    modifiers: Modifiers,
+    actions: ActionState,
+}
+
+impl InputMap {
+    #[must_use]
+    pub const fn actions(&self) -> &ActionState {
+        &self.actions
+    }
+
+    #[must_use]
+    pub const fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    pub fn process_event(&mut self, event: &WindowEvent, bindings: &KeyBindings) -> InputChange {
+        let mut change = InputChange::default();
+        match event {
+            WindowEvent::ModifiersChanged(state) => self.modifiers = (*state).into(),
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(keycode),
+                        ..
+                    },
+                ..
+            } => change.triggered = self.set_pressed(Input::Key(*keycode), *state, bindings),
+            WindowEvent::MouseInput { state, button, .. } => {
+                change.triggered = self.set_pressed(Input::Mouse(*button), *state, bindings);
+            }
+            // Key released events are lost if window is not focused.
+            WindowEvent::Focused(false) => {
+                self.pressed.clear();
+                self.modifiers = Modifiers::NONE;
+            }
+            _ => return change,
+        }
+
+        let actions = self.active_actions(bindings);
+        change.held_changed = actions != self.actions;
+        self.actions = actions;
+        change
+    }
+
+    /// Returns the action triggered by pressing `input`.
+    ///
+    /// Key repeat does not trigger actions again.
+    fn set_pressed(
+        &mut self,
+        input: Input,
+        state: ElementState,
+        bindings: &KeyBindings,
+    ) -> Option<Action> {
+        match state {
+            ElementState::Pressed if self.pressed.insert(input) => {
+                // Binding with more modifiers wins, so that Ctrl+1 does not
+                // also trigger the action of 1.
+                bindings
+                    .bindings
+                    .iter()
+                    .filter(|binding| {
+                        !binding.action.is_held()
+                            && binding.input == input
+                            && self.modifiers.contains(&binding.modifiers)
+                    })
+                    .max_by_key(|binding| binding.modifiers.count())
+                    .map(|binding| binding.action)
+            }
+            ElementState::Pressed => None,
+            ElementState::Released => {
+                self.pressed.remove(&input);
+                None
+            }
+        }
+    }
+
+    /// Held actions are active while their input is pressed, even if
+    /// more modifiers are held, like moving forward with Shift+W.
+    fn active_actions(&self, bindings: &KeyBindings) -> ActionState {
+        let active = bindings
+            .bindings
+            .iter()
+            .filter(|binding| {
+                binding.action.is_held()
+                    && self.pressed.contains(&binding.input)
+                    && self.modifiers.contains(&binding.modifiers)
+            })
+            .map(|binding| binding.action)
+            .collect();
+        ActionState { active }
+    }
+}
+
+/// Modifier keys are not bound on their own when pressed, as they may
+/// be part of a combination.
+#[must_use]
+pub const fn is_modifier_key(key: VirtualKeyCode) -> bool {
+    matches!(
+        key,
+        VirtualKeyCode::LControl
+            | VirtualKeyCode::RControl
+            | VirtualKeyCode::LShift
+            | VirtualKeyCode::RShift
+            | VirtualKeyCode::LAlt
+            | VirtualKeyCode::RAlt
+            | VirtualKeyCode::LWin
+            | VirtualKeyCode::RWin
+    )
+}
+
+/// Waits for the next input to bind to an action, used by rebinding UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BindingCapture {
+    pub action: Action,
+    /// Binding replaced by the captured one, or None to add a new binding.
+    pub replace: Option<Binding>,
+}
+
+impl BindingCapture {
+    /// Returns the new binding if `event` completes it.
+    ///
+    /// Modifier keys are captured when released without pressing
+    /// another key, so that Ctrl itself can be bound.
+    #[must_use]
+    pub fn process_event(&self, event: &WindowEvent, modifiers: Modifiers) -> Option<Binding> {
+        let (input, modifiers) = match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(keycode),
+                        ..
+                    },
+                ..
+            } => match (state, is_modifier_key(*keycode)) {
+                (ElementState::Pressed, false) => (Input::Key(*keycode), modifiers),
+                (ElementState::Released, true) => (Input::Key(*keycode), Modifiers::NONE),
+                _ => return None,
+            },
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } => (Input::Mouse(*button), modifiers),
+            _ => return None,
+        };
+        Some(Binding::new(self.action, input, modifiers))
+    }
+}
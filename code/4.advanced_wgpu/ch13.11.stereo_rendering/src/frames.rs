@@ -0,0 +1,688 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+#![allow(dead_code)]
+
+use cgmath::{Point3, Vector3};
+use instant::Instant;
+
+use crate::bindings::{Action, BindingCapture, KeyBindings, Layout};
+use crate::camera::{Projection, StereoSettings, ViewPreset};
+use crate::controller::CameraMode;
+use crate::gamepad::{GamepadAxes, GamepadButton, GamepadEvent, GamepadSettings};
+use crate::keyframe::{CameraAnimation, Easing, Interpolation};
+use crate::picking::Hit;
+use crate::stereo::StereoMode;
+use crate::viewport::ViewportLayout;
+
+#[derive(Debug, Clone)]
+pub struct UserWindow {
+    name: String,
+    age: u32,
+}
+
+impl Default for UserWindow {
+    fn default() -> Self {
+        Self {
+            name: "Author".to_owned(),
+            age: 42,
+        }
+    }
+}
+
+impl UserWindow {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub const fn age(&self) -> u32 {
+        self.age
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("User")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let name_label = ui.label("Your name: ");
+                    ui.text_edit_singleline(&mut self.name)
+                        .labelled_by(name_label.id);
+                });
+                ui.add(egui::Slider::new(&mut self.age, 0..=120).text("age"));
+                if ui.button("Click each year").clicked() {
+                    self.age += 1;
+                }
+                ui.label(format!("Hello '{}', age {}", self.name, self.age));
+            });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ColorWindow {
+    color: Vector3<f32>,
+}
+
+impl Default for ColorWindow {
+    fn default() -> Self {
+        Self {
+            color: Vector3::new(0.3, 0.4, 0.5),
+        }
+    }
+}
+
+impl ColorWindow {
+    pub fn color(&self) -> &Vector3<f32> {
+        &self.color
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Color")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.heading("Select vertex color:");
+                ui.color_edit_button_rgb(self.color.as_mut());
+            });
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FpsWindow {
+    fps: u32,
+    frames: u32,
+    timer: Instant,
+}
+
+impl Default for FpsWindow {
+    fn default() -> Self {
+        Self {
+            fps: 0,
+            frames: 0,
+            timer: Instant::now(),
+        }
+    }
+}
+
+impl FpsWindow {
+    pub fn update(&mut self) {
+        let dt = self.timer.elapsed().as_secs_f64();
+        let fps = (f64::from(self.frames) / dt).round() as u32;
+        //log::info!("fps: {fps}");
+        if dt > 1.0 {
+            self.frames = 0;
+            self.timer = Instant::now();
+            self.fps = fps;
+        }
+        self.frames += 1;
+    }
+
+    #[must_use]
+    pub const fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("FPS")
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                ui.heading(format!("FPS: {}", self.fps));
+            });
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PickingWindow {
+    pub hit: Option<Hit>,
+}
+
+impl PickingWindow {
+    #[must_use]
+    pub fn selected(&self) -> Option<usize> {
+        self.hit.map(|hit| hit.object)
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Picking")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                if let Some(hit) = &self.hit {
+                    ui.heading(format!("Selected cube: {}", hit.object));
+                    ui.label(format!("Triangle: {}", hit.triangle));
+                    ui.label(format!("Distance: {:.3}", hit.distance));
+                    ui.label(format!(
+                        "Barycentric: ({:.3}, {:.3}, {:.3})",
+                        hit.barycentric.x, hit.barycentric.y, hit.barycentric.z
+                    ));
+                    if ui.button("Clear selection").clicked() {
+                        self.hit = None;
+                    }
+                } else {
+                    ui.heading("Click on a cube to select it");
+                }
+            });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CameraWindow {
+    pub mode: CameraMode,
+    pub projection: Projection,
+
+    /// Preset view clicked by user, taken by `State::update()`.
+    pub preset: Option<ViewPreset>,
+
+    /// Frame the selected object, or the whole scene if nothing is selected.
+    /// Taken by `State::update()`.
+    pub fit: bool,
+
+    /// Draw cubes as wireframe, except the selected one.
+    pub wireframe: bool,
+
+    pub layout: ViewportLayout,
+
+    pub stereo_mode: StereoMode,
+    pub stereo: StereoSettings,
+    /// Move convergence plane to camera target, taken by `State::update()`.
+    pub converge_at_target: bool,
+
+    /// Pivot of arcball camera.
+    pub pivot: Point3<f32>,
+    /// Pivot is changed by user, taken by `State::update()`.
+    pub pivot_edited: bool,
+}
+
+impl Default for CameraWindow {
+    fn default() -> Self {
+        Self {
+            mode: CameraMode::default(),
+            projection: Projection::default(),
+            preset: None,
+            fit: false,
+            wireframe: false,
+            layout: ViewportLayout::default(),
+            stereo_mode: StereoMode::default(),
+            stereo: StereoSettings::default(),
+            converge_at_target: false,
+            pivot: Point3::new(0.0, 0.0, 0.0),
+            pivot_edited: false,
+        }
+    }
+}
+
+impl CameraWindow {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Camera")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.radio_value(&mut self.mode, CameraMode::Orbit, "Orbit");
+                ui.radio_value(&mut self.mode, CameraMode::Fly, "Fly");
+                ui.radio_value(&mut self.mode, CameraMode::Arcball, "Arcball");
+                match self.mode {
+                    CameraMode::Orbit => {
+                        ui.label("Drag with left mouse button or press A/D/W/S to orbit");
+                    }
+                    CameraMode::Fly => {
+                        ui.label("Hold right mouse button to look around");
+                        ui.label("A/D/W/S to move, Space/Ctrl to fly up/down");
+                    }
+                    CameraMode::Arcball => {
+                        ui.label("Drag with left mouse button to rotate");
+                        ui.label("Drag with middle mouse button to pan");
+                        ui.label("Double click on an object to rotate around it");
+                        ui.horizontal(|ui| {
+                            ui.label("Pivot:");
+                            for value in [&mut self.pivot.x, &mut self.pivot.y, &mut self.pivot.z] {
+                                if ui.add(egui::DragValue::new(value).speed(0.05)).changed() {
+                                    self.pivot_edited = true;
+                                }
+                            }
+                        });
+                        if ui.button("Reset pivot").clicked() {
+                            self.pivot = Point3::new(0.0, 0.0, 0.0);
+                            self.pivot_edited = true;
+                        }
+                    }
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.projection, Projection::Perspective, "Perspective");
+                    ui.radio_value(
+                        &mut self.projection,
+                        Projection::Orthographic,
+                        "Orthographic",
+                    );
+                });
+                ui.label("Press 5 to switch projection");
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    for (preset, name) in [
+                        (ViewPreset::Front, "Front"),
+                        (ViewPreset::Right, "Right"),
+                        (ViewPreset::Top, "Top"),
+                    ] {
+                        if ui.button(name).clicked() {
+                            self.preset = Some(preset);
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    for (preset, name) in [
+                        (ViewPreset::Back, "Back"),
+                        (ViewPreset::Left, "Left"),
+                        (ViewPreset::Bottom, "Bottom"),
+                    ] {
+                        if ui.button(name).clicked() {
+                            self.preset = Some(preset);
+                        }
+                    }
+                });
+                ui.label("Press 1/3/7 for front/right/top view, with Ctrl for the opposite");
+
+                ui.separator();
+                if ui.button("Fit").clicked() {
+                    self.fit = true;
+                }
+                ui.label("Press F to fit the selected cube, or all cubes");
+                ui.label("On touch screens, double tap to reset view");
+
+                ui.separator();
+                ui.checkbox(&mut self.wireframe, "Wireframe");
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Viewports:");
+                    ui.radio_value(&mut self.layout, ViewportLayout::Single, "Single");
+                    ui.radio_value(&mut self.layout, ViewportLayout::SideBySide, "Side by side");
+                    ui.radio_value(&mut self.layout, ViewportLayout::Quad, "Quad");
+                });
+                ui.label("Input goes to the viewport under cursor");
+                ui.label("Drag or scroll in orthographic views to pan or zoom");
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Stereo:");
+                    ui.radio_value(&mut self.stereo_mode, StereoMode::Off, "Off");
+                    ui.radio_value(
+                        &mut self.stereo_mode,
+                        StereoMode::SideBySide,
+                        "Side by side",
+                    );
+                    ui.radio_value(&mut self.stereo_mode, StereoMode::Anaglyph, "Red/cyan");
+                });
+                ui.label("Press B to switch stereo mode");
+                ui.add_enabled_ui(self.stereo_mode != StereoMode::Off, |ui| {
+                    ui.add(egui::Slider::new(&mut self.stereo.ipd, 0.0..=1.0).text("Eye distance"));
+                    ui.add(
+                        egui::Slider::new(&mut self.stereo.convergence, 0.5..=100.0)
+                            .logarithmic(true)
+                            .text("Convergence"),
+                    );
+                    if ui.button("Converge at target").clicked() {
+                        self.converge_at_target = true;
+                    }
+                });
+            });
+    }
+}
+
+/// Requests from animation window which are handled by `State::update()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationAction {
+    /// Append current camera pose as a new keyframe.
+    Capture,
+    #[cfg(not(target_arch = "wasm32"))]
+    Save,
+    #[cfg(not(target_arch = "wasm32"))]
+    Load,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationWindow {
+    pub animation: CameraAnimation,
+    pub action: Option<AnimationAction>,
+    /// Playback time is dragged by user, taken by `State::update()`.
+    pub scrubbed: bool,
+    pub file_name: String,
+    /// Result of the last save or load action.
+    pub status: String,
+}
+
+impl Default for AnimationWindow {
+    fn default() -> Self {
+        Self {
+            animation: CameraAnimation::default(),
+            action: None,
+            scrubbed: false,
+            file_name: "camera_path.json".to_owned(),
+            status: String::new(),
+        }
+    }
+}
+
+impl AnimationWindow {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Animation")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                let animation = &mut self.animation;
+                ui.horizontal(|ui| {
+                    if ui.button("Capture keyframe").clicked() {
+                        self.action = Some(AnimationAction::Capture);
+                    }
+                    if ui.button("Clear").clicked() {
+                        animation.stop();
+                        animation.path.keyframes.clear();
+                    }
+                });
+                ui.label("Press K to capture, P to play or pause");
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let text = if animation.is_playing() {
+                        "Pause"
+                    } else {
+                        "Play"
+                    };
+                    if ui.button(text).clicked() {
+                        animation.toggle();
+                    }
+                    if ui.button("Stop").clicked() {
+                        animation.stop();
+                        self.scrubbed = true;
+                    }
+                    ui.checkbox(&mut animation.looping, "Loop");
+                });
+                let duration = animation.path.duration;
+                let timeline = egui::Slider::new(&mut animation.time, 0.0..=duration)
+                    .text("Time")
+                    .suffix(" s");
+                if ui.add(timeline).changed() {
+                    animation.pause();
+                    self.scrubbed = true;
+                }
+                ui.add(
+                    egui::Slider::new(&mut animation.path.duration, 1.0..=60.0)
+                        .text("Duration")
+                        .suffix(" s"),
+                );
+
+                egui::ComboBox::from_label("Interpolation")
+                    .selected_text(format!("{:?}", animation.path.interpolation))
+                    .show_ui(ui, |ui| {
+                        for interpolation in [Interpolation::CatmullRom, Interpolation::Bezier] {
+                            ui.selectable_value(
+                                &mut animation.path.interpolation,
+                                interpolation,
+                                format!("{interpolation:?}"),
+                            );
+                        }
+                    });
+                egui::ComboBox::from_label("Easing")
+                    .selected_text(format!("{:?}", animation.path.easing))
+                    .show_ui(ui, |ui| {
+                        for easing in [
+                            Easing::Linear,
+                            Easing::EaseIn,
+                            Easing::EaseOut,
+                            Easing::EaseInOut,
+                        ] {
+                            ui.selectable_value(
+                                &mut animation.path.easing,
+                                easing,
+                                format!("{easing:?}"),
+                            );
+                        }
+                    });
+
+                ui.collapsing(
+                    format!("Keyframes ({})", animation.path.keyframes.len()),
+                    |ui| {
+                        let mut removed = None;
+                        for (index, frame) in animation.path.keyframes.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!(
+                                    "{index}: eye ({:.2}, {:.2}, {:.2}), fov {:.1}",
+                                    frame.eye.x, frame.eye.y, frame.eye.z, frame.fovy
+                                ));
+                                if ui.small_button("Delete").clicked() {
+                                    removed = Some(index);
+                                }
+                            });
+                        }
+                        if let Some(index) = removed {
+                            animation.path.keyframes.remove(index);
+                        }
+                    },
+                );
+
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("File:");
+                        ui.text_edit_singleline(&mut self.file_name);
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Save").clicked() {
+                            self.action = Some(AnimationAction::Save);
+                        }
+                        if ui.button("Load").clicked() {
+                            self.action = Some(AnimationAction::Load);
+                        }
+                    });
+                    if !self.status.is_empty() {
+                        ui.label(&self.status);
+                    }
+                }
+            });
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GamepadWindow {
+    pub settings: GamepadSettings,
+
+    /// Gamepad state shown in window, updated by `State::update()`.
+    pub connected: bool,
+    pub axes: GamepadAxes,
+
+    /// Virtual buttons clicked by user, taken by `State::update()`.
+    pub synthetic_events: Vec<GamepadEvent>,
+}
+
+impl GamepadWindow {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Gamepad")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                if self.connected {
+                    ui.heading("Gamepad connected");
+                } else {
+                    ui.heading("No gamepad");
+                }
+                ui.label(format!(
+                    "Left stick: ({:.2}, {:.2}), right stick: ({:.2}, {:.2})",
+                    self.axes.left_stick.x,
+                    self.axes.left_stick.y,
+                    self.axes.right_stick.x,
+                    self.axes.right_stick.y
+                ));
+                ui.label(format!(
+                    "Triggers: {:.2}, {:.2}",
+                    self.axes.left_trigger, self.axes.right_trigger
+                ));
+
+                ui.separator();
+                ui.add(
+                    egui::Slider::new(&mut self.settings.dead_zone, 0.0..=0.5).text("Dead zone"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut self.settings.sensitivity, 0.1..=3.0)
+                        .text("Sensitivity"),
+                );
+                ui.checkbox(&mut self.settings.invert_y, "Invert Y axis");
+
+                ui.separator();
+                ui.collapsing("Buttons", |ui| {
+                    ui.label("Click to send a synthetic button press");
+                    for (button, help) in [
+                        (GamepadButton::South, "South: toggle wireframe"),
+                        (GamepadButton::East, "East: next camera mode"),
+                        (GamepadButton::North, "North: fit view"),
+                        (GamepadButton::West, "West: switch projection"),
+                        (GamepadButton::DPadUp, "D-pad up: top view"),
+                        (GamepadButton::DPadDown, "D-pad down: front view"),
+                        (GamepadButton::DPadLeft, "D-pad left: left view"),
+                        (GamepadButton::DPadRight, "D-pad right: right view"),
+                        (GamepadButton::Start, "Start: play or pause animation"),
+                        (GamepadButton::Select, "Select: capture keyframe"),
+                        (
+                            GamepadButton::LeftShoulder,
+                            "Left shoulder: next stereo mode",
+                        ),
+                        (
+                            GamepadButton::RightShoulder,
+                            "Right shoulder: next viewport layout",
+                        ),
+                    ] {
+                        if ui.button(help).clicked() {
+                            self.synthetic_events
+                                .push(GamepadEvent::ButtonPressed(button));
+                            self.synthetic_events
+                                .push(GamepadEvent::ButtonReleased(button));
+                        }
+                    }
+                });
+            });
+    }
+}
+
+/// Requests from key bindings window which are handled by `State::update()`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingsAction {
+    Save,
+    Load,
+}
+
+#[derive(Debug, Clone)]
+pub struct BindingsWindow {
+    pub bindings: KeyBindings,
+    /// Waiting for the next input to bind, taken by `State::input()`.
+    pub capture: Option<BindingCapture>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub action: Option<BindingsAction>,
+    pub file_name: String,
+    /// Result of the last rebinding, save or load action.
+    pub status: String,
+}
+
+impl Default for BindingsWindow {
+    fn default() -> Self {
+        Self {
+            bindings: KeyBindings::default(),
+            capture: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            action: None,
+            file_name: "key_bindings.json".to_owned(),
+            status: String::new(),
+        }
+    }
+}
+
+impl BindingsWindow {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Key Bindings")
+            .default_width(360.0)
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.label("Click a binding to change it, then press a key or mouse button.");
+                ui.label("Press Escape to cancel.");
+                ui.horizontal(|ui| {
+                    ui.label("Defaults:");
+                    if ui.button("QWERTY").clicked() {
+                        self.bindings = KeyBindings::with_layout(Layout::Qwerty);
+                        self.capture = None;
+                    }
+                    if ui.button("AZERTY").clicked() {
+                        self.bindings = KeyBindings::with_layout(Layout::Azerty);
+                        self.capture = None;
+                    }
+                });
+
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(320.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("bindings_grid")
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for action in Action::ALL {
+                                    ui.label(format!("{action:?}"));
+                                    ui.horizontal_wrapped(|ui| self.bindings_ui(ui, action));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("File:");
+                        ui.text_edit_singleline(&mut self.file_name);
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Save").clicked() {
+                            self.action = Some(BindingsAction::Save);
+                        }
+                        if ui.button("Load").clicked() {
+                            self.action = Some(BindingsAction::Load);
+                        }
+                    });
+                }
+                if !self.status.is_empty() {
+                    ui.label(&self.status);
+                }
+            });
+    }
+
+    /// Buttons of bindings of `action`, and a button to add new one.
+    fn bindings_ui(&mut self, ui: &mut egui::Ui, action: Action) {
+        let mut removed = None;
+        let bindings: Vec<_> = self.bindings.for_action(action).copied().collect();
+        for binding in bindings {
+            let capture = BindingCapture {
+                action,
+                replace: Some(binding),
+            };
+            if self.capture == Some(capture) {
+                ui.label("Press a key...");
+                continue;
+            }
+            if ui.button(binding.to_string()).clicked() {
+                self.capture = Some(capture);
+            }
+            if ui.small_button("x").on_hover_text("Remove").clicked() {
+                removed = Some(binding);
+            }
+        }
+        if let Some(binding) = removed {
+            self.bindings.unbind(&binding);
+        }
+
+        let capture = BindingCapture {
+            action,
+            replace: None,
+        };
+        if self.capture == Some(capture) {
+            ui.label("Press a key...");
+        } else if ui.small_button("+").on_hover_text("Add binding").clicked() {
+            self.capture = Some(capture);
+        }
+    }
+}
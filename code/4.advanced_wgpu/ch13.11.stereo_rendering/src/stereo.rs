@@ -0,0 +1,271 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Stereo rendering, for 3D monitors and red/cyan glasses.
+//!
+//! The scene is drawn once for each eye, with cameras returned by
+//! `Camera::eye_uniform()`. Then the two images are either placed side
+//! by side, or mixed into one anaglyph image by a fullscreen pass.
+
+use wgpu::util::DeviceExt;
+use winit::dpi::PhysicalSize;
+
+use crate::camera::{Camera, Eye};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StereoMode {
+    #[default]
+    Off,
+
+    /// Left eye on the left half, right eye on the right half.
+    ///
+    /// Each half is squeezed horizontally, as 3D monitors and TVs stretch
+    /// them back to full width.
+    SideBySide,
+
+    /// Left eye in red channel and right eye in green and blue channels,
+    /// viewed with red/cyan glasses.
+    Anaglyph,
+}
+
+impl StereoMode {
+    /// Cycle through all modes, used by key bindings.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Off => Self::SideBySide,
+            Self::SideBySide => Self::Anaglyph,
+            Self::Anaglyph => Self::Off,
+        }
+    }
+}
+
+/// Uniform buffers of left and right eye cameras.
+pub struct StereoCameras {
+    buffers: [wgpu::Buffer; 2],
+    bind_groups: [wgpu::BindGroup; 2],
+}
+
+impl StereoCameras {
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        camera: &Camera,
+    ) -> Self {
+        let create = |eye: Eye| {
+            let uniform = camera.eye_uniform(eye);
+            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("{eye:?} Eye Camera Buffer")),
+                contents: bytemuck::cast_slice(uniform.as_ref()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+                label: Some(&format!("{eye:?} Eye Camera Bind Group")),
+            });
+            (buffer, bind_group)
+        };
+        let (left_buffer, left_bind_group) = create(Eye::Left);
+        let (right_buffer, right_bind_group) = create(Eye::Right);
+
+        Self {
+            buffers: [left_buffer, right_buffer],
+            bind_groups: [left_bind_group, right_bind_group],
+        }
+    }
+
+    #[must_use]
+    pub const fn bind_group(&self, eye: Eye) -> &wgpu::BindGroup {
+        &self.bind_groups[eye.index()]
+    }
+
+    /// Follow the mono camera, call it after camera is updated.
+    pub fn write_uniforms(&self, queue: &wgpu::Queue, camera: &Camera) {
+        for eye in Eye::BOTH {
+            let uniform = camera.eye_uniform(eye);
+            queue.write_buffer(
+                &self.buffers[eye.index()],
+                0,
+                bytemuck::cast_slice(uniform.as_ref()),
+            );
+        }
+    }
+}
+
+/// Offscreen targets of both eyes, and the pass which mixes them.
+#[derive(Debug)]
+pub struct Anaglyph {
+    pipeline: wgpu::RenderPipeline,
+    format: wgpu::TextureFormat,
+
+    eye_views: [wgpu::TextureView; 2],
+    bind_group: wgpu::BindGroup,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl Anaglyph {
+    /// Eye targets have the same format as surface, so that the scene
+    /// pipelines can draw into them.
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        size: PhysicalSize<u32>,
+    ) -> Self {
+        let bind_group_layout = Self::create_bind_group_layout(device);
+        let pipeline = Self::create_pipeline(device, config, &bind_group_layout);
+        let (eye_views, bind_group) =
+            Self::create_targets(device, &bind_group_layout, config.format, size);
+
+        Self {
+            pipeline,
+            format: config.format,
+
+            eye_views,
+            bind_group,
+            bind_group_layout,
+        }
+    }
+
+    /// Offscreen targets have to follow the size of surface.
+    pub fn resize(&mut self, device: &wgpu::Device, size: PhysicalSize<u32>) {
+        let (eye_views, bind_group) =
+            Self::create_targets(device, &self.bind_group_layout, self.format, size);
+        self.eye_views = eye_views;
+        self.bind_group = bind_group;
+    }
+
+    /// Target where the scene of `eye` is drawn.
+    #[must_use]
+    pub const fn eye_view(&self, eye: Eye) -> &wgpu::TextureView {
+        &self.eye_views[eye.index()]
+    }
+
+    fn create_targets(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+    ) -> ([wgpu::TextureView; 2], wgpu::BindGroup) {
+        let create_view = |label: &str| {
+            let texture = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            texture.create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        let left_view = create_view("Left Eye Texture");
+        let right_view = create_view("Right Eye Texture");
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&left_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&right_view),
+                },
+            ],
+            label: Some("Anaglyph Bind Group"),
+        });
+
+        ([left_view, right_view], bind_group)
+    }
+
+    fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        let texture_entry = |binding: u32| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            },
+            count: None,
+        };
+
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[texture_entry(0), texture_entry(1)],
+            label: Some("Anaglyph Bind Group Layout"),
+        })
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Anaglyph Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../res/shaders/anaglyph.wgsl").into()),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Anaglyph Pipeline Layout"),
+                bind_group_layouts: &[bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Anaglyph Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
+
+    /// Mix images of both eyes into `view`, call it after both eyes are drawn.
+    pub fn compose(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Anaglyph Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Split window into several viewports, each with its own camera.
+
+use wgpu::util::DeviceExt;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::WindowEvent;
+
+use crate::bounds::BoundingSphere;
+use crate::camera::{Camera, Projection, ViewPreset};
+use crate::controller::CameraController;
+use crate::pan_zoom::PanZoomController;
+
+/// Space between viewports, in pixels.
+const VIEWPORT_GAP: u32 = 2;
+
+/// Area of window covered by a viewport, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewportRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ViewportRect {
+    #[must_use]
+    pub const fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.width, self.height)
+    }
+
+    #[must_use]
+    pub fn aspect(&self) -> f32 {
+        self.width.max(1) as f32 / self.height.max(1) as f32
+    }
+
+    #[must_use]
+    pub fn contains(&self, pos: PhysicalPosition<f64>) -> bool {
+        let (x, y) = (f64::from(self.x), f64::from(self.y));
+        pos.x >= x
+            && pos.y >= y
+            && pos.x < x + f64::from(self.width)
+            && pos.y < y + f64::from(self.height)
+    }
+
+    /// Convert position in window to position relative to this viewport.
+    #[must_use]
+    pub fn to_local(self, pos: PhysicalPosition<f64>) -> PhysicalPosition<f64> {
+        PhysicalPosition::new(pos.x - f64::from(self.x), pos.y - f64::from(self.y))
+    }
+
+    /// Returns a copy of cursor moved `event` with position relative to
+    /// this viewport, or None for other events.
+    #[must_use]
+    pub fn to_local_event(self, event: &WindowEvent) -> Option<WindowEvent<'static>> {
+        match event {
+            #[allow(deprecated)]
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                modifiers,
+            } => Some(WindowEvent::CursorMoved {
+                device_id: *device_id,
+                position: self.to_local(*position),
+                modifiers: *modifiers,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Left and right halves, used by side by side stereo.
+    #[must_use]
+    pub const fn split_horizontal(self) -> [Self; 2] {
+        let left_width = self.width / 2;
+        [
+            Self {
+                width: left_width,
+                ..self
+            },
+            Self {
+                x: self.x + left_width,
+                width: self.width - left_width,
+                ..self
+            },
+        ]
+    }
+
+    /// Set viewport and scissor rect of render pass, so that nothing is
+    /// drawn outside of this viewport.
+    pub fn apply(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_viewport(
+            self.x as f32,
+            self.y as f32,
+            self.width as f32,
+            self.height as f32,
+            0.0,
+            1.0,
+        );
+        render_pass.set_scissor_rect(self.x, self.y, self.width, self.height);
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ViewportLayout {
+    /// Only the main camera.
+    #[default]
+    Single,
+
+    /// Front view on the left, and the main camera on the right.
+    SideBySide,
+
+    /// Top, front and right views, and the main camera at bottom right,
+    /// like CAD programs.
+    Quad,
+}
+
+impl ViewportLayout {
+    /// Cycle through all layouts, used by key bindings.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Single => Self::SideBySide,
+            Self::SideBySide => Self::Quad,
+            Self::Quad => Self::Single,
+        }
+    }
+
+    /// Orthographic views shown besides the main camera.
+    #[must_use]
+    pub const fn presets(self) -> &'static [ViewPreset] {
+        match self {
+            Self::Single => &[],
+            Self::SideBySide => &[ViewPreset::Front],
+            Self::Quad => &[ViewPreset::Top, ViewPreset::Front, ViewPreset::Right],
+        }
+    }
+
+    /// Rects of orthographic views, in the same order as `presets()`,
+    /// followed by the rect of main camera.
+    #[must_use]
+    pub fn rects(self, size: PhysicalSize<u32>) -> Vec<ViewportRect> {
+        let (columns, rows) = match self {
+            Self::Single => (1, 1),
+            Self::SideBySide => (2, 1),
+            Self::Quad => (2, 2),
+        };
+        let cell_width = (size.width.saturating_sub(VIEWPORT_GAP * (columns - 1)) / columns).max(1);
+        let cell_height = (size.height.saturating_sub(VIEWPORT_GAP * (rows - 1)) / rows).max(1);
+
+        let mut rects = Vec::with_capacity((columns * rows) as usize);
+        for row in 0..rows {
+            for column in 0..columns {
+                rects.push(ViewportRect {
+                    x: column * (cell_width + VIEWPORT_GAP),
+                    y: row * (cell_height + VIEWPORT_GAP),
+                    width: cell_width,
+                    height: cell_height,
+                });
+            }
+        }
+        rects
+    }
+}
+
+/// Axis aligned orthographic view, which can only be panned and zoomed.
+pub struct OrthoView {
+    pub preset: ViewPreset,
+    pub camera: Camera,
+    pub controller: PanZoomController,
+    pub rect: ViewportRect,
+    buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl OrthoView {
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        preset: ViewPreset,
+        rect: ViewportRect,
+    ) -> Self {
+        let mut camera = Camera::new((0.0, 0.0, 3.0).into(), rect.aspect());
+        camera.snap_to(preset);
+        camera.set_projection(Projection::Orthographic);
+        camera.finish_projection_blend();
+
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ortho View Camera Buffer"),
+            contents: bytemuck::cast_slice(camera.uniform_ref()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("ortho_view_camera_bind_group"),
+        });
+
+        let mut controller = PanZoomController::default();
+        controller.set_viewport(rect.size());
+        Self {
+            preset,
+            camera,
+            controller,
+            rect,
+            buffer,
+            bind_group,
+        }
+    }
+
+    pub fn set_rect(&mut self, rect: ViewportRect) {
+        self.rect = rect;
+        self.camera.set_aspect(rect.aspect());
+        self.controller.set_viewport(rect.size());
+    }
+
+    /// Frame the whole scene, keeping view direction.
+    pub fn frame(&mut self, scene: &BoundingSphere) {
+        self.camera.frame(scene);
+        self.controller.activate(&self.camera);
+    }
+
+    pub fn update(&mut self, scene: Option<&BoundingSphere>, dt: f32) {
+        self.controller.update(&mut self.camera, dt);
+        if let Some(scene) = scene {
+            self.camera.fit_clip_planes(scene);
+        }
+    }
+
+    pub fn write_uniform(&self, queue: &wgpu::Queue) {
+        queue.write_buffer(
+            &self.buffer,
+            0,
+            bytemuck::cast_slice(self.camera.uniform_ref()),
+        );
+    }
+}
+
+/// Paint name of viewport at its top left corner, behind egui windows.
+///
+/// The viewport under cursor, which receives input, is highlighted.
+pub fn paint_label(
+    ctx: &egui::Context,
+    rect: ViewportRect,
+    name: &str,
+    hovered: bool,
+    scale_factor: f32,
+) {
+    // egui is laid out in logical pixels.
+    let min = egui::pos2(rect.x as f32 / scale_factor, rect.y as f32 / scale_factor);
+    let max = egui::pos2(
+        (rect.x + rect.width) as f32 / scale_factor,
+        (rect.y + rect.height) as f32 / scale_factor,
+    );
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    let color = if hovered {
+        egui::Color32::from_rgb(255, 200, 80)
+    } else {
+        egui::Color32::from_gray(200)
+    };
+    painter.text(
+        min + egui::vec2(8.0, 6.0),
+        egui::Align2::LEFT_TOP,
+        name,
+        egui::FontId::proportional(14.0),
+        color,
+    );
+    if hovered {
+        painter.rect_stroke(
+            egui::Rect::from_min_max(min, max).shrink(0.5),
+            0.0,
+            egui::Stroke::new(1.0, color),
+        );
+    }
+}
@@ -0,0 +1,101 @@
+
+// Vertex Shader
+struct VertexInput {
+	@location(0) position: vec3<f32>,
+	@location(1) normal: vec3<f32>,
+	@location(2) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) frag_pos: vec3<f32>,
+	@location(1) normal: vec3<f32>,
+};
+
+struct CameraUniform {
+	view_proj: mat4x4<f32>,
+	view_pos: vec3<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> camera_uniform: CameraUniform;
+
+struct PointLight {
+	position: vec3<f32>,
+	color: vec3<f32>,
+	constant: f32,
+	linear: f32,
+	quadratic: f32,
+};
+
+const MAX_LIGHTS: u32 = 4u;
+
+struct LightsUniform {
+	lights: array<PointLight, MAX_LIGHTS>,
+	count: u32,
+};
+
+@group(1)
+@binding(0)
+var<uniform> lights_uniform: LightsUniform;
+
+struct ModelUniform {
+	model: mat4x4<f32>,
+	normal: mat4x4<f32>,
+	color: vec3<f32>,
+	emissive: f32,
+};
+
+@group(2)
+@binding(0)
+var<uniform> model_uniform: ModelUniform;
+
+@vertex
+fn vs_main(
+	in: VertexInput,
+) -> VertexOutput {
+	let world_pos = model_uniform.model * vec4<f32>(in.position, 1.0);
+
+	var out: VertexOutput;
+	out.position = camera_uniform.view_proj * world_pos;
+	out.frag_pos = world_pos.xyz;
+	out.normal = (model_uniform.normal * vec4<f32>(in.normal, 0.0)).xyz;
+	return out;
+}
+
+// Fragment Shader
+const AMBIENT: f32 = 0.05;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+	let color = model_uniform.color;
+	if model_uniform.emissive > 0.5 {
+		return vec4(color, 1.0);
+	}
+
+	let norm = normalize(in.normal);
+	let view_dir = normalize(camera_uniform.view_pos - in.frag_pos);
+	var result = AMBIENT * color;
+	for (var i = 0u; i < min(lights_uniform.count, MAX_LIGHTS); i++) {
+		let light = lights_uniform.lights[i];
+
+		// diffuse
+		let light_dir = normalize(light.position - in.frag_pos);
+		let diff = max(dot(norm, light_dir), 0.0);
+		let diffuse = light.color * diff * color;
+
+		// specular
+		let reflect_dir = reflect(-light_dir, norm);
+		let spec = pow(max(dot(view_dir, reflect_dir), 0.0), 32.0);
+		let specular = light.color * spec * 0.3;
+
+		// attenuation
+		let distance = length(light.position - in.frag_pos);
+		let attenuation = 1.0 / (light.constant + light.linear * distance +
+			light.quadratic * distance * distance);
+
+		result += (diffuse + specular) * attenuation;
+	}
+	return vec4(result, 1.0);
+}
@@ -0,0 +1,197 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Sun, planets and moons built with a scene graph.
+//!
+//! Each body hangs under an orbit node which only rotates around its
+//! parent. Moons are children of the planet's position node, so that they
+//! follow the planet, but not its spin or size.
+
+use cgmath::{Deg, Rad, Vector3};
+
+use crate::scene_graph::{
+    CameraAttachment, LightAttachment, MeshAttachment, Node, NodeId, SceneGraph, Shape,
+};
+use crate::transforms::Transform;
+
+/// Node rotated around its Y axis at constant speed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Spin {
+    node: NodeId,
+    /// Radians per second.
+    speed: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Body {
+    name: &'static str,
+    shape: Shape,
+    /// Distance to parent.
+    distance: f32,
+    radius: f32,
+    color: Vector3<f32>,
+    /// Orbit speed, in radians per second.
+    orbit_speed: f32,
+}
+
+impl Body {
+    const fn new(
+        name: &'static str,
+        distance: f32,
+        radius: f32,
+        color: (f32, f32, f32),
+        orbit_speed: f32,
+    ) -> Self {
+        Self {
+            name,
+            shape: Shape::Sphere,
+            distance,
+            radius,
+            color: Vector3::new(color.0, color.1, color.2),
+            orbit_speed,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SolarSystem {
+    pub graph: SceneGraph,
+    spins: Vec<Spin>,
+}
+
+impl Default for SolarSystem {
+    fn default() -> Self {
+        let mut system = Self {
+            graph: SceneGraph::default(),
+            spins: Vec::new(),
+        };
+
+        let sun = system.graph.add(
+            Node::new("Sun").with_light(LightAttachment {
+                color: Vector3::new(1.5, 1.4, 1.2),
+                constant: 1.0,
+                linear: 0.02,
+                quadratic: 0.002,
+            }),
+            None,
+        );
+        let sun_body = system.graph.add(
+            Node::new("Sun Body")
+                .with_scale(1.5)
+                .with_mesh(MeshAttachment {
+                    shape: Shape::Sphere,
+                    color: Vector3::new(1.0, 0.8, 0.3),
+                    emissive: true,
+                }),
+            Some(sun),
+        );
+        system.spins.push(Spin {
+            node: sun_body,
+            speed: 0.1,
+        });
+
+        system.add_body(sun, Body::new("Mercury", 3.0, 0.2, (0.6, 0.6, 0.6), 1.6));
+        system.add_body(sun, Body::new("Venus", 4.5, 0.35, (0.9, 0.7, 0.4), 1.2));
+
+        let earth = system.add_body(sun, Body::new("Earth", 6.5, 0.4, (0.2, 0.4, 0.9), 1.0));
+        let moon = system.add_body(earth, Body::new("Moon", 1.0, 0.12, (0.8, 0.8, 0.8), 4.0));
+        system.add_body(
+            earth,
+            Body {
+                shape: Shape::Cube,
+                ..Body::new("Station", 0.6, 0.05, (0.9, 0.9, 0.9), 8.0)
+            },
+        );
+
+        let mars = system.add_body(sun, Body::new("Mars", 9.0, 0.3, (0.8, 0.3, 0.2), 0.8));
+        system.add_body(mars, Body::new("Phobos", 0.55, 0.06, (0.6, 0.5, 0.4), 6.0));
+        system.add_body(mars, Body::new("Deimos", 0.85, 0.05, (0.7, 0.6, 0.5), 3.0));
+
+        let jupiter = system.add_body(sun, Body::new("Jupiter", 13.0, 0.9, (0.8, 0.6, 0.4), 0.4));
+        for (index, (name, color)) in [
+            ("Io", (0.9, 0.8, 0.3)),
+            ("Europa", (0.8, 0.7, 0.6)),
+            ("Ganymede", (0.6, 0.6, 0.6)),
+            ("Callisto", (0.4, 0.4, 0.4)),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let step = index as f32;
+            system.add_body(
+                jupiter,
+                Body::new(name, 1.3 + 0.4 * step, 0.1, color, 3.0 / (1.0 + step)),
+            );
+        }
+
+        // Bodies are placed along positive X axis of their orbit nodes,
+        // cameras behind them look back along negative X axis.
+        let look_back = Transform {
+            translation: Vector3::new(1.5, 0.4, 0.0),
+            ..Transform::from_angle_y(Deg(90.0).into())
+        };
+        system.graph.add(
+            Node::new("Earth Camera")
+                .with_transform(look_back)
+                .with_camera(CameraAttachment::default()),
+            Some(earth),
+        );
+        system.graph.add(
+            Node::new("Moon Camera")
+                .with_transform(Transform {
+                    translation: Vector3::new(0.3, 0.05, 0.0),
+                    ..look_back
+                })
+                .with_camera(CameraAttachment { fovy: 60.0 }),
+            Some(moon),
+        );
+
+        system
+    }
+}
+
+impl SolarSystem {
+    /// Add orbit, position and mesh nodes of `body`.
+    ///
+    /// Returns the position node, where moons and cameras are attached.
+    fn add_body(&mut self, parent: NodeId, body: Body) -> NodeId {
+        let orbit = self
+            .graph
+            .add(Node::new(&format!("{} Orbit", body.name)), Some(parent));
+        self.spins.push(Spin {
+            node: orbit,
+            speed: body.orbit_speed,
+        });
+
+        let position = self.graph.add(
+            Node::new(body.name).with_translation(Vector3::new(body.distance, 0.0, 0.0)),
+            Some(orbit),
+        );
+        let mesh = self.graph.add(
+            Node::new(&format!("{} Body", body.name))
+                .with_scale(body.radius)
+                .with_mesh(MeshAttachment {
+                    shape: body.shape,
+                    color: body.color,
+                    emissive: false,
+                }),
+            Some(position),
+        );
+        // Spin around its own axis, faster than orbiting.
+        self.spins.push(Spin {
+            node: mesh,
+            speed: body.orbit_speed * 2.0,
+        });
+
+        position
+    }
+
+    /// Rotate orbits and bodies to their angles at `time` in seconds.
+    pub fn update(&mut self, time: f32) {
+        for spin in &self.spins {
+            let angle = Rad(spin.speed * time);
+            self.graph.transform_mut(spin.node).rotation = Transform::from_angle_y(angle).rotation;
+        }
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Matrix4, One, Point3, Vector3};
+use std::mem;
+
+use crate::scene_graph::LightAttachment;
+
+/// Max number of point lights in a scene, lights after it are ignored.
+pub const MAX_LIGHTS: usize = 4;
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelUniform {
+    pub model: Matrix4<f32>,
+    /// Inverse transpose of `model`, so that normals stay perpendicular
+    /// to surfaces under non-uniform scale.
+    pub normal: Matrix4<f32>,
+    pub color: Vector3<f32>,
+    /// 1.0 for emissive meshes, 0.0 for lit ones.
+    pub emissive: f32,
+}
+
+impl Default for ModelUniform {
+    fn default() -> Self {
+        Self {
+            model: Matrix4::one(),
+            normal: Matrix4::one(),
+            color: Vector3::new(1.0, 1.0, 1.0),
+            emissive: 0.0,
+        }
+    }
+}
+
+pub type ModelUniformBytes = [f32; 36];
+pub type ModelUniformRef<'a> = &'a ModelUniformBytes;
+
+impl AsRef<ModelUniformBytes> for ModelUniform {
+    fn as_ref(&self) -> ModelUniformRef {
+        unsafe { mem::transmute(self) }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub position: Vector3<f32>,
+    pad0: f32,
+    pub color: Vector3<f32>,
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+    pad1: [f32; 2],
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self::new(Point3::new(0.0, 0.0, 0.0), &LightAttachment::default())
+    }
+}
+
+impl PointLight {
+    #[must_use]
+    pub const fn new(position: Point3<f32>, light: &LightAttachment) -> Self {
+        Self {
+            position: Vector3::new(position.x, position.y, position.z),
+            pad0: 1.0,
+            color: light.color,
+            constant: light.constant,
+            linear: light.linear,
+            quadratic: light.quadratic,
+            pad1: [1.0, 1.0],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LightsUniform {
+    pub lights: [PointLight; MAX_LIGHTS],
+    pub count: u32,
+    pad: [u32; 3],
+}
+
+impl Default for LightsUniform {
+    fn default() -> Self {
+        Self {
+            lights: [PointLight::default(); MAX_LIGHTS],
+            count: 0,
+            pad: [0; 3],
+        }
+    }
+}
+
+pub type LightsUniformBytes = [f32; 12 * MAX_LIGHTS + 4];
+pub type LightsUniformRef<'a> = &'a LightsUniformBytes;
+
+impl AsRef<LightsUniformBytes> for LightsUniform {
+    fn as_ref(&self) -> LightsUniformRef {
+        unsafe { mem::transmute(self) }
+    }
+}
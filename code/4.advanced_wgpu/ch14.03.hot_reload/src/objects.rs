@@ -0,0 +1,106 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use wgpu::util::DeviceExt;
+
+use crate::geometry::{GeometryData, INDICES32_THRESHOLD};
+use crate::uniforms::ModelUniform;
+
+#[derive(Debug)]
+pub struct Mesh {
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub index_format: wgpu::IndexFormat,
+    pub num_indices: u32,
+}
+
+impl Mesh {
+    pub fn new(device: &wgpu::Device, geometry_data: &GeometryData, label: &str) -> Self {
+        let vertices = geometry_data.vertex_data();
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{label} Vertex Buffer")),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        // Large meshes loaded from files only have 32-bit indices.
+        let use_indices16 = !geometry_data.indices16.is_empty()
+            && geometry_data.vertices.len() <= INDICES32_THRESHOLD;
+        let (contents, index_format, num_indices) = if use_indices16 {
+            (
+                bytemuck::cast_slice(&geometry_data.indices16),
+                wgpu::IndexFormat::Uint16,
+                geometry_data.indices16.len(),
+            )
+        } else {
+            (
+                bytemuck::cast_slice(&geometry_data.indices32),
+                wgpu::IndexFormat::Uint32,
+                geometry_data.indices32.len(),
+            )
+        };
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{label} Index Buffer")),
+            contents,
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            index_format,
+            num_indices: num_indices as u32,
+        }
+    }
+
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
+
+/// GPU side of a scene node with mesh attached.
+#[derive(Debug)]
+pub struct SceneObject {
+    pub uniform: ModelUniform,
+    pub uniform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl SceneObject {
+    pub fn new(
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        uniform: ModelUniform,
+        label: &str,
+    ) -> Self {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("{label} Model Buffer")),
+            contents: bytemuck::cast_slice(uniform.as_ref()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some(&format!("{label} Model Bind Group")),
+        });
+
+        Self {
+            uniform,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn write_uniform(&self, queue: &wgpu::Queue) {
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(self.uniform.as_ref()),
+        );
+    }
+}
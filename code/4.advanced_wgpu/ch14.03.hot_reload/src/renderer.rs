@@ -0,0 +1,397 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Draw meshes attached to scene graph nodes, lit by attached lights.
+
+use cgmath::{Matrix, Matrix4, SquareMatrix};
+use wgpu::util::DeviceExt;
+
+use crate::objects::{Mesh, SceneObject};
+use crate::scene::Scene;
+use crate::scene_graph::NodeId;
+use crate::texture::Texture;
+use crate::uniforms::{LightsUniform, ModelUniform, PointLight, MAX_LIGHTS};
+use crate::vertex::Vertex;
+use crate::Error;
+
+/// Shader of scene pipeline, relative to resource directory.
+pub const SCENE_SHADER: &str = "shaders/scene.wgsl";
+
+pub struct SceneRenderer {
+    render_pipeline: wgpu::RenderPipeline,
+    /// Kept to rebuild pipeline when shader is reloaded.
+    render_pipeline_layout: wgpu::PipelineLayout,
+    color_format: wgpu::TextureFormat,
+
+    /// Indexed like `Scene::meshes`.
+    meshes: Vec<Mesh>,
+    material_bind_group_layout: wgpu::BindGroupLayout,
+    /// Indexed like `Scene::textures`.
+    textures: Vec<Texture>,
+    /// Used by materials without texture.
+    white_texture: Texture,
+    /// Indexed like `Scene::materials`.
+    material_bind_groups: Vec<wgpu::BindGroup>,
+
+    lights: LightsUniform,
+    lights_buffer: wgpu::Buffer,
+    lights_bind_group: wgpu::BindGroup,
+
+    model_bind_group_layout: wgpu::BindGroupLayout,
+    /// Indexed by `NodeId`, None for nodes without mesh.
+    objects: Vec<Option<SceneObject>>,
+}
+
+impl SceneRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: &wgpu::SurfaceConfiguration,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Result<Self, Error> {
+        let lights = LightsUniform::default();
+        let lights_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Lights Uniform Buffer"),
+            contents: bytemuck::cast_slice(lights.as_ref()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let lights_bind_group_layout =
+            Self::create_uniform_bind_group_layout(device, "Lights Bind Group Layout");
+        let lights_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &lights_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: lights_buffer.as_entire_binding(),
+            }],
+            label: Some("Lights Bind Group"),
+        });
+
+        let model_bind_group_layout =
+            Self::create_uniform_bind_group_layout(device, "Model Bind Group Layout");
+        let material_bind_group_layout = Self::create_material_bind_group_layout(device);
+
+        let bind_group_layouts = [
+            camera_bind_group_layout,
+            &lights_bind_group_layout,
+            &model_bind_group_layout,
+            &material_bind_group_layout,
+        ];
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Scene Render Pipeline Layout"),
+                bind_group_layouts: &bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = Self::create_render_pipeline(
+            device,
+            config.format,
+            &render_pipeline_layout,
+            include_str!("../res/shaders/scene.wgsl"),
+        );
+
+        let white_texture =
+            Texture::from_color(device, queue, [255, 255, 255, 255], Some("White Texture"))?;
+
+        Ok(Self {
+            render_pipeline,
+            render_pipeline_layout,
+            color_format: config.format,
+
+            meshes: Vec::new(),
+            material_bind_group_layout,
+            textures: Vec::new(),
+            white_texture,
+            material_bind_groups: Vec::new(),
+
+            lights,
+            lights_buffer,
+            lights_bind_group,
+
+            model_bind_group_layout,
+            objects: Vec::new(),
+        })
+    }
+
+    fn create_material_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("Material Bind Group Layout"),
+        })
+    }
+
+    fn create_uniform_bind_group_layout(
+        device: &wgpu::Device,
+        label: &str,
+    ) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some(label),
+        })
+    }
+
+    fn create_render_pipeline(
+        device: &wgpu::Device,
+        color_format: wgpu::TextureFormat,
+        layout: &wgpu::PipelineLayout,
+        shader_source: &str,
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Scene Shader"),
+            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Scene Render Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
+
+    /// Create meshes and textures of `scene`, reading files relative to
+    /// its base directory.
+    ///
+    /// Resources of the previous scene are kept if any of them fails.
+    pub fn load_scene(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        scene: &Scene,
+    ) -> Result<(), Error> {
+        let mut meshes = Vec::with_capacity(scene.meshes.len());
+        for asset in &scene.meshes {
+            let geometry = asset.source.geometry(&scene.base_dir)?;
+            meshes.push(Mesh::new(device, &geometry, &asset.name));
+        }
+
+        let mut textures = Vec::with_capacity(scene.textures.len());
+        for asset in &scene.textures {
+            let img = image::open(scene.base_dir.join(&asset.path))?;
+            textures.push(Texture::from_image(device, queue, &img, Some(&asset.name))?);
+        }
+
+        self.material_bind_groups = self.create_material_bind_groups(device, scene, &textures);
+        self.meshes = meshes;
+        self.textures = textures;
+        // Node ids of the previous scene are not valid any more.
+        self.objects.clear();
+        Ok(())
+    }
+
+    /// Rebuild scene pipeline with new shader source.
+    ///
+    /// The current pipeline is kept if the shader fails to compile or
+    /// does not match the pipeline layout.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload_shader(
+        &mut self,
+        device: &wgpu::Device,
+        source: &str,
+    ) -> Result<(), wgpu::Error> {
+        // Catch validation errors, instead of panicking in the default
+        // uncaptured error handler.
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let render_pipeline = Self::create_render_pipeline(
+            device,
+            self.color_format,
+            &self.render_pipeline_layout,
+            source,
+        );
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(err) => Err(err),
+            None => {
+                self.render_pipeline = render_pipeline;
+                Ok(())
+            }
+        }
+    }
+
+    /// Upload texture `index` of `scene` again, and update materials using it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        scene: &Scene,
+        index: usize,
+    ) -> Result<(), Error> {
+        let asset = &scene.textures[index];
+        let img = image::open(scene.base_dir.join(&asset.path))?;
+        self.textures[index] = Texture::from_image(device, queue, &img, Some(&asset.name))?;
+        self.material_bind_groups = self.create_material_bind_groups(device, scene, &self.textures);
+        Ok(())
+    }
+
+    fn create_material_bind_groups(
+        &self,
+        device: &wgpu::Device,
+        scene: &Scene,
+        textures: &[Texture],
+    ) -> Vec<wgpu::BindGroup> {
+        scene
+            .materials
+            .iter()
+            .map(|material| {
+                let texture = material
+                    .texture
+                    .map_or(&self.white_texture, |index| &textures[index]);
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.material_bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&texture.view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                        },
+                    ],
+                    label: Some(&format!("{} Material Bind Group", material.name)),
+                })
+            })
+            .collect()
+    }
+
+    /// Upload uniforms of nodes, call it after world transforms of the
+    /// scene graph are updated, with nodes returned by
+    /// `update_world_transforms()`.
+    pub fn sync(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        scene: &Scene,
+        changed: &[NodeId],
+    ) {
+        let graph = &scene.graph;
+        let mut transform_changed = vec![false; graph.len()];
+        for id in changed {
+            transform_changed[id.index()] = true;
+        }
+        self.objects.resize_with(graph.len(), || None);
+
+        for (id, node) in graph.iter() {
+            let slot = &mut self.objects[id.index()];
+            let Some(mesh) = &node.mesh else {
+                *slot = None;
+                continue;
+            };
+
+            let mut uniform = slot
+                .as_ref()
+                .map_or_else(ModelUniform::default, |object| object.uniform.clone());
+            if slot.is_none() || transform_changed[id.index()] {
+                let model = *node.world_matrix();
+                uniform.model = model;
+                uniform.normal = model.invert().unwrap_or_else(Matrix4::identity).transpose();
+            }
+            let material = &scene.materials[mesh.material];
+            uniform.color = material.color;
+            uniform.emissive = if material.emissive { 1.0 } else { 0.0 };
+
+            match slot {
+                Some(object) => {
+                    // Colors may be edited without moving the node.
+                    if object.uniform != uniform {
+                        object.uniform = uniform;
+                        object.write_uniform(queue);
+                    }
+                }
+                None => {
+                    *slot = Some(SceneObject::new(
+                        device,
+                        &self.model_bind_group_layout,
+                        uniform,
+                        &node.name,
+                    ));
+                }
+            }
+        }
+
+        let mut lights = LightsUniform::default();
+        for (index, (_id, node, light)) in graph.lights().take(MAX_LIGHTS).enumerate() {
+            lights.lights[index] = PointLight::new(node.world_position(), light);
+            lights.count = index as u32 + 1;
+        }
+        if lights != self.lights {
+            self.lights = lights;
+            queue.write_buffer(
+                &self.lights_buffer,
+                0,
+                bytemuck::cast_slice(self.lights.as_ref()),
+            );
+        }
+    }
+
+    /// Draw meshes of all nodes, camera bind group shall be set to group 0.
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, scene: &Scene) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(1, &self.lights_bind_group, &[]);
+        for (id, node) in scene.graph.iter() {
+            let (Some(mesh), Some(Some(object))) = (&node.mesh, self.objects.get(id.index()))
+            else {
+                continue;
+            };
+            render_pass.set_bind_group(2, &object.bind_group, &[]);
+            render_pass.set_bind_group(3, &self.material_bind_groups[mesh.material], &[]);
+            self.meshes[mesh.mesh].draw(render_pass);
+        }
+    }
+}
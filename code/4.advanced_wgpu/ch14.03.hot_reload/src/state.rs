@@ -0,0 +1,531 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::Vector3;
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use wgpu::util::DeviceExt;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::window::Window;
+
+use crate::camera::Camera;
+use crate::frames::SceneGraphWindow;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::frames::{HotReloadWindow, SceneAction};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::hot_reload::{same_file, HotReload};
+use crate::renderer::SceneRenderer;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::renderer::SCENE_SHADER;
+use crate::scene::{Scene, SceneCamera};
+use crate::texture::Texture;
+use crate::Error;
+
+pub struct State {
+    surface: wgpu::Surface,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    size: PhysicalSize<u32>,
+    window: Window,
+
+    scene: Scene,
+    renderer: SceneRenderer,
+
+    /// Free camera controlled by mouse and keyboard.
+    camera: Camera,
+    /// Follows the camera of a scene node, if one is selected.
+    node_camera: Camera,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+
+    depth_texture: Texture,
+
+    start_time: Instant,
+    last_frame_time: Instant,
+    egui_platform: Platform,
+    egui_render_pass: RenderPass,
+    scene_window: SceneGraphWindow,
+
+    /// Watches resource directory in dev mode.
+    #[cfg(not(target_arch = "wasm32"))]
+    hot_reload: Option<HotReload>,
+    #[cfg(not(target_arch = "wasm32"))]
+    hot_reload_window: HotReloadWindow,
+}
+
+impl State {
+    async fn create_surface(
+        window: &Window,
+    ) -> Result<
+        (
+            wgpu::Surface,
+            wgpu::Device,
+            wgpu::Queue,
+            wgpu::SurfaceConfiguration,
+            PhysicalSize<u32>,
+            wgpu::TextureFormat,
+        ),
+        Error,
+    > {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let surface = unsafe { instance.create_surface(&window) }?;
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| {
+                Error::Others("Failed to get an approprivate wgpu adapter".to_owned())
+            })?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    features: if cfg!(target_arch = "wasm32") {
+                        wgpu::Features::empty()
+                    } else {
+                        wgpu::Features::POLYGON_MODE_LINE
+                    },
+                    limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                },
+                None,
+            )
+            .await?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(wgpu::TextureFormat::is_srgb)
+            .unwrap_or(surface_caps.formats[0]);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&device, &config);
+
+        Ok((surface, device, queue, config, size, surface_format))
+    }
+
+    fn create_camera(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+        pose: &SceneCamera,
+    ) -> Result<(Camera, wgpu::Buffer, wgpu::BindGroupLayout, wgpu::BindGroup), Error> {
+        let aspect = size.width as f32 / size.height as f32;
+        let mut camera = Camera::new(pose.eye, aspect);
+        camera.set_view(pose.eye, pose.target, Vector3::unit_y(), pose.fovy);
+
+        let uniform_ref = camera.uniform_ref();
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Uniform Buffer"),
+            contents: bytemuck::cast_slice(uniform_ref),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("camera_bind_group_layout"),
+            });
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("camera_bind_group"),
+        });
+
+        Ok((
+            camera,
+            camera_buffer,
+            camera_bind_group_layout,
+            camera_bind_group,
+        ))
+    }
+
+    fn create_egui_platform(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> (Platform, RenderPass, SceneGraphWindow) {
+        let platform = Platform::new(PlatformDescriptor {
+            physical_width: size.width,
+            physical_height: size.height,
+            scale_factor,
+            ..Default::default()
+        });
+
+        let render_pass = RenderPass::new(device, surface_format, 1);
+        let scene_window = SceneGraphWindow::default();
+
+        (platform, render_pass, scene_window)
+    }
+
+    /// Load scene from `scene_path`, or the built-in solar system if it is None.
+    pub async fn new(window: Window, scene_path: Option<&str>) -> Result<Self, Error> {
+        let (surface, device, queue, config, size, surface_format) =
+            Self::create_surface(&window).await?;
+
+        let (egui_platform, egui_render_pass, mut scene_window) =
+            Self::create_egui_platform(&device, surface_format, size, window.scale_factor());
+
+        let scene = match scene_path {
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(path) => {
+                scene_window.file_name = path.to_owned();
+                Scene::load(path)?
+            }
+            _ => Scene::builtin()?,
+        };
+
+        let (camera, camera_buffer, camera_bind_group_layout, camera_bind_group) =
+            Self::create_camera(&device, size, &scene.camera)?;
+
+        let node_camera = Camera::new(
+            (0.0, 0.0, 3.0).into(),
+            size.width as f32 / size.height as f32,
+        );
+
+        let mut renderer = SceneRenderer::new(&device, &queue, &config, &camera_bind_group_layout)?;
+        renderer.load_scene(&device, &queue, &scene)?;
+
+        let depth_texture = Texture::create_depth_texture(&device, size, Some("Depth Texture"));
+
+        Ok(Self {
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            size,
+
+            scene,
+            renderer,
+
+            camera,
+            node_camera,
+            camera_buffer,
+            camera_bind_group,
+
+            depth_texture,
+
+            start_time: Instant::now(),
+            last_frame_time: Instant::now(),
+            egui_platform,
+            egui_render_pass,
+            scene_window,
+
+            #[cfg(not(target_arch = "wasm32"))]
+            hot_reload: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            hot_reload_window: HotReloadWindow::default(),
+        })
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn platform_mut(&mut self) -> &mut Platform {
+        &mut self.egui_platform
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&self.device, &self.config);
+            let aspect = new_size.width as f32 / new_size.height as f32;
+            self.camera.set_aspect(aspect);
+            self.node_camera.set_aspect(aspect);
+
+            self.depth_texture =
+                Texture::create_depth_texture(&self.device, self.size, Some("Depth Texture"));
+        }
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        // Cameras of scene nodes are moved by animation only.
+        if self.scene_window.view_camera.is_some() {
+            return false;
+        }
+        self.camera.process_event(event)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn process_scene_action(&mut self, action: SceneAction) {
+        let file_name = self.scene_window.file_name.clone();
+        let status = match action {
+            SceneAction::Save => {
+                // Free camera is saved where it is now.
+                self.scene.camera = SceneCamera {
+                    eye: self.camera.eye(),
+                    target: self.camera.target(),
+                    fovy: self.camera.fovy(),
+                };
+                match self.scene.save(&file_name) {
+                    Ok(()) => format!("Saved to {file_name}"),
+                    Err(err) => format!("Failed to save: {err:?}"),
+                }
+            }
+            SceneAction::Load => match self.load_scene(&file_name) {
+                Ok(()) => format!("Loaded from {file_name}"),
+                Err(err) => format!("Failed to load: {err:?}"),
+            },
+        };
+        self.scene_window.status = status;
+    }
+
+    /// Replace current scene, which is kept if `path` fails to load.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_scene(&mut self, path: &str) -> Result<(), Error> {
+        let scene = Scene::load(path)?;
+        self.renderer
+            .load_scene(&self.device, &self.queue, &scene)?;
+        let pose = scene.camera;
+        self.camera
+            .set_view(pose.eye, pose.target, Vector3::unit_y(), pose.fovy);
+        self.scene = scene;
+        self.scene_window.reset();
+        Ok(())
+    }
+
+    /// Dev mode, reload shaders and textures in resource directory
+    /// of this chapter when they are changed.
+    ///
+    /// Textures of scene files outside of that directory are not watched.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn enable_hot_reload(&mut self) -> Result<(), Error> {
+        let hot_reload = HotReload::new(concat!(env!("CARGO_MANIFEST_DIR"), "/res"))?;
+        self.hot_reload_window.watched_dir = hot_reload.root().display().to_string();
+        // Shader may be edited after the binary is built.
+        let shader_path = hot_reload.root().join(SCENE_SHADER);
+        self.hot_reload = Some(hot_reload);
+        self.reload_file(&shader_path);
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn process_hot_reload(&mut self) {
+        let files = match &self.hot_reload {
+            Some(hot_reload) => hot_reload.changed_files(),
+            None => return,
+        };
+        for path in files {
+            self.reload_file(&path);
+        }
+    }
+
+    /// Reload `path` if it is the scene shader or a texture of the scene,
+    /// other files are ignored.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn reload_file(&mut self, path: &Path) {
+        let Some(hot_reload) = &self.hot_reload else {
+            return;
+        };
+        let result = if same_file(path, &hot_reload.root().join(SCENE_SHADER)) {
+            fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|source| {
+                    self.renderer
+                        .reload_shader(&self.device, &source)
+                        .map_err(|err| err.to_string())
+                })
+        } else if let Some(index) = self
+            .scene
+            .textures
+            .iter()
+            .position(|texture| same_file(path, &self.scene.base_dir.join(&texture.path)))
+        {
+            self.renderer
+                .reload_texture(&self.device, &self.queue, &self.scene, index)
+                .map_err(|err| format!("{err:?}"))
+        } else {
+            return;
+        };
+
+        let window = &mut self.hot_reload_window;
+        match result {
+            Ok(()) => {
+                log::info!("Reloaded {}", path.display());
+                window.clear_error(path);
+                window.status = format!("Reloaded {}", path.display());
+            }
+            Err(message) => {
+                log::warn!("Failed to reload {}: {message}", path.display());
+                window.set_error(path, message);
+            }
+        }
+    }
+
+    pub fn update(&mut self) {
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        self.egui_platform.update_time(elapsed);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.process_hot_reload();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(action) = self.scene_window.action.take() {
+            self.process_scene_action(action);
+        }
+
+        // Animation only advances while not paused.
+        let now = Instant::now();
+        if !self.scene_window.paused {
+            let dt = (now - self.last_frame_time).as_secs_f32();
+            self.scene.graph.animate(dt * self.scene_window.time_scale);
+        }
+        self.last_frame_time = now;
+
+        let graph = &mut self.scene.graph;
+        let changed = graph.update_world_transforms();
+        self.renderer
+            .sync(&self.device, &self.queue, &self.scene, &changed);
+        let graph = &self.scene.graph;
+
+        let camera = match self.scene_window.view_camera {
+            Some(id) => {
+                let node = graph.node(id);
+                let fovy = node.camera.map_or(45.0, |camera| camera.fovy);
+                let eye = node.world_position();
+                let forward = node.world_direction(-Vector3::unit_z());
+                let up = node.world_direction(Vector3::unit_y());
+                self.node_camera.set_view(eye, eye + forward, up, fovy);
+                &self.node_camera
+            }
+            None => &self.camera,
+        };
+        self.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(camera.uniform_ref()),
+        );
+    }
+
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.2,
+                            g: 0.3,
+                            b: 0.3,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_texture.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            self.renderer.draw(&mut render_pass, &self.scene);
+        }
+
+        // Draw the egui UI frame.
+        {
+            self.egui_platform.begin_frame();
+            self.scene_window
+                .ui(&self.egui_platform.context(), &mut self.scene);
+            #[cfg(not(target_arch = "wasm32"))]
+            if self.hot_reload.is_some() {
+                self.hot_reload_window.ui(&self.egui_platform.context());
+            }
+            let full_output = self.egui_platform.end_frame(Some(&self.window));
+            let paint_jobs = self.egui_platform.context().tessellate(full_output.shapes);
+            // Upload all resources for the GPU.
+            let screen_descriptor = ScreenDescriptor {
+                physical_width: self.config.width,
+                physical_height: self.config.height,
+                scale_factor: self.window.scale_factor() as f32,
+            };
+            let tdelta: egui::TexturesDelta = full_output.textures_delta;
+            self.egui_render_pass
+                .add_textures(&self.device, &self.queue, &tdelta)
+                .expect("add texture ok");
+            self.egui_render_pass.update_buffers(
+                &self.device,
+                &self.queue,
+                &paint_jobs,
+                &screen_descriptor,
+            );
+
+            // Record all render passes.
+            self.egui_render_pass
+                .execute(&mut encoder, &view, &paint_jobs, &screen_descriptor, None)
+                .unwrap();
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+#![allow(dead_code)]
+
+use cgmath::{BaseFloat, Matrix4, One, Quaternion, Rad, Rotation3, Vector3};
+
+pub fn translate<S: BaseFloat>(mat: &mut Matrix4<S>, translation: Vector3<S>) {
+    let trans_mat = Matrix4::from_translation(translation);
+    *mat = *mat * trans_mat
+}
+
+pub fn rotate<S: BaseFloat>(mat: &mut Matrix4<S>, rotation: Vector3<S>) {
+    let rotate_mat_x = Matrix4::from_angle_x(Rad(rotation[0]));
+    let rotate_mat_y = Matrix4::from_angle_y(Rad(rotation[1]));
+    let rotate_mat_z = Matrix4::from_angle_z(Rad(rotation[2]));
+
+    *mat = *mat * rotate_mat_z * rotate_mat_y * rotate_mat_x
+}
+
+pub fn scale<S: BaseFloat>(mat: &mut Matrix4<S>, scaling: Vector3<S>) {
+    let scale_mat = Matrix4::<S>::from_nonuniform_scale(scaling[0], scaling[1], scaling[2]);
+    *mat = *mat * scale_mat
+}
+
+/// Local transform of a scene node, relative to its parent.
+///
+/// Unlike the free functions above, which accumulate into a matrix,
+/// each part can be changed at any time without rebuilding the others.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Transform {
+    #[must_use]
+    pub fn from_translation(translation: Vector3<f32>) -> Self {
+        Self {
+            translation,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn from_rotation(rotation: Quaternion<f32>) -> Self {
+        Self {
+            rotation,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn from_scale(scale: f32) -> Self {
+        Self {
+            scale: Vector3::new(scale, scale, scale),
+            ..Self::default()
+        }
+    }
+
+    /// Rotation around Y axis, used by orbits and spinning planets.
+    #[must_use]
+    pub fn from_angle_y(angle: Rad<f32>) -> Self {
+        Self::from_rotation(Quaternion::from_angle_y(angle))
+    }
+
+    /// Scale first, then rotate, then translate.
+    #[must_use]
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
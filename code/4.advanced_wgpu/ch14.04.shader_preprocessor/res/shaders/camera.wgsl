@@ -0,0 +1,9 @@
+// Camera shared by all shaders.
+struct CameraUniform {
+	view_proj: mat4x4<f32>,
+	view_pos: vec3<f32>,
+}
+
+@group(0)
+@binding(0)
+var<uniform> camera_uniform: CameraUniform;
@@ -0,0 +1,47 @@
+// Phong lighting of point lights.
+//
+// Defines:
+// - SPECULAR, adds specular highlights.
+struct PointLight {
+	position: vec3<f32>,
+	color: vec3<f32>,
+	constant: f32,
+	linear: f32,
+	quadratic: f32,
+};
+
+const MAX_LIGHTS: u32 = 4u;
+
+struct LightsUniform {
+	lights: array<PointLight, MAX_LIGHTS>,
+	count: u32,
+};
+
+@group(1)
+@binding(0)
+var<uniform> lights_uniform: LightsUniform;
+
+#define SHININESS 32.0
+#define SPECULAR_STRENGTH 0.3
+
+fn attenuation(light: PointLight, distance: f32) -> f32 {
+	return 1.0 / (light.constant + light.linear * distance +
+		light.quadratic * distance * distance);
+}
+
+// Light reflected by surface at `frag_pos` with `color`.
+fn point_light(light: PointLight, frag_pos: vec3<f32>, norm: vec3<f32>,
+	view_dir: vec3<f32>, color: vec3<f32>) -> vec3<f32> {
+	// diffuse
+	let light_dir = normalize(light.position - frag_pos);
+	let diff = max(dot(norm, light_dir), 0.0);
+	var result = light.color * diff * color;
+
+#ifdef SPECULAR
+	let reflect_dir = reflect(-light_dir, norm);
+	let spec = pow(max(dot(view_dir, reflect_dir), 0.0), SHININESS);
+	result += light.color * spec * SPECULAR_STRENGTH;
+#endif
+
+	return result * attenuation(light, length(light.position - frag_pos));
+}
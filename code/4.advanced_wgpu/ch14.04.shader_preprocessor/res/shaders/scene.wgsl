@@ -0,0 +1,92 @@
+// Defines:
+// - TEXTURED, material has a diffuse texture in group 3.
+// - SPECULAR, see lighting.wgsl.
+// - FOG, fades distant objects into the clear color.
+#include "camera.wgsl"
+#include "lighting.wgsl"
+
+// Vertex Shader
+struct VertexInput {
+	@location(0) position: vec3<f32>,
+	@location(1) normal: vec3<f32>,
+	@location(2) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+	@builtin(position) position: vec4<f32>,
+	@location(0) frag_pos: vec3<f32>,
+	@location(1) normal: vec3<f32>,
+	@location(2) tex_coords: vec2<f32>,
+};
+
+struct ModelUniform {
+	model: mat4x4<f32>,
+	normal: mat4x4<f32>,
+	color: vec3<f32>,
+	emissive: f32,
+};
+
+@group(2)
+@binding(0)
+var<uniform> model_uniform: ModelUniform;
+
+#ifdef TEXTURED
+@group(3)
+@binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(3)
+@binding(1)
+var s_diffuse: sampler;
+#endif
+
+@vertex
+fn vs_main(
+	in: VertexInput,
+) -> VertexOutput {
+	let world_pos = model_uniform.model * vec4<f32>(in.position, 1.0);
+
+	var out: VertexOutput;
+	out.position = camera_uniform.view_proj * world_pos;
+	out.frag_pos = world_pos.xyz;
+	out.normal = (model_uniform.normal * vec4<f32>(in.normal, 0.0)).xyz;
+	out.tex_coords = in.tex_coords;
+	return out;
+}
+
+// Fragment Shader
+const AMBIENT: f32 = 0.05;
+
+#ifdef FOG
+#define FOG_DENSITY 0.04
+const FOG_COLOR: vec3<f32> = vec3<f32>(0.2, 0.3, 0.3);
+
+fn apply_fog(color: vec3<f32>, frag_pos: vec3<f32>) -> vec3<f32> {
+	let distance = length(camera_uniform.view_pos - frag_pos);
+	let visibility = exp(-FOG_DENSITY * distance);
+	return mix(FOG_COLOR, color, clamp(visibility, 0.0, 1.0));
+}
+#endif
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+#ifdef TEXTURED
+	let color = model_uniform.color * textureSample(t_diffuse, s_diffuse, in.tex_coords).rgb;
+#else
+	let color = model_uniform.color;
+#endif
+
+	var result = color;
+	if model_uniform.emissive < 0.5 {
+		let norm = normalize(in.normal);
+		let view_dir = normalize(camera_uniform.view_pos - in.frag_pos);
+		result = AMBIENT * color;
+		for (var i = 0u; i < min(lights_uniform.count, MAX_LIGHTS); i++) {
+			result += point_light(lights_uniform.lights[i], in.frag_pos, norm, view_dir, color);
+		}
+	}
+
+#ifdef FOG
+	result = apply_fog(result, in.frag_pos);
+#endif
+	return vec4(result, 1.0);
+}
@@ -0,0 +1,432 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+#![allow(dead_code)]
+
+use cgmath::Vector3;
+use instant::Instant;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::preprocessor::ShaderDefs;
+use crate::renderer::SceneRenderer;
+use crate::scene::Scene;
+use crate::scene_graph::{NodeId, SceneGraph};
+
+#[derive(Debug, Clone)]
+pub struct UserWindow {
+    name: String,
+    age: u32,
+}
+
+impl Default for UserWindow {
+    fn default() -> Self {
+        Self {
+            name: "Author".to_owned(),
+            age: 42,
+        }
+    }
+}
+
+impl UserWindow {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub const fn age(&self) -> u32 {
+        self.age
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("User")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let name_label = ui.label("Your name: ");
+                    ui.text_edit_singleline(&mut self.name)
+                        .labelled_by(name_label.id);
+                });
+                ui.add(egui::Slider::new(&mut self.age, 0..=120).text("age"));
+                if ui.button("Click each year").clicked() {
+                    self.age += 1;
+                }
+                ui.label(format!("Hello '{}', age {}", self.name, self.age));
+            });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ColorWindow {
+    color: Vector3<f32>,
+}
+
+impl Default for ColorWindow {
+    fn default() -> Self {
+        Self {
+            color: Vector3::new(0.3, 0.4, 0.5),
+        }
+    }
+}
+
+impl ColorWindow {
+    pub fn set_color(&mut self, color: Vector3<f32>) {
+        self.color = color;
+    }
+
+    pub fn color(&self) -> &Vector3<f32> {
+        &self.color
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Color")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.heading("Select vertex color:");
+                ui.color_edit_button_rgb(self.color.as_mut());
+            });
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FpsWindow {
+    fps: u32,
+    frames: u32,
+    timer: Instant,
+}
+
+impl Default for FpsWindow {
+    fn default() -> Self {
+        Self {
+            fps: 0,
+            frames: 0,
+            timer: Instant::now(),
+        }
+    }
+}
+
+impl FpsWindow {
+    pub fn update(&mut self) {
+        let dt = self.timer.elapsed().as_secs_f64();
+        let fps = (f64::from(self.frames) / dt).round() as u32;
+        //log::info!("fps: {fps}");
+        if dt > 1.0 {
+            self.frames = 0;
+            self.timer = Instant::now();
+            self.fps = fps;
+        }
+        self.frames += 1;
+    }
+
+    #[must_use]
+    pub const fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("FPS")
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                ui.heading(format!("FPS: {}", self.fps));
+            });
+    }
+}
+
+/// Requests from scene graph window which are handled by `State::update()`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneAction {
+    Save,
+    Load,
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneGraphWindow {
+    pub paused: bool,
+    /// Multiplier of animation speed.
+    pub time_scale: f32,
+    /// Node whose camera is used, or None for the free camera.
+    pub view_camera: Option<NodeId>,
+    selected: Option<NodeId>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub action: Option<SceneAction>,
+    pub file_name: String,
+    /// Result of the last save or load action.
+    pub status: String,
+}
+
+impl Default for SceneGraphWindow {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            time_scale: 1.0,
+            view_camera: None,
+            selected: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            action: None,
+            file_name: "scene.json".to_owned(),
+            status: String::new(),
+        }
+    }
+}
+
+impl SceneGraphWindow {
+    /// Forget nodes of the previous scene, call it after a scene is loaded.
+    pub fn reset(&mut self) {
+        self.view_camera = None;
+        self.selected = None;
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, scene: &mut Scene) {
+        egui::Window::new("Scene Graph")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                let graph = &scene.graph;
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.paused, "Paused");
+                    ui.add(egui::Slider::new(&mut self.time_scale, 0.0..=5.0).text("Speed"));
+                });
+
+                let camera_name = |id: Option<NodeId>| {
+                    id.map_or("Free camera", |id| graph.node(id).name.as_str())
+                        .to_owned()
+                };
+                egui::ComboBox::from_label("Camera")
+                    .selected_text(camera_name(self.view_camera))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.view_camera, None, "Free camera");
+                        for (id, node, _camera) in graph.cameras() {
+                            ui.selectable_value(&mut self.view_camera, Some(id), &node.name);
+                        }
+                    });
+
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for root in graph.roots() {
+                            self.node_ui(ui, graph, *root);
+                        }
+                    });
+
+                if let Some(selected) = self.selected {
+                    ui.separator();
+                    Self::selected_ui(ui, scene, selected);
+                }
+
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("File:");
+                        ui.text_edit_singleline(&mut self.file_name);
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Save").clicked() {
+                            self.action = Some(SceneAction::Save);
+                        }
+                        if ui.button("Load").clicked() {
+                            self.action = Some(SceneAction::Load);
+                        }
+                    });
+                }
+                if !self.status.is_empty() {
+                    ui.label(&self.status);
+                }
+            });
+    }
+
+    fn node_ui(&mut self, ui: &mut egui::Ui, graph: &SceneGraph, id: NodeId) {
+        let node = graph.node(id);
+        let selected = self.selected == Some(id);
+        if node.children().is_empty() {
+            if ui.selectable_label(selected, &node.name).clicked() {
+                self.selected = Some(id);
+            }
+            return;
+        }
+        egui::CollapsingHeader::new(&node.name)
+            .id_source(id)
+            .default_open(graph.node(id).parent().is_none())
+            .show(ui, |ui| {
+                if ui.selectable_label(selected, "(this node)").clicked() {
+                    self.selected = Some(id);
+                }
+                for child in node.children() {
+                    self.node_ui(ui, graph, *child);
+                }
+            });
+    }
+
+    fn selected_ui(ui: &mut egui::Ui, scene: &mut Scene, id: NodeId) {
+        let graph = &mut scene.graph;
+        let node = graph.node(id);
+        ui.label(format!("Selected: {}", node.name));
+        let position = node.world_position();
+        ui.label(format!(
+            "World position: ({:.2}, {:.2}, {:.2})",
+            position.x, position.y, position.z
+        ));
+
+        let mut transform = *node.transform();
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Translation:");
+            for value in [
+                &mut transform.translation.x,
+                &mut transform.translation.y,
+                &mut transform.translation.z,
+            ] {
+                changed |= ui.add(egui::DragValue::new(value).speed(0.05)).changed();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Scale:");
+            for value in [
+                &mut transform.scale.x,
+                &mut transform.scale.y,
+                &mut transform.scale.z,
+            ] {
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(value)
+                            .speed(0.01)
+                            .clamp_range(0.01..=10.0),
+                    )
+                    .changed();
+            }
+        });
+        if changed {
+            graph.set_transform(id, transform);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Spin:");
+            ui.add(egui::DragValue::new(&mut graph.node_mut(id).spin).speed(0.01));
+        });
+
+        // Materials are shared, other nodes with the same one change too.
+        if let Some(mesh) = graph.node(id).mesh {
+            let material = &mut scene.materials[mesh.material];
+            ui.horizontal(|ui| {
+                ui.label(format!("Material {}:", material.name));
+                ui.color_edit_button_rgb(material.color.as_mut());
+                ui.checkbox(&mut material.emissive, "Emissive");
+            });
+        }
+        if let Some(light) = &mut scene.graph.node_mut(id).light {
+            ui.horizontal(|ui| {
+                ui.label("Light:");
+                ui.color_edit_button_rgb(light.color.as_mut());
+            });
+        }
+    }
+}
+
+/// Shader features which apply to all materials.
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderWindow {
+    pub specular: bool,
+    pub fog: bool,
+}
+
+impl Default for ShaderWindow {
+    fn default() -> Self {
+        Self {
+            specular: true,
+            fog: false,
+        }
+    }
+}
+
+impl ShaderWindow {
+    /// Defines of the selected features.
+    #[must_use]
+    pub fn defs(&self) -> ShaderDefs {
+        let mut defs = ShaderDefs::new();
+        if self.specular {
+            defs.insert("SPECULAR", "");
+        }
+        if self.fog {
+            defs.insert("FOG", "");
+        }
+        defs
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, renderer: &SceneRenderer) {
+        egui::Window::new("Shader")
+            .default_width(320.0)
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.specular, "Specular");
+                    ui.checkbox(&mut self.fog, "Fog");
+                });
+                ui.label(format!("Features: {}", renderer.features()));
+                ui.label(format!("Cached pipelines: {}", renderer.pipeline_count()));
+                for (defs, message) in renderer.errors() {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(255, 96, 96),
+                        format!("Failed to build ({defs}):"),
+                    );
+                    ui.label(egui::RichText::new(message).monospace());
+                }
+            });
+    }
+}
+
+/// State of hot reload in dev mode.
+#[derive(Debug, Clone, Default)]
+pub struct HotReloadWindow {
+    pub watched_dir: String,
+    /// Result of the last successful reload.
+    pub status: String,
+    /// Error messages of files which failed to reload, the last good
+    /// version of them is still in use.
+    errors: BTreeMap<PathBuf, String>,
+}
+
+impl HotReloadWindow {
+    pub fn set_error(&mut self, path: &Path, message: String) {
+        self.errors.insert(path.to_path_buf(), message);
+    }
+
+    pub fn clear_error(&mut self, path: &Path) {
+        self.errors.remove(path);
+    }
+
+    pub fn ui(&self, ctx: &egui::Context) {
+        egui::Window::new("Hot Reload")
+            .default_width(320.0)
+            .default_open(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Watching: {}", self.watched_dir));
+                if !self.status.is_empty() {
+                    ui.label(&self.status);
+                }
+            });
+
+        if self.errors.is_empty() {
+            return;
+        }
+        // Overlay at the bottom of window, until all files are fixed.
+        egui::Area::new("hot_reload_errors")
+            .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(8.0, -8.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_max_width(ctx.screen_rect().width() - 32.0);
+                    for (path, message) in &self.errors {
+                        ui.colored_label(
+                            egui::Color32::from_rgb(255, 96, 96),
+                            format!(
+                                "Failed to reload {}, keeping the last good version:",
+                                path.display()
+                            ),
+                        );
+                        ui.label(egui::RichText::new(message).monospace());
+                    }
+                });
+            });
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Watch resource directory in dev mode, so that shaders and textures
+//! are reloaded as soon as they are saved.
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::Error;
+
+pub struct HotReload {
+    /// Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+    receiver: mpsc::Receiver<notify::Result<notify::Event>>,
+    root: PathBuf,
+}
+
+impl HotReload {
+    /// Watch `root` and all of its sub directories.
+    pub fn new<P: AsRef<Path>>(root: P) -> Result<Self, Error> {
+        let root = root.as_ref().canonicalize()?;
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            // Receiver is gone only if the app is exiting.
+            let _ret = sender.send(event);
+        })?;
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+        log::info!("Watching {} for changes", root.display());
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+            root,
+        })
+    }
+
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Files created or modified since the previous call, without duplicates.
+    ///
+    /// Editors usually emit several events for one save, and some of them
+    /// write a temporary file and rename it, so only files that still
+    /// exist are returned.
+    pub fn changed_files(&self) -> Vec<PathBuf> {
+        let mut files = BTreeSet::new();
+        for event in self.receiver.try_iter() {
+            match event {
+                Ok(event) => {
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        files.extend(event.paths);
+                    }
+                }
+                Err(err) => log::warn!("File watcher error: {err:?}"),
+            }
+        }
+        files.into_iter().filter(|path| path.is_file()).collect()
+    }
+}
+
+/// Returns true if `a` and `b` are the same file, for paths which are
+/// written differently, like relative paths in scene files.
+#[must_use]
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Minimal Wavefront OBJ parser, for meshes referenced by scene files.
+//!
+//! Only positions, texture coordinates, normals and faces are read,
+//! materials, groups and other statements are ignored. Polygons are
+//! split into triangle fans.
+
+use cgmath::{InnerSpace, Vector3};
+use std::collections::HashMap;
+
+use crate::geometry::{GeometryData, INDICES32_THRESHOLD};
+use crate::Error;
+
+/// Indices of position, texture coordinate and normal of a face vertex.
+type VertexKey = (usize, Option<usize>, Option<usize>);
+
+pub fn parse_obj(source: &str) -> Result<GeometryData, Error> {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut tex_coords: Vec<[f32; 2]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+
+    let mut geo_data = GeometryData::default();
+    let mut vertex_map: HashMap<VertexKey, u32> = HashMap::new();
+    let mut has_normals = true;
+
+    for (line_no, line) in source.lines().enumerate() {
+        let error = |msg: &str| Error::Others(format!("OBJ line {}: {msg}", line_no + 1));
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("v") => positions.push(parse_floats(words).ok_or_else(|| error("invalid v"))?),
+            Some("vt") => {
+                // The optional third coordinate is for 3D textures.
+                let [u, v, _w]: [f32; 3] =
+                    parse_floats(words.chain(["0"])).ok_or_else(|| error("invalid vt"))?;
+                tex_coords.push([u, v]);
+            }
+            Some("vn") => normals.push(parse_floats(words).ok_or_else(|| error("invalid vn"))?),
+            Some("f") => {
+                let mut face = Vec::new();
+                for word in words {
+                    let key =
+                        parse_face_vertex(word, positions.len(), tex_coords.len(), normals.len())
+                            .ok_or_else(|| error(&format!("invalid face vertex {word:?}")))?;
+                    has_normals &= key.2.is_some();
+                    let index = *vertex_map.entry(key).or_insert_with(|| {
+                        let (position, tex_coord, normal) = key;
+                        geo_data.vertices.push(positions[position]);
+                        geo_data
+                            .tex_coords
+                            .push(tex_coord.map_or([0.0, 0.0], |index| tex_coords[index]));
+                        geo_data
+                            .normals
+                            .push(normal.map_or([0.0, 0.0, 0.0], |index| normals[index]));
+                        geo_data.vertices.len() as u32 - 1
+                    });
+                    face.push(index);
+                }
+                if face.len() < 3 {
+                    return Err(error("face with less than 3 vertices"));
+                }
+                for i in 1..face.len() - 1 {
+                    geo_data
+                        .indices32
+                        .extend_from_slice(&[face[0], face[i], face[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if geo_data.indices32.is_empty() {
+        return Err(Error::Others("OBJ file has no faces".to_owned()));
+    }
+    if !has_normals {
+        compute_normals(&mut geo_data);
+    }
+    if geo_data.vertices.len() <= INDICES32_THRESHOLD {
+        geo_data.indices16 = geo_data.indices32.iter().map(|i| *i as u16).collect();
+    }
+    Ok(geo_data)
+}
+
+fn parse_floats<'a, const N: usize>(mut words: impl Iterator<Item = &'a str>) -> Option<[f32; N]> {
+    let mut values = [0.0; N];
+    for value in &mut values {
+        *value = words.next()?.parse().ok()?;
+    }
+    Some(values)
+}
+
+/// Parse `v`, `v/vt`, `v//vn` or `v/vt/vn`, indices start from 1 and
+/// negative ones count back from the last element.
+fn parse_face_vertex(
+    word: &str,
+    num_positions: usize,
+    num_tex_coords: usize,
+    num_normals: usize,
+) -> Option<VertexKey> {
+    let resolve = |part: &str, len: usize| -> Option<usize> {
+        let index: i64 = part.parse().ok()?;
+        let index = if index < 0 {
+            len as i64 + index
+        } else {
+            index - 1
+        };
+        usize::try_from(index).ok().filter(|index| *index < len)
+    };
+
+    let mut parts = word.split('/');
+    let position = resolve(parts.next()?, num_positions)?;
+    let tex_coord = match parts.next() {
+        None | Some("") => None,
+        Some(part) => Some(resolve(part, num_tex_coords)?),
+    };
+    let normal = match parts.next() {
+        None | Some("") => None,
+        Some(part) => Some(resolve(part, num_normals)?),
+    };
+    Some((position, tex_coord, normal))
+}
+
+/// Smooth normals, averaged from faces around each vertex.
+fn compute_normals(geo_data: &mut GeometryData) {
+    let mut normals = vec![Vector3::new(0.0, 0.0, 0.0); geo_data.vertices.len()];
+    for triangle in geo_data.indices32.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(geo_data.vertices[triangle[i] as usize]));
+        // Not normalized, so that larger faces weigh more.
+        let normal = (b - a).cross(c - a);
+        for index in triangle {
+            normals[*index as usize] += normal;
+        }
+    }
+    geo_data.normals = normals
+        .into_iter()
+        .map(|normal| {
+            if normal.magnitude2() > 0.0 {
+                normal.normalize().into()
+            } else {
+                [0.0, 1.0, 0.0]
+            }
+        })
+        .collect();
+}
@@ -0,0 +1,394 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Small WGSL preprocessor, so that shared code like lighting is written
+//! only once.
+//!
+//! Directives are on their own lines:
+//! - `#include "lighting.wgsl"` inserts another source, each file is
+//!   included at most once in a shader, so include guards are not needed.
+//! - `#define NAME` or `#define NAME value`, the value replaces NAME as a
+//!   whole word in the following lines.
+//! - `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif`.
+//!
+//! Defines passed to `Preprocessor::process()` select a permutation of
+//! features, like `TEXTURED` or `FOG`.
+
+#![allow(dead_code)]
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use crate::Error;
+
+/// Defines of a shader permutation, sorted so that it can be used as key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShaderDefs(BTreeMap<String, String>);
+
+impl ShaderDefs {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with(mut self, name: &str) -> Self {
+        self.insert(name, "");
+        self
+    }
+
+    pub fn insert(&mut self, name: &str, value: &str) {
+        self.0.insert(name.to_owned(), value.to_owned());
+    }
+
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+impl fmt::Display for ShaderDefs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("(none)");
+        }
+        for (index, (name, value)) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            if value.is_empty() {
+                f.write_str(name)?;
+            } else {
+                write!(f, "{name}={value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Line in one of the original source files, starting from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessedShader {
+    pub source: String,
+    /// Location of each line of `source` in the original files.
+    lines: Vec<SourceLocation>,
+    /// The shader itself and all files it includes.
+    pub files: Vec<String>,
+}
+
+impl ProcessedShader {
+    /// Original location of `line` of `source`, starting from 1.
+    #[must_use]
+    pub fn location(&self, line: usize) -> Option<&SourceLocation> {
+        line.checked_sub(1).and_then(|index| self.lines.get(index))
+    }
+
+    /// Replace locations like `wgsl:12:5` in compiler messages with the
+    /// file and line which the code comes from.
+    #[must_use]
+    pub fn map_error(&self, message: &str) -> String {
+        const PREFIX: &str = "wgsl:";
+        let mut result = String::with_capacity(message.len());
+        let mut rest = message;
+        while let Some(start) = rest.find(PREFIX) {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + PREFIX.len()..];
+            let digits = after.bytes().take_while(u8::is_ascii_digit).count();
+            match after[..digits]
+                .parse()
+                .ok()
+                .and_then(|line| self.location(line))
+            {
+                Some(location) => {
+                    result.push_str(&format!("{}:{}", location.file, location.line));
+                    rest = &after[digits..];
+                }
+                None => {
+                    result.push_str(PREFIX);
+                    rest = after;
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// State of one `#ifdef` block.
+#[derive(Debug, Clone, Copy)]
+struct Condition {
+    /// Lines are emitted in the current branch.
+    active: bool,
+    /// Lines of the enclosing block are emitted.
+    parent_active: bool,
+    seen_else: bool,
+}
+
+struct Output {
+    defines: HashMap<String, String>,
+    included: HashSet<String>,
+    source: String,
+    lines: Vec<SourceLocation>,
+    files: Vec<String>,
+}
+
+/// Shader sources by file name, relative to the shader directory.
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessor {
+    sources: HashMap<String, String>,
+}
+
+impl Preprocessor {
+    pub fn set_source(&mut self, name: &str, source: String) {
+        self.sources.insert(name.to_owned(), source);
+    }
+
+    #[must_use]
+    pub fn has_source(&self, name: &str) -> bool {
+        self.sources.contains_key(name)
+    }
+
+    /// Expand includes and conditions of shader `name`, with `defs` defined.
+    pub fn process(&self, name: &str, defs: &ShaderDefs) -> Result<ProcessedShader, Error> {
+        let mut output = Output {
+            defines: defs
+                .iter()
+                .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                .collect(),
+            included: HashSet::new(),
+            source: String::new(),
+            lines: Vec::new(),
+            files: Vec::new(),
+        };
+        self.process_file(name, &mut output)?;
+        Ok(ProcessedShader {
+            source: output.source,
+            lines: output.lines,
+            files: output.files,
+        })
+    }
+
+    fn process_file(&self, name: &str, output: &mut Output) -> Result<(), Error> {
+        if !output.included.insert(name.to_owned()) {
+            return Ok(());
+        }
+        let source = self
+            .sources
+            .get(name)
+            .ok_or_else(|| Error::Others(format!("Shader file not found: {name}")))?;
+        output.files.push(name.to_owned());
+
+        let mut conditions: Vec<Condition> = Vec::new();
+        for (index, line) in source.lines().enumerate() {
+            let line_no = index + 1;
+            let error = |msg: String| Error::Others(format!("{name}:{line_no}: {msg}"));
+            let active = conditions.last().is_none_or(|condition| condition.active);
+
+            let Some(directive) = line.trim_start().strip_prefix('#') else {
+                if active {
+                    output.source.push_str(&substitute(line, &output.defines));
+                    output.source.push('\n');
+                    output.lines.push(SourceLocation {
+                        file: name.to_owned(),
+                        line: line_no,
+                    });
+                }
+                continue;
+            };
+
+            let mut words = directive.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            let mut argument = || {
+                words
+                    .next()
+                    .ok_or_else(|| error(format!("#{keyword} needs an argument")))
+            };
+            match keyword {
+                "ifdef" | "ifndef" => {
+                    let defined = output.defines.contains_key(argument()?);
+                    conditions.push(Condition {
+                        active: active && (defined == (keyword == "ifdef")),
+                        parent_active: active,
+                        seen_else: false,
+                    });
+                }
+                "else" => {
+                    let condition = conditions
+                        .last_mut()
+                        .filter(|condition| !condition.seen_else)
+                        .ok_or_else(|| error("#else without #ifdef".to_owned()))?;
+                    condition.active = condition.parent_active && !condition.active;
+                    condition.seen_else = true;
+                }
+                "endif" => {
+                    conditions
+                        .pop()
+                        .ok_or_else(|| error("#endif without #ifdef".to_owned()))?;
+                }
+                "define" if active => {
+                    let define = argument()?.to_owned();
+                    let value = words.collect::<Vec<_>>().join(" ");
+                    output.defines.insert(define, value);
+                }
+                "include" if active => {
+                    let file = directive.trim_start()["include".len()..].trim();
+                    let file = file
+                        .strip_prefix('"')
+                        .and_then(|file| file.strip_suffix('"'))
+                        .ok_or_else(|| error(format!("invalid include {file}")))?;
+                    self.process_file(file, output).map_err(|err| match err {
+                        Error::Others(msg) => error(msg),
+                        err => err,
+                    })?;
+                }
+                "define" | "include" => {}
+                _ => return Err(error(format!("unknown directive #{keyword}"))),
+            }
+        }
+
+        if conditions.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Others(format!("{name}: #ifdef without #endif")))
+        }
+    }
+}
+
+/// Replace whole words which are defined with a value.
+fn substitute(line: &str, defines: &HashMap<String, String>) -> String {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find(is_ident) {
+        result.push_str(&rest[..start]);
+        let word_len = rest[start..]
+            .find(|c: char| !is_ident(c))
+            .unwrap_or(rest.len() - start);
+        let word = &rest[start..start + word_len];
+        match defines.get(word) {
+            Some(value) if !value.is_empty() => result.push_str(value),
+            _ => result.push_str(word),
+        }
+        rest = &rest[start + word_len..];
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preprocessor(sources: &[(&str, &str)]) -> Preprocessor {
+        let mut preprocessor = Preprocessor::default();
+        for (name, source) in sources {
+            preprocessor.set_source(name, (*source).to_owned());
+        }
+        preprocessor
+    }
+
+    fn process_lines(preprocessor: &Preprocessor, defs: &ShaderDefs) -> Vec<String> {
+        let shader = preprocessor.process("main.wgsl", defs).unwrap();
+        shader.source.lines().map(str::to_owned).collect()
+    }
+
+    fn process_error(source: &str) -> String {
+        preprocessor(&[("main.wgsl", source)])
+            .process("main.wgsl", &ShaderDefs::new())
+            .unwrap_err()
+            .into_message()
+    }
+
+    #[test]
+    fn nested_conditions() {
+        let preprocessor = preprocessor(&[(
+            "main.wgsl",
+            "a\n#ifdef A\nb\n#ifdef B\nc\n#else\nd\n#endif\n#endif\ne",
+        )]);
+        let defs = ShaderDefs::new();
+        assert_eq!(process_lines(&preprocessor, &defs), ["a", "e"]);
+        let defs = ShaderDefs::new().with("A");
+        assert_eq!(process_lines(&preprocessor, &defs), ["a", "b", "d", "e"]);
+        let defs = ShaderDefs::new().with("A").with("B");
+        assert_eq!(process_lines(&preprocessor, &defs), ["a", "b", "c", "e"]);
+    }
+
+    #[test]
+    fn else_in_inactive_parent() {
+        let preprocessor = preprocessor(&[(
+            "main.wgsl",
+            "#ifdef A\n#ifdef B\nb\n#else\nnot_b\n#endif\n#else\nnot_a\n#endif",
+        )]);
+        assert_eq!(process_lines(&preprocessor, &ShaderDefs::new()), ["not_a"]);
+        let defs = ShaderDefs::new().with("B");
+        assert_eq!(process_lines(&preprocessor, &defs), ["not_a"]);
+    }
+
+    #[test]
+    fn files_are_included_once() {
+        let preprocessor = preprocessor(&[
+            (
+                "main.wgsl",
+                "#include \"common.wgsl\"\n#include \"other.wgsl\"\n#include \"common.wgsl\"\nmain",
+            ),
+            ("common.wgsl", "common"),
+            ("other.wgsl", "#include \"common.wgsl\"\nother"),
+        ]);
+        let shader = preprocessor
+            .process("main.wgsl", &ShaderDefs::new())
+            .unwrap();
+        assert_eq!(shader.source, "common\nother\nmain\n");
+        assert_eq!(shader.files, ["main.wgsl", "common.wgsl", "other.wgsl"]);
+    }
+
+    #[test]
+    fn invalid_directives_are_reported() {
+        assert_eq!(
+            process_error("a\n#pragma once"),
+            "main.wgsl:2: unknown directive #pragma"
+        );
+        assert_eq!(
+            process_error("#ifdef A\na"),
+            "main.wgsl: #ifdef without #endif"
+        );
+        assert_eq!(
+            process_error("#endif"),
+            "main.wgsl:1: #endif without #ifdef"
+        );
+        assert_eq!(
+            process_error("#ifdef A\n#else\n#else\n#endif"),
+            "main.wgsl:3: #else without #ifdef"
+        );
+    }
+
+    #[test]
+    fn error_lines_are_mapped_across_includes() {
+        let preprocessor = preprocessor(&[
+            ("main.wgsl", "a\n#include \"lib.wgsl\"\nb"),
+            ("lib.wgsl", "#define SCALE 2.0\nc\nd"),
+        ]);
+        let shader = preprocessor
+            .process("main.wgsl", &ShaderDefs::new())
+            .unwrap();
+        assert_eq!(shader.source, "a\nc\nd\nb\n");
+        assert_eq!(
+            shader.map_error("error: wgsl:3:5 and wgsl:4:1"),
+            "error: lib.wgsl:3:5 and main.wgsl:3:1"
+        );
+        // Lines out of range are kept as they are.
+        assert_eq!(shader.map_error("wgsl:9:1"), "wgsl:9:1");
+    }
+}
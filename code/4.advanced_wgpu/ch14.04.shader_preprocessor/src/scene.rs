@@ -0,0 +1,209 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Scene graph with the meshes, textures and materials used by its nodes,
+//! and the initial pose of the free camera.
+
+#![allow(dead_code)]
+
+use cgmath::{Point3, Vector3};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::geometry::{
+    create_cone_detail, create_cube_detail, create_cylinder_detail, create_grid_detail,
+    create_plane_detail, create_sphere_detail, GeometryData,
+};
+use crate::obj::parse_obj;
+use crate::scene_file::SceneFile;
+use crate::scene_graph::SceneGraph;
+use crate::Error;
+
+/// Built-in scene, used if no scene file is given in command line.
+const DEFAULT_SCENE: &str = include_str!("../res/scenes/solar_system.json");
+
+/// Primitive generator with its parameters, or a mesh file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeshSource {
+    Cube {
+        width: f32,
+        height: f32,
+        depth: f32,
+    },
+    Sphere {
+        radius: f32,
+        levels: u32,
+        slices: u32,
+    },
+    Cylinder {
+        radius: f32,
+        height: f32,
+        slices: u32,
+        stacks: u32,
+    },
+    Cone {
+        radius: f32,
+        height: f32,
+        slices: u32,
+    },
+    Plane {
+        width: f32,
+        depth: f32,
+    },
+    Grid {
+        width: f32,
+        depth: f32,
+        slices_x: u32,
+        slices_y: u32,
+    },
+    /// Wavefront OBJ file, relative to the scene file.
+    File(String),
+}
+
+impl MeshSource {
+    pub fn geometry(&self, base_dir: &Path) -> Result<GeometryData, Error> {
+        let geo_data = match *self {
+            Self::Cube {
+                width,
+                height,
+                depth,
+            } => create_cube_detail(width, height, depth),
+            Self::Sphere {
+                radius,
+                levels,
+                slices,
+            } => create_sphere_detail(radius, levels, slices),
+            Self::Cylinder {
+                radius,
+                height,
+                slices,
+                stacks,
+            } => create_cylinder_detail(radius, height, slices, stacks, 1.0, 1.0),
+            Self::Cone {
+                radius,
+                height,
+                slices,
+            } => create_cone_detail(radius, height, slices),
+            Self::Plane { width, depth } => create_plane_detail(width, depth, 1.0, 1.0),
+            Self::Grid {
+                width,
+                depth,
+                slices_x,
+                slices_y,
+            } => create_grid_detail(width, depth, slices_x, slices_y, 1.0, 1.0),
+            Self::File(ref path) => parse_obj(&fs::read_to_string(base_dir.join(path))?)?,
+        };
+        Ok(geo_data)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeshAsset {
+    pub name: String,
+    pub source: MeshSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextureAsset {
+    pub name: String,
+    /// Image file, relative to the scene file.
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// Multiplied with the texture, if there is one.
+    pub color: Vector3<f32>,
+    /// Emissive meshes are not lit, they glow with their own color, like the sun.
+    pub emissive: bool,
+    /// Index into `Scene::textures`.
+    pub texture: Option<usize>,
+}
+
+/// Pose of the free camera when a scene is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SceneCamera {
+    pub eye: Point3<f32>,
+    pub target: Point3<f32>,
+    /// Vertical field of view, in degrees.
+    pub fovy: f32,
+}
+
+impl Default for SceneCamera {
+    fn default() -> Self {
+        Self {
+            eye: Point3::new(0.0, 0.0, 3.0),
+            target: Point3::new(0.0, 0.0, 0.0),
+            fovy: 45.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Scene {
+    pub graph: SceneGraph,
+    pub meshes: Vec<MeshAsset>,
+    pub textures: Vec<TextureAsset>,
+    pub materials: Vec<Material>,
+    pub camera: SceneCamera,
+    /// Directory of the scene file, paths of meshes and textures are
+    /// relative to it.
+    pub base_dir: PathBuf,
+}
+
+impl Scene {
+    /// The solar system, built into the binary.
+    pub fn builtin() -> Result<Self, Error> {
+        SceneFile::from_json(DEFAULT_SCENE)?
+            .into_scene(Path::new(env!("CARGO_MANIFEST_DIR")).join("res/scenes"))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        SceneFile::load(path)?.into_scene(parent_dir(path).to_path_buf())
+    }
+
+    /// Write the current state, including animated transforms and
+    /// edited materials, to `path`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        SceneFile::from_scene(self, parent_dir(path)).save(path)
+    }
+
+    /// Path of an asset, to be written to a scene file in `dir`.
+    ///
+    /// Paths are kept as they are if the file is saved to the same
+    /// directory, otherwise they are made absolute.
+    #[must_use]
+    pub fn asset_path(&self, path: &str, dir: &Path) -> String {
+        if same_dir(&self.base_dir, dir) {
+            return path.to_owned();
+        }
+        let full_path = self.base_dir.join(path);
+        fs::canonicalize(&full_path)
+            .unwrap_or(full_path)
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Directory of `path`, or current directory for bare file names.
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
@@ -0,0 +1,284 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! JSON scene format.
+//!
+//! Meshes, textures and materials are listed once with a name, and nodes
+//! refer to them by name. Nodes are nested like the scene graph:
+//!
+//! ```json
+//! {
+//!   "camera": { "eye": { "x": 0.0, "y": 2.0, "z": 8.0 }, "target": ..., "fovy": 45.0 },
+//!   "meshes": [{ "name": "box", "source": { "cube": { "width": 1.0, ... } } }],
+//!   "textures": [{ "name": "container", "path": "../textures/container.jpg" }],
+//!   "materials": [{ "name": "crate", "color": ..., "texture": "container" }],
+//!   "nodes": [{ "name": "Box", "mesh": "box", "material": "crate", "children": [] }]
+//! }
+//! ```
+
+use cgmath::{Deg, Euler, Quaternion, Rad, Vector3};
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::scene::{Material, MeshAsset, MeshSource, Scene, SceneCamera, TextureAsset};
+use crate::scene_graph::{
+    CameraAttachment, LightAttachment, MeshAttachment, Node, NodeId, SceneGraph,
+};
+use crate::transforms::Transform;
+use crate::Error;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterialDesc {
+    pub name: String,
+    pub color: Vector3<f32>,
+    #[serde(default)]
+    pub emissive: bool,
+    /// Name of texture.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<String>,
+}
+
+/// Local transform, with rotation in Euler angles which are easier to
+/// write by hand than quaternions.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformDesc {
+    pub translation: Vector3<f32>,
+    /// Rotation around X, Y and Z axes, in degrees.
+    pub rotation: Vector3<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Default for TransformDesc {
+    fn default() -> Self {
+        Self::from(&Transform::default())
+    }
+}
+
+impl From<&Transform> for TransformDesc {
+    fn from(transform: &Transform) -> Self {
+        let euler = Euler::from(transform.rotation);
+        // Rounded, so that angles written by hand are saved as they are,
+        // instead of 19.999998.
+        let degrees = |angle: Rad<f32>| (Deg::from(angle).0 * 1000.0).round() / 1000.0;
+        Self {
+            translation: transform.translation,
+            rotation: Vector3::new(degrees(euler.x), degrees(euler.y), degrees(euler.z)),
+            scale: transform.scale,
+        }
+    }
+}
+
+impl From<&TransformDesc> for Transform {
+    fn from(desc: &TransformDesc) -> Self {
+        let euler = Euler::new(
+            Deg(desc.rotation.x),
+            Deg(desc.rotation.y),
+            Deg(desc.rotation.z),
+        );
+        Self {
+            translation: desc.translation,
+            rotation: Quaternion::from(euler),
+            scale: desc.scale,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeDesc {
+    pub name: String,
+    #[serde(default)]
+    pub transform: TransformDesc,
+    /// Names of mesh and its material, both or neither shall be set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mesh: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub light: Option<LightAttachment>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub camera: Option<CameraAttachment>,
+    /// Rotation speed around local Y axis, in radians per second.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub spin: f32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<NodeDesc>,
+}
+
+fn is_zero(value: &f32) -> bool {
+    *value == 0.0
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SceneFile {
+    #[serde(default)]
+    pub camera: SceneCamera,
+    #[serde(default)]
+    pub meshes: Vec<MeshAsset>,
+    #[serde(default)]
+    pub textures: Vec<TextureAsset>,
+    #[serde(default)]
+    pub materials: Vec<MaterialDesc>,
+    pub nodes: Vec<NodeDesc>,
+}
+
+impl SceneFile {
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Describe the current state of `scene`, to be saved in `dir`.
+    #[must_use]
+    pub fn from_scene(scene: &Scene, dir: &Path) -> Self {
+        let meshes = scene
+            .meshes
+            .iter()
+            .map(|mesh| {
+                let mut mesh = mesh.clone();
+                if let MeshSource::File(path) = &mut mesh.source {
+                    *path = scene.asset_path(path, dir);
+                }
+                mesh
+            })
+            .collect();
+        let textures = scene
+            .textures
+            .iter()
+            .map(|texture| TextureAsset {
+                name: texture.name.clone(),
+                path: scene.asset_path(&texture.path, dir),
+            })
+            .collect();
+        let materials = scene
+            .materials
+            .iter()
+            .map(|material| MaterialDesc {
+                name: material.name.clone(),
+                color: material.color,
+                emissive: material.emissive,
+                texture: material
+                    .texture
+                    .map(|index| scene.textures[index].name.clone()),
+            })
+            .collect();
+        let nodes = scene
+            .graph
+            .roots()
+            .iter()
+            .map(|root| Self::node_desc(scene, *root))
+            .collect();
+
+        Self {
+            camera: scene.camera,
+            meshes,
+            textures,
+            materials,
+            nodes,
+        }
+    }
+
+    fn node_desc(scene: &Scene, id: NodeId) -> NodeDesc {
+        let node = scene.graph.node(id);
+        NodeDesc {
+            name: node.name.clone(),
+            transform: TransformDesc::from(node.transform()),
+            mesh: node.mesh.map(|mesh| scene.meshes[mesh.mesh].name.clone()),
+            material: node
+                .mesh
+                .map(|mesh| scene.materials[mesh.material].name.clone()),
+            light: node.light,
+            camera: node.camera,
+            spin: node.spin,
+            children: node
+                .children()
+                .iter()
+                .map(|child| Self::node_desc(scene, *child))
+                .collect(),
+        }
+    }
+
+    /// Build scene graph, resolving names of meshes, materials and textures.
+    ///
+    /// Files of meshes and textures are not read here, `base_dir` is kept
+    /// in the scene for the renderer.
+    pub fn into_scene(self, base_dir: PathBuf) -> Result<Scene, Error> {
+        let find = |kind: &str, names: &[&str], name: &str| {
+            names
+                .iter()
+                .position(|item| *item == name)
+                .ok_or_else(|| Error::Others(format!("Unknown {kind} {name:?} in scene file")))
+        };
+
+        let texture_names: Vec<&str> = self.textures.iter().map(|t| t.name.as_str()).collect();
+        let mut materials = Vec::with_capacity(self.materials.len());
+        for desc in &self.materials {
+            let texture = match &desc.texture {
+                Some(name) => Some(find("texture", &texture_names, name)?),
+                None => None,
+            };
+            materials.push(Material {
+                name: desc.name.clone(),
+                color: desc.color,
+                emissive: desc.emissive,
+                texture,
+            });
+        }
+
+        let mesh_names: Vec<&str> = self.meshes.iter().map(|m| m.name.as_str()).collect();
+        let material_names: Vec<&str> = self.materials.iter().map(|m| m.name.as_str()).collect();
+        let mut graph = SceneGraph::default();
+        // Parents are added before their children.
+        let mut stack: Vec<(&NodeDesc, Option<NodeId>)> =
+            self.nodes.iter().rev().map(|desc| (desc, None)).collect();
+        while let Some((desc, parent)) = stack.pop() {
+            let mut node = Node::new(&desc.name)
+                .with_transform(Transform::from(&desc.transform))
+                .with_spin(desc.spin);
+            node.mesh = match (&desc.mesh, &desc.material) {
+                (Some(mesh), Some(material)) => Some(MeshAttachment {
+                    mesh: find("mesh", &mesh_names, mesh)?,
+                    material: find("material", &material_names, material)?,
+                }),
+                (None, None) => None,
+                _ => {
+                    return Err(Error::Others(format!(
+                        "Node {:?} needs both mesh and material",
+                        desc.name
+                    )))
+                }
+            };
+            node.light = desc.light;
+            node.camera = desc.camera;
+
+            let id = graph.add(node, parent);
+            stack.extend(desc.children.iter().rev().map(|child| (child, Some(id))));
+        }
+
+        Ok(Scene {
+            graph,
+            meshes: self.meshes,
+            textures: self.textures,
+            materials,
+            camera: self.camera,
+            base_dir,
+        })
+    }
+}
@@ -0,0 +1,351 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Hierarchy of nodes, each with a transform relative to its parent.
+//!
+//! World matrices are cached in nodes. Changing a local transform only
+//! marks the node dirty, and `SceneGraph::update_world_transforms()`
+//! recomputes world matrices of dirty nodes and all of their descendants.
+
+#![allow(dead_code)]
+
+use cgmath::{
+    InnerSpace, Matrix4, One, Point3, Quaternion, Rad, Rotation3, Transform as _, Vector3,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::transforms::Transform;
+use crate::Error;
+
+/// Index of node in `SceneGraph`, only valid for the graph which created it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// Mesh drawn at the node, as indices into meshes and materials of `Scene`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MeshAttachment {
+    pub mesh: usize,
+    pub material: usize,
+}
+
+/// Point light, placed at world position of its node.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LightAttachment {
+    pub color: Vector3<f32>,
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+}
+
+impl Default for LightAttachment {
+    fn default() -> Self {
+        Self {
+            color: Vector3::new(1.0, 1.0, 1.0),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+        }
+    }
+}
+
+/// Camera looking along negative Z axis of its node, with Y axis up.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraAttachment {
+    /// Vertical field of view, in degrees.
+    pub fovy: f32,
+}
+
+impl Default for CameraAttachment {
+    fn default() -> Self {
+        Self { fovy: 45.0 }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub name: String,
+    transform: Transform,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+
+    /// Cached product of local transforms from root to this node.
+    world: Matrix4<f32>,
+    /// Local transform or parent is changed since the last update.
+    dirty: bool,
+
+    pub mesh: Option<MeshAttachment>,
+    pub light: Option<LightAttachment>,
+    pub camera: Option<CameraAttachment>,
+    /// Rotation speed around local Y axis, in radians per second.
+    pub spin: f32,
+}
+
+impl Node {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            transform: Transform::default(),
+            parent: None,
+            children: Vec::new(),
+            world: Matrix4::one(),
+            dirty: true,
+            mesh: None,
+            light: None,
+            camera: None,
+            spin: 0.0,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_translation(mut self, translation: Vector3<f32>) -> Self {
+        self.transform.translation = translation;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_scale(mut self, scale: f32) -> Self {
+        self.transform.scale = Vector3::new(scale, scale, scale);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_mesh(mut self, mesh: MeshAttachment) -> Self {
+        self.mesh = Some(mesh);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_light(mut self, light: LightAttachment) -> Self {
+        self.light = Some(light);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_camera(mut self, camera: CameraAttachment) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_spin(mut self, spin: f32) -> Self {
+        self.spin = spin;
+        self
+    }
+
+    #[must_use]
+    pub const fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    #[must_use]
+    pub const fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    #[must_use]
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    /// Valid after `SceneGraph::update_world_transforms()`.
+    #[must_use]
+    pub const fn world_matrix(&self) -> &Matrix4<f32> {
+        &self.world
+    }
+
+    #[must_use]
+    pub fn world_position(&self) -> Point3<f32> {
+        self.world.transform_point(Point3::new(0.0, 0.0, 0.0))
+    }
+
+    /// Direction of a local axis in world space, normalized.
+    #[must_use]
+    pub fn world_direction(&self, local: Vector3<f32>) -> Vector3<f32> {
+        self.world.transform_vector(local).normalize()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SceneGraph {
+    nodes: Vec<Node>,
+    roots: Vec<NodeId>,
+}
+
+impl SceneGraph {
+    /// Add `node` as the last child of `parent`, or as a root node.
+    ///
+    /// # Panics
+    /// Panics if `parent` is not a node of this graph.
+    pub fn add(&mut self, mut node: Node, parent: Option<NodeId>) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        node.parent = parent;
+        node.children.clear();
+        node.dirty = true;
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+        self.nodes.push(node);
+        id
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    #[must_use]
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    #[must_use]
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+
+    /// Attachments and name can be changed, but not the transform,
+    /// use `transform_mut()` for that.
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id.0]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (NodeId(index), node))
+    }
+
+    /// Returns the first node with `name`.
+    #[must_use]
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .position(|node| node.name == name)
+            .map(NodeId)
+    }
+
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        *self.transform_mut(id) = transform;
+    }
+
+    /// Mutable local transform, the node is marked dirty.
+    pub fn transform_mut(&mut self, id: NodeId) -> &mut Transform {
+        let node = &mut self.nodes[id.0];
+        node.dirty = true;
+        &mut node.transform
+    }
+
+    /// Move `id` with its subtree under `parent`, or make it a root node.
+    ///
+    /// Local transform is kept, so the node moves with its new parent.
+    /// Returns error if `parent` is `id` itself or one of its descendants.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), Error> {
+        let mut ancestor = parent;
+        while let Some(current) = ancestor {
+            if current == id {
+                return Err(Error::Others(format!(
+                    "Node {:?} can not be a child of itself",
+                    self.nodes[id.0].name
+                )));
+            }
+            ancestor = self.nodes[current.0].parent;
+        }
+
+        match self.nodes[id.0].parent {
+            Some(old_parent) => self.nodes[old_parent.0]
+                .children
+                .retain(|child| *child != id),
+            None => self.roots.retain(|root| *root != id),
+        }
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+        let node = &mut self.nodes[id.0];
+        node.parent = parent;
+        node.dirty = true;
+        Ok(())
+    }
+
+    /// Rotate spinning nodes by the angle they travel in `dt` seconds.
+    ///
+    /// Rotation is accumulated in local transforms, so that a saved scene
+    /// continues from where it is.
+    pub fn animate(&mut self, dt: f32) {
+        for node in &mut self.nodes {
+            if node.spin != 0.0 {
+                let rotation = Quaternion::from_angle_y(Rad(node.spin * dt));
+                node.transform.rotation = (node.transform.rotation * rotation).normalize();
+                node.dirty = true;
+            }
+        }
+    }
+
+    /// Recompute world matrices of dirty nodes and their descendants.
+    ///
+    /// Returns nodes whose world matrix changed, so that only their
+    /// uniform buffers are uploaded.
+    pub fn update_world_transforms(&mut self) -> Vec<NodeId> {
+        let mut changed = Vec::new();
+        // Depth first, parents are always updated before their children.
+        let mut stack: Vec<(NodeId, Matrix4<f32>, bool)> = self
+            .roots
+            .iter()
+            .rev()
+            .map(|root| (*root, Matrix4::one(), false))
+            .collect();
+        while let Some((id, parent_world, parent_changed)) = stack.pop() {
+            let node = &mut self.nodes[id.0];
+            let dirty = parent_changed || node.dirty;
+            if dirty {
+                node.world = parent_world * node.transform.matrix();
+                node.dirty = false;
+                changed.push(id);
+            }
+            let world = node.world;
+            stack.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|child| (*child, world, dirty)),
+            );
+        }
+        changed
+    }
+
+    /// Nodes with a light attached.
+    pub fn lights(&self) -> impl Iterator<Item = (NodeId, &Node, &LightAttachment)> {
+        self.iter()
+            .filter_map(|(id, node)| node.light.as_ref().map(|light| (id, node, light)))
+    }
+
+    /// Nodes with a camera attached.
+    pub fn cameras(&self) -> impl Iterator<Item = (NodeId, &Node, &CameraAttachment)> {
+        self.iter()
+            .filter_map(|(id, node)| node.camera.as_ref().map(|camera| (id, node, camera)))
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Shader modules of feature permutations, compiled on first use.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::preprocessor::{Preprocessor, ShaderDefs};
+use crate::Error;
+
+/// Shaders built into the binary, by name relative to `res/shaders`.
+///
+/// In dev mode they are replaced by files on disk as they change.
+pub const BUILTIN_SHADERS: &[(&str, &str)] = &[
+    ("camera.wgsl", include_str!("../res/shaders/camera.wgsl")),
+    (
+        "lighting.wgsl",
+        include_str!("../res/shaders/lighting.wgsl"),
+    ),
+    ("scene.wgsl", include_str!("../res/shaders/scene.wgsl")),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShaderKey {
+    pub name: String,
+    pub defs: ShaderDefs,
+}
+
+impl ShaderKey {
+    #[must_use]
+    pub fn new(name: &str, defs: ShaderDefs) -> Self {
+        Self {
+            name: name.to_owned(),
+            defs,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CachedModule {
+    module: wgpu::ShaderModule,
+    /// Files the module is built from, including the shader itself.
+    files: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ShaderCache {
+    preprocessor: Preprocessor,
+    modules: HashMap<ShaderKey, CachedModule>,
+}
+
+impl Default for ShaderCache {
+    fn default() -> Self {
+        let mut preprocessor = Preprocessor::default();
+        for (name, source) in BUILTIN_SHADERS {
+            preprocessor.set_source(name, (*source).to_owned());
+        }
+        Self {
+            preprocessor,
+            modules: HashMap::new(),
+        }
+    }
+}
+
+impl ShaderCache {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Replace source of file `name`.
+    ///
+    /// Modules built from it are dropped, and their keys are returned so
+    /// that pipelines using them can be rebuilt.
+    pub fn set_source(&mut self, name: &str, source: String) -> Vec<ShaderKey> {
+        self.preprocessor.set_source(name, source);
+        let stale: Vec<ShaderKey> = self
+            .modules
+            .iter()
+            .filter(|(_key, cached)| cached.files.iter().any(|file| file == name))
+            .map(|(key, _cached)| key.clone())
+            .collect();
+        for key in &stale {
+            self.modules.remove(key);
+        }
+        stale
+    }
+
+    /// Returns module of `key`, compiling it if it is not cached.
+    ///
+    /// Compiler errors refer to lines of the original files.
+    pub fn module(
+        &mut self,
+        device: &wgpu::Device,
+        key: &ShaderKey,
+    ) -> Result<&wgpu::ShaderModule, Error> {
+        if !self.modules.contains_key(key) {
+            let processed = self.preprocessor.process(&key.name, &key.defs)?;
+            let label = format!("{} ({})", key.name, key.defs);
+            let module = capture_validation_error(device, || {
+                device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(&label),
+                    source: wgpu::ShaderSource::Wgsl(processed.source.as_str().into()),
+                })
+            })
+            .map_err(|err| Error::Others(processed.map_error(&err.to_string())))?;
+            log::info!("Compiled shader {label}");
+            self.modules.insert(
+                key.clone(),
+                CachedModule {
+                    module,
+                    files: processed.files,
+                },
+            );
+        }
+        Ok(&self.modules[key].module)
+    }
+}
+
+/// Run `f`, and return validation error it raises, instead of panicking
+/// in the default uncaptured error handler.
+///
+/// Errors can only be waited for on native platforms, on web they are
+/// still reported by the uncaptured error handler.
+pub fn capture_validation_error<T>(
+    device: &wgpu::Device,
+    f: impl FnOnce() -> T,
+) -> Result<T, wgpu::Error> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            let _ = device;
+            Ok(f())
+        } else {
+            device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let value = f();
+            match pollster::block_on(device.pop_error_scope()) {
+                Some(err) => Err(err),
+                None => Ok(value),
+            }
+        }
+    }
+}
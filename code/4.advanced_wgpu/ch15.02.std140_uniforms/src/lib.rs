@@ -0,0 +1,25 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+mod box_scene;
+mod camera;
+mod error;
+mod frames;
+mod geometry;
+mod init;
+mod light;
+mod light_scene;
+mod scenes;
+#[cfg(test)]
+mod shader_layout;
+mod state;
+mod texture;
+mod transforms;
+mod uniform_buffer;
+mod uniforms;
+mod vertex;
+
+pub use self::error::Error;
+pub use self::init::run;
+pub use self::state::State;
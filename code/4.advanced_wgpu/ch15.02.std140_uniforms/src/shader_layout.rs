@@ -0,0 +1,379 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Check uniform structs in Rust against layout of WGSL shaders, without GPU.
+//!
+//! Shaders are parsed and validated by naga, which computes offsets of
+//! struct members with the same rules as the GPU. Rust structs are encoded
+//! by `encase::ShaderType`, so offset and size of each field in the encoded
+//! bytes are compared, which catches fields in wrong order or of wrong type.
+//! Run with `cargo test`.
+
+use encase::internal::WriteInto;
+use encase::ShaderType;
+use naga::proc::Layouter;
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::uniform_buffer::std140_bytes;
+
+/// Offset and size of a struct field, in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructLayout {
+    pub name: String,
+    pub size: usize,
+    pub fields: Vec<FieldLayout>,
+}
+
+/// Encoded layout of Rust struct `$ty`, all of its fields shall be listed,
+/// in any order. `$ty` shall implement `Default`.
+macro_rules! rust_layout {
+    ($ty:ident { $($field:ident),* $(,)? }) => {{
+        // Fails to compile if a field is missing or misspelled.
+        let _ = |value: &$ty| {
+            let $ty { $($field: _),* } = value;
+        };
+        StructLayout {
+            name: stringify!($ty).to_owned(),
+            size: <$ty as ShaderType>::min_size().get() as usize,
+            fields: vec![$(field_layout(
+                stringify!($field),
+                |value: &mut $ty, byte| value.$field = Probe::probe(byte),
+            )),*],
+        }
+    }};
+}
+
+/// Value of which every byte is the same, to find where a field is encoded.
+trait Probe {
+    fn probe(byte: u8) -> Self;
+}
+
+impl Probe for f32 {
+    fn probe(byte: u8) -> Self {
+        Self::from_bits(u32::probe(byte))
+    }
+}
+
+impl Probe for i32 {
+    fn probe(byte: u8) -> Self {
+        Self::from_ne_bytes([byte; 4])
+    }
+}
+
+impl Probe for u32 {
+    fn probe(byte: u8) -> Self {
+        Self::from_ne_bytes([byte; 4])
+    }
+}
+
+impl<T: Probe> Probe for cgmath::Vector2<T> {
+    fn probe(byte: u8) -> Self {
+        Self::new(T::probe(byte), T::probe(byte))
+    }
+}
+
+impl<T: Probe> Probe for cgmath::Vector3<T> {
+    fn probe(byte: u8) -> Self {
+        Self::new(T::probe(byte), T::probe(byte), T::probe(byte))
+    }
+}
+
+impl<T: Probe> Probe for cgmath::Vector4<T> {
+    fn probe(byte: u8) -> Self {
+        Self::new(
+            T::probe(byte),
+            T::probe(byte),
+            T::probe(byte),
+            T::probe(byte),
+        )
+    }
+}
+
+impl<T: Probe> Probe for cgmath::Matrix4<T> {
+    fn probe(byte: u8) -> Self {
+        let column = cgmath::Vector4::probe;
+        Self::from_cols(column(byte), column(byte), column(byte), column(byte))
+    }
+}
+
+/// Layout of the field written by `set`, found by encoding default value of
+/// `T` twice, with every byte of the field set to different values.
+fn field_layout<T>(name: &str, set: impl Fn(&mut T, u8)) -> FieldLayout
+where
+    T: Default + ShaderType + WriteInto,
+{
+    let encode = |byte| {
+        let mut value = T::default();
+        set(&mut value, byte);
+        std140_bytes(&value)
+    };
+    let (first, second) = (encode(0xa5), encode(0x5a));
+    let changed: Vec<usize> = (0..first.len())
+        .filter(|&index| first[index] != second[index])
+        .collect();
+    let (Some(&offset), Some(&last)) = (changed.first(), changed.last()) else {
+        panic!("{name} is not encoded");
+    };
+    assert_eq!(changed.len(), last - offset + 1, "{name} is not contiguous");
+    FieldLayout {
+        name: name.to_owned(),
+        offset,
+        size: changed.len(),
+    }
+}
+
+/// Uniform struct bound at `group` and `binding` of a shader.
+#[derive(Debug, Clone)]
+pub struct UniformBinding {
+    pub group: u32,
+    pub binding: u32,
+    pub layout: StructLayout,
+}
+
+pub struct Shader {
+    module: naga::Module,
+    layouter: Layouter,
+}
+
+impl Shader {
+    /// Read, parse and validate shader file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source =
+            fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+        Self::parse(&source, &path.display().to_string())
+    }
+
+    /// Parse and validate `source`, `path` is used in error messages.
+    pub fn parse(source: &str, path: &str) -> Result<Self, String> {
+        let module = naga::front::wgsl::parse_str(source)
+            .map_err(|err| err.emit_to_string_with_path(source, path))?;
+        Validator::new(ValidationFlags::all(), Capabilities::all())
+            .validate(&module)
+            .map_err(|err| err.emit_to_string_with_path(source, path))?;
+        let mut layouter = Layouter::default();
+        layouter
+            .update(module.to_ctx())
+            .map_err(|err| format!("{path}: {err}"))?;
+        Ok(Self { module, layouter })
+    }
+
+    /// Structs of `var<uniform>` globals, in declaration order.
+    pub fn uniforms(&self) -> Vec<UniformBinding> {
+        self.module
+            .global_variables
+            .iter()
+            .filter(|(_handle, var)| var.space == naga::AddressSpace::Uniform)
+            .filter_map(|(_handle, var)| {
+                let binding = var.binding.as_ref()?;
+                Some(UniformBinding {
+                    group: binding.group,
+                    binding: binding.binding,
+                    layout: self.struct_layout(var.ty)?,
+                })
+            })
+            .collect()
+    }
+
+    fn struct_layout(&self, ty: naga::Handle<naga::Type>) -> Option<StructLayout> {
+        let ty = &self.module.types[ty];
+        let naga::TypeInner::Struct { members, span } = &ty.inner else {
+            return None;
+        };
+        Some(StructLayout {
+            name: ty.name.clone().unwrap_or_default(),
+            size: *span as usize,
+            fields: members
+                .iter()
+                .map(|member| FieldLayout {
+                    name: member.name.clone().unwrap_or_default(),
+                    offset: member.offset as usize,
+                    size: self.layouter[member.ty].size as usize,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Differences between layout of WGSL struct and Rust struct.
+///
+/// Rust struct may be larger, as shaders may declare only the leading
+/// members they use, like `CameraUniform` in `light.wgsl`.
+pub fn compare_layout(wgsl: &StructLayout, rust: &StructLayout) -> Vec<String> {
+    let mut errors = Vec::new();
+    for member in &wgsl.fields {
+        let Some(field) = rust.fields.iter().find(|field| field.name == member.name) else {
+            errors.push(format!("{}.{} has no Rust field", wgsl.name, member.name));
+            continue;
+        };
+        if field.offset != member.offset {
+            errors.push(format!(
+                "{}.{} is at offset {} in WGSL, but {} in Rust",
+                wgsl.name, member.name, member.offset, field.offset
+            ));
+        }
+        if field.size != member.size {
+            errors.push(format!(
+                "{}.{} is {} bytes in WGSL, but {} in Rust",
+                wgsl.name, member.name, member.size, field.size
+            ));
+        }
+    }
+    if rust.size < wgsl.size {
+        errors.push(format!(
+            "{} is {} bytes in WGSL, but only {} in Rust",
+            wgsl.name, wgsl.size, rust.size
+        ));
+    }
+    errors
+}
+
+/// All `.wgsl` files in `dir`, sorted.
+pub fn shader_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("{}: {err}", dir.display()))
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wgsl"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::box_scene::Material;
+    use crate::camera::CameraUniform;
+    use crate::light::Light;
+    use crate::light_scene::LightUniform;
+
+    const SHADER_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/shaders");
+
+    /// Rust types of uniform structs, with the same name as in WGSL.
+    fn rust_layouts() -> Vec<StructLayout> {
+        vec![
+            rust_layout!(CameraUniform {
+                view_proj,
+                view_pos
+            }),
+            rust_layout!(LightUniform { model }),
+            rust_layout!(Material {
+                diffuse,
+                specular,
+                shininess,
+            }),
+            rust_layout!(Light {
+                position,
+                direction,
+                cutoff,
+                ambient,
+                diffuse,
+                specular,
+                constant,
+                linear,
+                quadratic,
+            }),
+        ]
+    }
+
+    #[test]
+    fn shaders_are_valid() {
+        let files = shader_files(Path::new(SHADER_DIR));
+        assert!(!files.is_empty(), "No shader in {SHADER_DIR}");
+        let errors: Vec<String> = files
+            .iter()
+            .filter_map(|path| Shader::load(path).err())
+            .collect();
+        assert!(errors.is_empty(), "{}", errors.join("\n"));
+    }
+
+    #[test]
+    fn uniform_layouts_match_rust() {
+        let rust_layouts = rust_layouts();
+        let mut used = vec![false; rust_layouts.len()];
+        let mut errors = Vec::new();
+        for path in shader_files(Path::new(SHADER_DIR)) {
+            // Invalid shaders are reported by `shaders_are_valid()`.
+            let Ok(shader) = Shader::load(&path) else {
+                continue;
+            };
+            for uniform in shader.uniforms() {
+                let wgsl = &uniform.layout;
+                let Some(index) = rust_layouts.iter().position(|rust| rust.name == wgsl.name)
+                else {
+                    errors.push(format!(
+                        "{}: no Rust type for {} at group {} binding {}",
+                        path.display(),
+                        wgsl.name,
+                        uniform.group,
+                        uniform.binding
+                    ));
+                    continue;
+                };
+                used[index] = true;
+                errors.extend(
+                    compare_layout(wgsl, &rust_layouts[index])
+                        .into_iter()
+                        .map(|err| format!("{}: {err}", path.display())),
+                );
+            }
+        }
+        for (rust, used) in rust_layouts.iter().zip(used) {
+            if !used {
+                errors.push(format!("{} is not used by any shader", rust.name));
+            }
+        }
+        assert!(errors.is_empty(), "{}", errors.join("\n"));
+    }
+
+    #[test]
+    fn wrong_field_order_is_detected() {
+        #[derive(ShaderType)]
+        struct Spot {
+            position: cgmath::Vector3<f32>,
+            cutoff: f32,
+            direction: cgmath::Vector3<f32>,
+        }
+
+        impl Default for Spot {
+            fn default() -> Self {
+                Self {
+                    position: cgmath::Vector3::unit_y(),
+                    cutoff: 0.5,
+                    direction: -cgmath::Vector3::unit_y(),
+                }
+            }
+        }
+
+        let shader = Shader::parse(
+            "struct Spot { position: vec3<f32>, direction: vec3<f32>, cutoff: f32 }
+            @group(0) @binding(0) var<uniform> spot: Spot;",
+            "spot.wgsl",
+        )
+        .unwrap();
+        let uniforms = shader.uniforms();
+        assert_eq!(uniforms.len(), 1);
+        let errors = compare_layout(
+            &uniforms[0].layout,
+            // Offsets are found by field name, not by order in the list.
+            &rust_layout!(Spot {
+                position,
+                direction,
+                cutoff,
+            }),
+        );
+        assert_eq!(
+            errors,
+            ["Spot.cutoff is at offset 28 in WGSL, but 12 in Rust"]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Bind group layouts generated by shader reflection, and a builder which
+//! checks resources against them before wgpu does.
+
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+
+use crate::reflection::{BindingInfo, ShaderReflection};
+use crate::Error;
+
+/// Bind group layout, with the names and types of its bindings.
+#[derive(Debug)]
+pub struct BindGroupLayout {
+    layout: wgpu::BindGroupLayout,
+    label: String,
+    /// Sorted by binding.
+    bindings: Vec<BindingInfo>,
+}
+
+impl BindGroupLayout {
+    /// Layout of `group`, used by all of `shaders`.
+    ///
+    /// A group shared by pipelines of several shaders, like the camera,
+    /// is visible in stages of all of them. Bindings declared in more than
+    /// one shader shall have the same type, uniform buffers are as large
+    /// as the largest struct.
+    pub fn from_shaders(
+        device: &wgpu::Device,
+        shaders: &[&ShaderReflection],
+        group: u32,
+        label: &str,
+    ) -> Result<Self, Error> {
+        let mut bindings: BTreeMap<u32, (BindingInfo, &str)> = BTreeMap::new();
+        for shader in shaders {
+            for info in shader.group_bindings(group)? {
+                let Some((merged, first_shader)) = bindings.get_mut(&info.binding) else {
+                    bindings.insert(info.binding, (info, shader.name()));
+                    continue;
+                };
+                merged.visibility |= info.visibility;
+                merged.ty = merge_types(merged.ty, info.ty).ok_or_else(|| {
+                    Error::Others(format!(
+                        "{label}: binding {} is {} in {first_shader}, but {} in {}",
+                        info.binding,
+                        kind_name(&merged.ty),
+                        kind_name(&info.ty),
+                        shader.name()
+                    ))
+                })?;
+            }
+        }
+        if bindings.is_empty() {
+            return Err(Error::Others(format!(
+                "{label}: group {group} is not used by any shader"
+            )));
+        }
+
+        let bindings: Vec<BindingInfo> = bindings.into_values().map(|(info, _)| info).collect();
+        let entries: Vec<wgpu::BindGroupLayoutEntry> =
+            bindings.iter().map(BindingInfo::layout_entry).collect();
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &entries,
+            label: Some(label),
+        });
+        Ok(Self {
+            layout,
+            label: label.to_owned(),
+            bindings,
+        })
+    }
+
+    #[must_use]
+    pub const fn raw(&self) -> &wgpu::BindGroupLayout {
+        &self.layout
+    }
+
+    #[must_use]
+    pub fn bindings(&self) -> &[BindingInfo] {
+        &self.bindings
+    }
+
+    /// Start a bind group of this layout.
+    #[must_use]
+    pub const fn bind_group(&self) -> BindGroupBuilder<'_> {
+        BindGroupBuilder {
+            layout: self,
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// Same binding declared by two shaders, None if they do not match.
+fn merge_types(a: wgpu::BindingType, b: wgpu::BindingType) -> Option<wgpu::BindingType> {
+    match (a, b) {
+        (
+            wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset,
+                min_binding_size: size_a,
+            },
+            wgpu::BindingType::Buffer {
+                ty: ty_b,
+                has_dynamic_offset: dynamic_b,
+                min_binding_size: size_b,
+            },
+        ) if ty == ty_b && has_dynamic_offset == dynamic_b => Some(wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset,
+            min_binding_size: size_a.max(size_b),
+        }),
+        (a, b) if a == b => Some(a),
+        _ => None,
+    }
+}
+
+fn kind_name(ty: &wgpu::BindingType) -> &'static str {
+    match ty {
+        wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            ..
+        } => "a uniform buffer",
+        wgpu::BindingType::Buffer { .. } => "a storage buffer",
+        wgpu::BindingType::Sampler(_) => "a sampler",
+        wgpu::BindingType::Texture { .. } => "a texture",
+        wgpu::BindingType::StorageTexture { .. } => "a storage texture",
+    }
+}
+
+/// Resources of a bind group, checked against binding types of the layout
+/// when the group is built.
+pub struct BindGroupBuilder<'a> {
+    layout: &'a BindGroupLayout,
+    entries: Vec<(u32, wgpu::BindingResource<'a>)>,
+}
+
+impl<'a> BindGroupBuilder<'a> {
+    #[must_use]
+    pub fn buffer(mut self, binding: u32, buffer: &'a wgpu::Buffer) -> Self {
+        self.entries.push((binding, buffer.as_entire_binding()));
+        self
+    }
+
+    #[must_use]
+    pub fn texture(mut self, binding: u32, view: &'a wgpu::TextureView) -> Self {
+        self.entries
+            .push((binding, wgpu::BindingResource::TextureView(view)));
+        self
+    }
+
+    #[must_use]
+    pub fn sampler(mut self, binding: u32, sampler: &'a wgpu::Sampler) -> Self {
+        self.entries
+            .push((binding, wgpu::BindingResource::Sampler(sampler)));
+        self
+    }
+
+    /// Create the bind group, if every binding of the layout has a resource
+    /// of the right kind, and buffers are large enough.
+    pub fn build(self, device: &wgpu::Device, label: &str) -> Result<wgpu::BindGroup, Error> {
+        let error = |msg: String| Error::Others(format!("{label}: {msg}"));
+        let layout = self.layout;
+
+        for (index, (binding, resource)) in self.entries.iter().enumerate() {
+            if self.entries[..index]
+                .iter()
+                .any(|(other, _)| other == binding)
+            {
+                return Err(error(format!("binding {binding} is set twice")));
+            }
+            let info = layout
+                .bindings
+                .iter()
+                .find(|info| info.binding == *binding)
+                .ok_or_else(|| {
+                    error(format!(
+                        "binding {binding} is not in layout {}",
+                        layout.label
+                    ))
+                })?;
+            check_resource(info, resource)
+                .map_err(|msg| error(format!("binding {binding} ({}) {msg}", info.name)))?;
+        }
+        for info in &layout.bindings {
+            if !self
+                .entries
+                .iter()
+                .any(|(binding, _)| *binding == info.binding)
+            {
+                return Err(error(format!(
+                    "binding {} ({}) is not set, it expects {}",
+                    info.binding,
+                    info.name,
+                    kind_name(&info.ty)
+                )));
+            }
+        }
+
+        let entries: Vec<wgpu::BindGroupEntry> = self
+            .entries
+            .into_iter()
+            .map(|(binding, resource)| wgpu::BindGroupEntry { binding, resource })
+            .collect();
+        Ok(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &layout.layout,
+            entries: &entries,
+            label: Some(label),
+        }))
+    }
+}
+
+fn check_resource(info: &BindingInfo, resource: &wgpu::BindingResource) -> Result<(), String> {
+    let expected = kind_name(&info.ty);
+    match (&info.ty, resource) {
+        (
+            wgpu::BindingType::Buffer {
+                ty,
+                min_binding_size,
+                ..
+            },
+            wgpu::BindingResource::Buffer(binding),
+        ) => {
+            let usage = match ty {
+                wgpu::BufferBindingType::Uniform => wgpu::BufferUsages::UNIFORM,
+                wgpu::BufferBindingType::Storage { .. } => wgpu::BufferUsages::STORAGE,
+            };
+            if !binding.buffer.usage().contains(usage) {
+                return Err(format!(
+                    "expects {expected}, but buffer has no {usage:?} usage"
+                ));
+            }
+            let size = binding.buffer.size();
+            match min_binding_size {
+                Some(min_size) if size < min_size.get() => Err(format!(
+                    "expects at least {min_size} bytes, but buffer has {size}"
+                )),
+                _ => Ok(()),
+            }
+        }
+        (wgpu::BindingType::Sampler(_), wgpu::BindingResource::Sampler(_))
+        | (
+            wgpu::BindingType::Texture { .. } | wgpu::BindingType::StorageTexture { .. },
+            wgpu::BindingResource::TextureView(_),
+        ) => Ok(()),
+        (_, resource) => Err(format!(
+            "expects {expected}, but got {}",
+            resource_name(resource)
+        )),
+    }
+}
+
+fn resource_name(resource: &wgpu::BindingResource) -> &'static str {
+    match resource {
+        wgpu::BindingResource::Buffer(_) | wgpu::BindingResource::BufferArray(_) => "a buffer",
+        wgpu::BindingResource::Sampler(_) | wgpu::BindingResource::SamplerArray(_) => "a sampler",
+        _ => "a texture",
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Matrix4, One, Vector3};
+use encase::{ShaderSize, ShaderType};
+use wgpu::util::DeviceExt;
+
+use crate::bind_group::BindGroupLayout;
+use crate::error::Error;
+use crate::reflection::ShaderReflection;
+use crate::scenes::create_vertex;
+use crate::texture::Texture;
+use crate::transforms;
+use crate::uniform_buffer::std140_bytes;
+use crate::vertex::Vertex;
+
+#[derive(Debug, Clone, PartialEq, ShaderType)]
+pub struct LightUniform {
+    pub model: Matrix4<f32>,
+}
+
+// Size of `LightUniform` in `light.wgsl`, checked at compile time.
+const _: () = assert!(LightUniform::SHADER_SIZE.get() == 64);
+
+impl Default for LightUniform {
+    fn default() -> Self {
+        Self {
+            model: Matrix4::one(),
+        }
+    }
+}
+
+impl LightUniform {
+    pub fn reset(&mut self) {
+        self.model = Matrix4::one();
+    }
+
+    pub fn set_position(&mut self, pos: Vector3<f32>) {
+        transforms::translate(&mut self.model, pos);
+    }
+
+    pub fn scale(&mut self, scale: Vector3<f32>) {
+        transforms::scale(&mut self.model, scale);
+    }
+}
+
+#[derive(Debug)]
+pub struct LightScene {
+    pub render_pipeline: wgpu::RenderPipeline,
+
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+
+    pub uniform: LightUniform,
+    pub uniform_buffer: wgpu::Buffer,
+    pub uniform_bind_group: wgpu::BindGroup,
+}
+
+impl LightScene {
+    pub const SHADER: &'static str = include_str!("../res/shaders/light.wgsl");
+
+    pub fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        shader: &ShaderReflection,
+        camera_bind_group_layout: &BindGroupLayout,
+    ) -> Result<Self, Error> {
+        let uniform_bind_group_layout =
+            BindGroupLayout::from_shaders(device, &[shader], 1, "Light Buffer Bind Group Layout")?;
+        let (uniform, uniform_buffer, uniform_bind_group) =
+            Self::create_uniform(device, &uniform_bind_group_layout)?;
+
+        let bind_group_layouts = [
+            camera_bind_group_layout.raw(),
+            uniform_bind_group_layout.raw(),
+        ];
+        let render_pipeline = Self::create_render_pipeline(device, config, &bind_group_layouts);
+
+        let (vertex_buffer, index_buffer, num_indices) = create_vertex(device);
+
+        Ok(Self {
+            render_pipeline,
+
+            vertex_buffer,
+            index_buffer,
+            num_indices,
+
+            uniform,
+            uniform_buffer,
+            uniform_bind_group,
+        })
+    }
+
+    pub fn create_uniform(
+        device: &wgpu::Device,
+        layout: &BindGroupLayout,
+    ) -> Result<(LightUniform, wgpu::Buffer, wgpu::BindGroup), Error> {
+        let mut uniform = LightUniform::default();
+        uniform.set_position((-1.5, 1.5, 2.0).into());
+        uniform.scale((0.2, 0.2, 0.2).into());
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Light Uniform Buffer"),
+            contents: &std140_bytes(&uniform),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = layout
+            .bind_group()
+            .buffer(0, &uniform_buffer)
+            .build(device, "Light Bind Group")?;
+
+        Ok((uniform, uniform_buffer, bind_group))
+    }
+
+    fn create_render_pipeline(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> wgpu::RenderPipeline {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Light Shader"),
+            source: wgpu::ShaderSource::Wgsl(Self::SHADER.into()),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Light Render Pipeline Layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Light Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        render_pipeline
+    }
+}
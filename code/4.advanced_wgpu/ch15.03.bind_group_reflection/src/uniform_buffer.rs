@@ -0,0 +1,49 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Encode uniforms with the memory layout of WGSL, instead of padding
+//! Rust structs by hand.
+//!
+//! Uniform structs derive `encase::ShaderType`, which computes offset of
+//! each field at compile time, with vec3 alignment, array strides and
+//! nested structs of std140 (uniform) or std430 (storage) rules.
+
+#![allow(dead_code)]
+
+use encase::internal::WriteInto;
+use encase::{ShaderSize, ShaderType, StorageBuffer, UniformBuffer};
+
+/// Bytes of `value` in uniform address space, which is std140 layout.
+///
+/// # Panics
+///
+/// Panics if `T` does not satisfy layout constraints of uniform buffers,
+/// like arrays of scalars, which shall be arrays of vec4 instead.
+#[must_use]
+pub fn std140_bytes<T: ShaderType + WriteInto>(value: &T) -> Vec<u8> {
+    let mut buffer = UniformBuffer::new(Vec::new());
+    // Writing to a Vec only fails if it cannot grow.
+    buffer
+        .write(value)
+        .expect("Failed to encode uniform buffer");
+    buffer.into_inner()
+}
+
+/// Bytes of `value` in storage address space, which is std430 layout.
+#[must_use]
+pub fn std430_bytes<T: ShaderType + WriteInto>(value: &T) -> Vec<u8> {
+    let mut buffer = StorageBuffer::new(Vec::new());
+    buffer
+        .write(value)
+        .expect("Failed to encode storage buffer");
+    buffer.into_inner()
+}
+
+/// Size of `T` in shaders, used as `min_binding_size` of bind group
+/// layouts, so that wgpu checks it against the shader struct when the
+/// pipeline is created.
+#[must_use]
+pub const fn uniform_size<T: ShaderSize>() -> wgpu::BufferSize {
+    T::SHADER_SIZE
+}
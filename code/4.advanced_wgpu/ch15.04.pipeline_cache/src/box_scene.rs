@@ -0,0 +1,178 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::Vector3;
+use encase::{ShaderSize, ShaderType};
+use wgpu::util::DeviceExt;
+
+use crate::bind_group::BindGroupLayout;
+use crate::error::Error;
+use crate::light::Light;
+use crate::pipeline::{RenderPipelineBuilder, RenderTarget};
+use crate::reflection::ShaderReflection;
+use crate::scenes::create_vertex;
+use crate::texture::Texture;
+use crate::uniform_buffer::std140_bytes;
+use crate::vertex::Vertex;
+
+#[derive(Debug, Clone, PartialEq, ShaderType)]
+pub struct Material {
+    pub diffuse: Vector3<f32>,
+    pub specular: Vector3<f32>,
+    pub shininess: i32,
+}
+
+// Size of `Material` in `box.wgsl`, checked at compile time.
+const _: () = assert!(Material::SHADER_SIZE.get() == 32);
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            diffuse: Vector3::new(1.0, 0.5, 0.31),
+            specular: Vector3::new(0.5, 0.5, 0.5),
+            shininess: 32,
+        }
+    }
+}
+
+/// Blend with the blend constant of render pass as opacity, so that the box
+/// becomes transparent without changing its shader.
+pub const CONSTANT_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusConstant,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent::OVER,
+};
+
+#[derive(Debug)]
+pub struct BoxScene {
+    pub shader: wgpu::ShaderModule,
+    pub uniform_bind_group_layout: BindGroupLayout,
+    pub texture_bind_group_layout: BindGroupLayout,
+
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+    pub num_indices: u32,
+
+    pub material: Material,
+    pub light: Light,
+    pub material_buffer: wgpu::Buffer,
+    pub light_buffer: wgpu::Buffer,
+    pub uniform_bind_group: wgpu::BindGroup,
+
+    pub texture_bind_group: wgpu::BindGroup,
+}
+
+impl BoxScene {
+    pub const SHADER: &'static str = include_str!("../res/shaders/box.wgsl");
+
+    /// Layouts of group 1 and 2 are generated from `shader`, camera at
+    /// group 0 is shared with other pipelines.
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        shader: &ShaderReflection,
+    ) -> Result<Self, Error> {
+        let uniform_bind_group_layout =
+            BindGroupLayout::from_shaders(device, &[shader], 1, "Box Buffer Bind Group Layout")?;
+        let (material, light, material_buffer, light_buffer, uniform_bind_group) =
+            Self::create_uniform(device, &uniform_bind_group_layout)?;
+
+        let texture_bind_group_layout =
+            BindGroupLayout::from_shaders(device, &[shader], 2, "Box Texture Bind Group Layout")?;
+        let texture_bind_group = Self::create_texture(device, queue, &texture_bind_group_layout)?;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Box Shader"),
+            source: wgpu::ShaderSource::Wgsl(Self::SHADER.into()),
+        });
+
+        let (vertex_buffer, index_buffer, num_indices) = create_vertex(device);
+
+        Ok(Self {
+            shader,
+            uniform_bind_group_layout,
+            texture_bind_group_layout,
+
+            vertex_buffer,
+            index_buffer,
+            num_indices,
+
+            material,
+            light,
+            material_buffer,
+            light_buffer,
+            uniform_bind_group,
+
+            texture_bind_group,
+        })
+    }
+
+    /// Opaque pipeline of box, variants are made by changing the builder.
+    #[must_use]
+    pub fn pipeline<'a>(
+        &'a self,
+        camera_bind_group_layout: &'a BindGroupLayout,
+        target: RenderTarget,
+    ) -> RenderPipelineBuilder<'a> {
+        RenderPipelineBuilder::new("Box Render Pipeline", &self.shader, target)
+            .bind_group_layouts(&[
+                camera_bind_group_layout.raw(),
+                self.uniform_bind_group_layout.raw(),
+                self.texture_bind_group_layout.raw(),
+            ])
+            .vertex_buffer(Vertex::desc())
+    }
+
+    pub fn create_uniform(
+        device: &wgpu::Device,
+        layout: &BindGroupLayout,
+    ) -> Result<(Material, Light, wgpu::Buffer, wgpu::Buffer, wgpu::BindGroup), Error> {
+        let material = Material::default();
+        let light = Light::default();
+
+        let material_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Box Material Buffer"),
+            contents: &std140_bytes(&material),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let light_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Box Light Buffer"),
+            contents: &std140_bytes(&light),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = layout
+            .bind_group()
+            .buffer(0, &material_buffer)
+            .buffer(1, &light_buffer)
+            .build(device, "Box Bind Group")?;
+
+        Ok((material, light, material_buffer, light_buffer, bind_group))
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &BindGroupLayout,
+    ) -> Result<wgpu::BindGroup, Error> {
+        let diffuse_bytes = include_bytes!("../res/textures/container2.png");
+        let diffuse_texture =
+            Texture::from_bytes(device, queue, diffuse_bytes, Some("diffuse texture"))?;
+
+        let specular_bytes = include_bytes!("../res/textures/container2_specular.png");
+        let specular_texture =
+            Texture::from_bytes(device, queue, specular_bytes, Some("specular texture"))?;
+
+        layout
+            .bind_group()
+            .texture(0, &diffuse_texture.view)
+            .sampler(1, &diffuse_texture.sampler)
+            .texture(2, &specular_texture.view)
+            .sampler(3, &specular_texture.sampler)
+            .build(device, "texture_bind_group")
+    }
+}
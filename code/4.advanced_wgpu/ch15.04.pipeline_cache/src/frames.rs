@@ -0,0 +1,223 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+#![allow(dead_code)]
+
+use cgmath::Vector3;
+use instant::Instant;
+
+#[derive(Debug, Clone)]
+pub struct UserWindow {
+    name: String,
+    age: u32,
+}
+
+impl Default for UserWindow {
+    fn default() -> Self {
+        Self {
+            name: "Author".to_owned(),
+            age: 42,
+        }
+    }
+}
+
+impl UserWindow {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub const fn age(&self) -> u32 {
+        self.age
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("User")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let name_label = ui.label("Your name: ");
+                    ui.text_edit_singleline(&mut self.name)
+                        .labelled_by(name_label.id);
+                });
+                ui.add(egui::Slider::new(&mut self.age, 0..=120).text("age"));
+                if ui.button("Click each year").clicked() {
+                    self.age += 1;
+                }
+                ui.label(format!("Hello '{}', age {}", self.name, self.age));
+            });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ColorWindow {
+    color: Vector3<f32>,
+}
+
+impl Default for ColorWindow {
+    fn default() -> Self {
+        Self {
+            color: Vector3::new(0.3, 0.4, 0.5),
+        }
+    }
+}
+
+impl ColorWindow {
+    pub fn set_color(&mut self, color: Vector3<f32>) {
+        self.color = color;
+    }
+
+    pub fn color(&self) -> &Vector3<f32> {
+        &self.color
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Color")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.heading("Select vertex color:");
+                ui.color_edit_button_rgb(self.color.as_mut());
+            });
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FpsWindow {
+    fps: u32,
+    frames: u32,
+    timer: Instant,
+}
+
+impl Default for FpsWindow {
+    fn default() -> Self {
+        Self {
+            fps: 0,
+            frames: 0,
+            timer: Instant::now(),
+        }
+    }
+}
+
+impl FpsWindow {
+    pub fn update(&mut self) {
+        let dt = self.timer.elapsed().as_secs_f64();
+        let fps = (f64::from(self.frames) / dt).round() as u32;
+        //log::info!("fps: {fps}");
+        if dt > 1.0 {
+            self.frames = 0;
+            self.timer = Instant::now();
+            self.fps = fps;
+        }
+        self.frames += 1;
+    }
+
+    #[must_use]
+    pub const fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("FPS")
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                ui.heading(format!("FPS: {}", self.fps));
+            });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BoxUniformWindow {
+    pub light_color: Vector3<f32>,
+    pub light_pos: Vector3<f32>,
+}
+
+impl Default for BoxUniformWindow {
+    fn default() -> Self {
+        Self {
+            light_color: Vector3::new(1.0, 1.0, 1.0),
+            light_pos: Vector3::new(-1.5, 1.5, 2.0),
+        }
+    }
+}
+
+impl BoxUniformWindow {
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Box Uniform")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Light Color:");
+                    ui.color_edit_button_rgb(self.light_color.as_mut());
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Light Pos:");
+                    ui.add(egui::Slider::new(&mut self.light_pos.x, -3.0..=3.0));
+                    ui.add(egui::Slider::new(&mut self.light_pos.y, -3.0..=3.0));
+                    ui.add(egui::Slider::new(&mut self.light_pos.z, -3.0..=3.0));
+                });
+            });
+    }
+}
+
+/// Options which select a variant of the box pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineSettings {
+    pub wireframe: bool,
+    pub transparent: bool,
+    pub msaa: bool,
+}
+
+impl Default for PipelineSettings {
+    fn default() -> Self {
+        Self {
+            wireframe: false,
+            transparent: false,
+            msaa: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PipelineWindow {
+    pub settings: PipelineSettings,
+    pub opacity: f32,
+    wireframe_supported: bool,
+    pipeline_count: usize,
+}
+
+impl PipelineWindow {
+    /// Wireframe option is disabled if `POLYGON_MODE_LINE` is not supported.
+    #[must_use]
+    pub fn new(wireframe_supported: bool) -> Self {
+        Self {
+            settings: PipelineSettings::default(),
+            opacity: 0.5,
+            wireframe_supported,
+            pipeline_count: 0,
+        }
+    }
+
+    pub fn set_pipeline_count(&mut self, count: usize) {
+        self.pipeline_count = count;
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Pipeline")
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                ui.add_enabled(
+                    self.wireframe_supported,
+                    egui::Checkbox::new(&mut self.settings.wireframe, "Wireframe"),
+                );
+                ui.checkbox(&mut self.settings.transparent, "Transparent");
+                ui.add_enabled(
+                    self.settings.transparent,
+                    egui::Slider::new(&mut self.opacity, 0.0..=1.0).text("opacity"),
+                );
+                ui.checkbox(&mut self.settings.msaa, "MSAA 4x");
+                ui.label(format!("Cached pipelines: {}", self.pipeline_count));
+            });
+    }
+}
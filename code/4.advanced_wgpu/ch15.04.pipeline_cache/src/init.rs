@@ -0,0 +1,180 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+cfg_if::cfg_if! {
+    if #[cfg(target_arch = "wasm32")] {
+        use wasm_bindgen::prelude::wasm_bindgen;
+        use winit::event_loop::EventLoopProxy;
+        use winit::platform::web::WindowExtWebSys;
+    } else {
+        use std::path::PathBuf;
+    }
+}
+use std::sync::Arc;
+use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::window::{Window, WindowId};
+
+use crate::{Error, State};
+
+/// Window and state are created once the event loop is resumed.
+struct App {
+    /// State is created asynchronously on the web, and sent back to
+    /// event loop as a user event.
+    #[cfg(target_arch = "wasm32")]
+    proxy: Option<EventLoopProxy<State>>,
+    /// Directory of pipeline cache, which is kept in memory if not set.
+    #[cfg(not(target_arch = "wasm32"))]
+    cache_dir: Option<PathBuf>,
+    state: Option<State>,
+}
+
+impl App {
+    fn create_window(event_loop: &ActiveEventLoop) -> Result<Arc<Window>, Error> {
+        log::info!("Creating a new with with size 800x600");
+        let attributes = Window::default_attributes()
+            .with_title("Pipeline Cache")
+            .with_inner_size(PhysicalSize::new(800, 600));
+        let window = Arc::new(event_loop.create_window(attributes)?);
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let element = web_sys::window()
+                .and_then(|win| win.document())
+                .and_then(|doc| doc.get_element_by_id("wasm-container"))
+                .ok_or_else(|| {
+                    Error::Web("failed to find dom element '#wasm-container'".to_owned())
+                })?;
+            let canvas = window
+                .canvas()
+                .ok_or_else(|| Error::Web("failed to get canvas of window".to_owned()))?;
+            element.append_child(&web_sys::Element::from(canvas))?;
+        }
+
+        Ok(window)
+    }
+}
+
+impl ApplicationHandler<State> for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.state.is_some() {
+            return;
+        }
+        // Proxy is taken while state is being created.
+        #[cfg(target_arch = "wasm32")]
+        let Some(proxy) = self.proxy.take() else {
+            return;
+        };
+        let window = match Self::create_window(event_loop) {
+            Ok(window) => window,
+            Err(err) => {
+                log::error!("Failed to create window: {err:?}");
+                event_loop.exit();
+                return;
+            }
+        };
+
+        cfg_if::cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                wasm_bindgen_futures::spawn_local(async move {
+                    match State::new(window, None).await {
+                        Ok(state) => {
+                            if proxy.send_event(state).is_err() {
+                                log::error!("Event loop is closed");
+                            }
+                        }
+                        Err(err) => log::error!("Failed to create state: {err:?}"),
+                    }
+                });
+            } else {
+                match pollster::block_on(State::new(window, self.cache_dir.as_deref())) {
+                    Ok(state) => self.state = Some(state),
+                    Err(err) => {
+                        log::error!("Failed to create state: {err:?}");
+                        event_loop.exit();
+                    }
+                }
+            }
+        }
+    }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, state: State) {
+        state.window().request_redraw();
+        self.state = Some(state);
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = self.state.as_mut() else {
+            return;
+        };
+        if window_id != state.window().id() {
+            return;
+        }
+
+        state.platform_mut().handle_event(&event);
+        if state.input(&event) {
+            return;
+        }
+        match event {
+            WindowEvent::Resized(physical_size) => state.resize(physical_size),
+            WindowEvent::CloseRequested => {
+                state.save_pipeline_cache();
+                event_loop.exit();
+            }
+            WindowEvent::RedrawRequested => {
+                state.update();
+                match state.render() {
+                    Ok(()) => {}
+                    Err(wgpu::SurfaceError::Lost) => state.resize(state.size()),
+                    Err(wgpu::SurfaceError::OutOfMemory) => {
+                        log::error!("System out of memory!");
+                        event_loop.exit();
+                    }
+                    Err(err) => log::error!("{err:?}"),
+                }
+                state.window().request_redraw();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
+pub async fn run() -> Result<(), Error> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            wasm_logger::init(wasm_logger::Config::default());
+        } else {
+            env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+        }
+    }
+
+    // Command line arguments: [--pipeline-cache <dir>]
+    // Pipeline cache is saved to disk, so it is native only.
+    #[cfg(not(target_arch = "wasm32"))]
+    let cache_dir = {
+        let mut args = std::env::args().skip(1);
+        args.position(|arg| arg == "--pipeline-cache")
+            .and_then(|_| args.next())
+            .map(PathBuf::from)
+    };
+
+    let event_loop = EventLoop::<State>::with_user_event().build()?;
+    let mut app = App {
+        #[cfg(target_arch = "wasm32")]
+        proxy: Some(event_loop.create_proxy()),
+        #[cfg(not(target_arch = "wasm32"))]
+        cache_dir,
+        state: None,
+    };
+    event_loop.run_app(&mut app)?;
+    Ok(())
+}
@@ -0,0 +1,427 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Render pipelines described by a builder, with the defaults used by
+//! scenes of this book, and created once per descriptor by a cache.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::ops::Index;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// Attachments which a pipeline renders to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderTarget {
+    pub color_format: wgpu::TextureFormat,
+    pub depth_format: Option<wgpu::TextureFormat>,
+    pub sample_count: u32,
+}
+
+impl RenderTarget {
+    #[must_use]
+    pub const fn new(
+        config: &wgpu::SurfaceConfiguration,
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) -> Self {
+        Self {
+            color_format: config.format,
+            depth_format,
+            sample_count,
+        }
+    }
+}
+
+/// Owned copy of `wgpu::VertexBufferLayout`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct VertexLayoutKey {
+    array_stride: wgpu::BufferAddress,
+    step_mode: wgpu::VertexStepMode,
+    attributes: Vec<wgpu::VertexAttribute>,
+}
+
+/// Everything in a pipeline descriptor.
+///
+/// Shader module and bind group layouts are compared by identity, the key
+/// holds them so that they are not dropped while the pipeline is cached.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    shader: wgpu::ShaderModule,
+    bind_group_layouts: Vec<wgpu::BindGroupLayout>,
+    vertex_entry: String,
+    fragment_entry: Option<String>,
+    vertex_buffers: Vec<VertexLayoutKey>,
+    primitive: wgpu::PrimitiveState,
+    blend: Option<wgpu::BlendState>,
+    depth_write: bool,
+    depth_compare: wgpu::CompareFunction,
+    target: RenderTarget,
+}
+
+/// Render pipeline descriptor.
+///
+/// By default triangles are filled without culling, `vs_main` and `fs_main`
+/// are the entry points, color replaces the target, depth is written and
+/// tested with `Less`. Formats and sample count are taken from the target.
+#[derive(Debug, Clone)]
+pub struct RenderPipelineBuilder<'a> {
+    label: &'a str,
+    shader: &'a wgpu::ShaderModule,
+    bind_group_layouts: Vec<&'a wgpu::BindGroupLayout>,
+    vertex_entry: &'a str,
+    fragment_entry: Option<&'a str>,
+    vertex_buffers: Vec<wgpu::VertexBufferLayout<'a>>,
+    primitive: wgpu::PrimitiveState,
+    blend: Option<wgpu::BlendState>,
+    depth_write: bool,
+    depth_compare: wgpu::CompareFunction,
+    target: RenderTarget,
+}
+
+impl<'a> RenderPipelineBuilder<'a> {
+    #[must_use]
+    pub fn new(label: &'a str, shader: &'a wgpu::ShaderModule, target: RenderTarget) -> Self {
+        Self {
+            label,
+            shader,
+            bind_group_layouts: Vec::new(),
+            vertex_entry: "vs_main",
+            fragment_entry: Some("fs_main"),
+            vertex_buffers: Vec::new(),
+            primitive: wgpu::PrimitiveState::default(),
+            blend: Some(wgpu::BlendState::REPLACE),
+            depth_write: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            target,
+        }
+    }
+
+    /// Layouts of group 0, 1 and so on.
+    #[must_use]
+    pub fn bind_group_layouts(mut self, layouts: &[&'a wgpu::BindGroupLayout]) -> Self {
+        self.bind_group_layouts = layouts.to_vec();
+        self
+    }
+
+    #[must_use]
+    pub fn vertex_buffer(mut self, layout: wgpu::VertexBufferLayout<'a>) -> Self {
+        self.vertex_buffers.push(layout);
+        self
+    }
+
+    /// Set `fragment` to None for depth only passes.
+    #[must_use]
+    pub const fn entry_points(mut self, vertex: &'a str, fragment: Option<&'a str>) -> Self {
+        self.vertex_entry = vertex;
+        self.fragment_entry = fragment;
+        self
+    }
+
+    #[must_use]
+    pub const fn topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.primitive.topology = topology;
+        self
+    }
+
+    #[must_use]
+    pub const fn cull_mode(mut self, cull_mode: Option<wgpu::Face>) -> Self {
+        self.primitive.cull_mode = cull_mode;
+        self
+    }
+
+    /// `Line` and `Point` require features of the device.
+    #[must_use]
+    pub const fn polygon_mode(mut self, polygon_mode: wgpu::PolygonMode) -> Self {
+        self.primitive.polygon_mode = polygon_mode;
+        self
+    }
+
+    #[must_use]
+    pub const fn blend(mut self, blend: Option<wgpu::BlendState>) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    #[must_use]
+    pub const fn depth_write(mut self, depth_write: bool) -> Self {
+        self.depth_write = depth_write;
+        self
+    }
+
+    #[must_use]
+    pub const fn depth_compare(mut self, depth_compare: wgpu::CompareFunction) -> Self {
+        self.depth_compare = depth_compare;
+        self
+    }
+
+    /// Draw edges of triangles only.
+    #[must_use]
+    pub const fn wireframe(self) -> Self {
+        self.polygon_mode(wgpu::PolygonMode::Line)
+    }
+
+    /// Blend with `src_alpha`, and do not write depth, so that objects drawn
+    /// later behind this one are still visible.
+    #[must_use]
+    pub const fn transparent(self) -> Self {
+        self.blend(Some(wgpu::BlendState::ALPHA_BLENDING))
+            .depth_write(false)
+    }
+
+    #[must_use]
+    pub fn key(&self) -> PipelineKey {
+        PipelineKey {
+            shader: self.shader.clone(),
+            bind_group_layouts: self
+                .bind_group_layouts
+                .iter()
+                .map(|&layout| layout.clone())
+                .collect(),
+            vertex_entry: self.vertex_entry.to_owned(),
+            fragment_entry: self.fragment_entry.map(str::to_owned),
+            vertex_buffers: self
+                .vertex_buffers
+                .iter()
+                .map(|layout| VertexLayoutKey {
+                    array_stride: layout.array_stride,
+                    step_mode: layout.step_mode,
+                    attributes: layout.attributes.to_vec(),
+                })
+                .collect(),
+            primitive: self.primitive,
+            blend: self.blend,
+            depth_write: self.depth_write,
+            depth_compare: self.depth_compare,
+            target: self.target,
+        }
+    }
+
+    /// Create the pipeline, without looking it up in a cache.
+    ///
+    /// `cache` lets the driver reuse pipelines compiled by previous runs.
+    pub fn build(
+        &self,
+        device: &wgpu::Device,
+        cache: Option<&wgpu::PipelineCache>,
+    ) -> Result<wgpu::RenderPipeline, Error> {
+        let feature = match self.primitive.polygon_mode {
+            wgpu::PolygonMode::Fill => wgpu::Features::empty(),
+            wgpu::PolygonMode::Line => wgpu::Features::POLYGON_MODE_LINE,
+            wgpu::PolygonMode::Point => wgpu::Features::POLYGON_MODE_POINT,
+        };
+        if !device.features().contains(feature) {
+            return Err(Error::Others(format!(
+                "{}: {:?} polygon mode requires {feature:?}, which is not enabled on device",
+                self.label, self.primitive.polygon_mode
+            )));
+        }
+
+        let layout_label = format!("{} Layout", self.label);
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&layout_label),
+            bind_group_layouts: &self.bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+        let targets = [Some(wgpu::ColorTargetState {
+            format: self.target.color_format,
+            blend: self.blend,
+            write_mask: wgpu::ColorWrites::ALL,
+        })];
+        Ok(
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(self.label),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: self.shader,
+                    entry_point: Some(self.vertex_entry),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    buffers: &self.vertex_buffers,
+                },
+                fragment: self.fragment_entry.map(|entry_point| wgpu::FragmentState {
+                    module: self.shader,
+                    entry_point: Some(entry_point),
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                    targets: &targets,
+                }),
+                primitive: self.primitive,
+                depth_stencil: self
+                    .target
+                    .depth_format
+                    .map(|format| wgpu::DepthStencilState {
+                        format,
+                        depth_write_enabled: self.depth_write,
+                        depth_compare: self.depth_compare,
+                        stencil: wgpu::StencilState::default(),
+                        bias: wgpu::DepthBiasState::default(),
+                    }),
+                multisample: wgpu::MultisampleState {
+                    count: self.target.sample_count,
+                    ..Default::default()
+                },
+                multiview: None,
+                cache,
+            }),
+        )
+    }
+}
+
+/// Index of a pipeline in `PipelineCache`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PipelineId(usize);
+
+/// Compiled pipelines of the driver, loaded from and saved to a file.
+///
+/// The file is named by adapter and driver, as its data is only valid for
+/// the driver which wrote it.
+#[derive(Debug)]
+pub struct DiskCache {
+    path: PathBuf,
+    cache: wgpu::PipelineCache,
+}
+
+impl DiskCache {
+    /// Device features required by the cache.
+    pub const FEATURES: wgpu::Features = wgpu::Features::PIPELINE_CACHE;
+
+    /// Load cache of `adapter_info` in `dir`.
+    ///
+    /// Returns `None` if `device` does not support pipeline cache, which is
+    /// only available on Vulkan.
+    pub fn load(
+        device: &wgpu::Device,
+        adapter_info: &wgpu::AdapterInfo,
+        dir: &Path,
+    ) -> Option<Self> {
+        if !device.features().contains(Self::FEATURES) {
+            return None;
+        }
+        let path = dir.join(Self::file_name(adapter_info)?);
+        let data = match fs::read(&path) {
+            Ok(data) => Some(data),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                log::warn!("Failed to read pipeline cache {}: {err:?}", path.display());
+                None
+            }
+        };
+        // SAFETY: data is only written by `save()`, and with `fallback` an
+        // empty cache is created if it is invalid or from another driver.
+        let cache = unsafe {
+            device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                label: Some("Pipeline Cache"),
+                data: data.as_deref(),
+                fallback: true,
+            })
+        };
+        Some(Self { path, cache })
+    }
+
+    /// Name of cache file, from adapter vendor and device, and driver
+    /// version.
+    fn file_name(adapter_info: &wgpu::AdapterInfo) -> Option<String> {
+        let key = wgpu::util::pipeline_cache_key(adapter_info)?;
+        let driver: String = format!("{}_{}", adapter_info.driver, adapter_info.driver_info)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        Some(format!("{key}_{driver}.bin"))
+    }
+
+    /// Write compiled pipelines to the cache file.
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(data) = self.cache.get_data() else {
+            return Ok(());
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, data)?;
+        log::info!("Pipeline cache saved to {}", self.path.display());
+        Ok(())
+    }
+}
+
+/// Render pipelines created by `RenderPipelineBuilder`, one per key.
+///
+/// Variants like wireframe or transparent are created the first time they
+/// are requested, switching back and forth only looks them up. With a
+/// `DiskCache`, pipelines compiled by previous runs are reused too.
+#[derive(Debug, Default)]
+pub struct PipelineCache {
+    ids: HashMap<PipelineKey, PipelineId>,
+    pipelines: Vec<wgpu::RenderPipeline>,
+    disk: Option<DiskCache>,
+}
+
+impl PipelineCache {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_disk_cache(disk: Option<DiskCache>) -> Self {
+        Self {
+            disk,
+            ..Self::default()
+        }
+    }
+
+    /// Save compiled pipelines, if a disk cache is used.
+    pub fn save(&self) -> Result<(), Error> {
+        self.disk.as_ref().map_or(Ok(()), DiskCache::save)
+    }
+
+    /// Pipeline of `builder`, created if not found in cache.
+    pub fn get_or_create(
+        &mut self,
+        device: &wgpu::Device,
+        builder: &RenderPipelineBuilder,
+    ) -> Result<PipelineId, Error> {
+        let key = builder.key();
+        if let Some(id) = self.ids.get(&key) {
+            return Ok(*id);
+        }
+        let cache = self.disk.as_ref().map(|disk| &disk.cache);
+        let pipeline = builder.build(device, cache)?;
+        let id = PipelineId(self.pipelines.len());
+        self.pipelines.push(pipeline);
+        self.ids.insert(key, id);
+        Ok(id)
+    }
+
+    #[must_use]
+    pub fn get(&self, id: PipelineId) -> Option<&wgpu::RenderPipeline> {
+        self.pipelines.get(id.0)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    /// Drop all pipelines, ids returned before are invalid.
+    pub fn clear(&mut self) {
+        self.ids.clear();
+        self.pipelines.clear();
+    }
+}
+
+impl Index<PipelineId> for PipelineCache {
+    type Output = wgpu::RenderPipeline;
+
+    fn index(&self, id: PipelineId) -> &Self::Output {
+        &self.pipelines[id.0]
+    }
+}
@@ -0,0 +1,360 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+//! Read resource bindings from WGSL, so that bind group layouts are
+//! generated from `@group` and `@binding` attributes in shaders, instead of
+//! being written again in Rust.
+
+#![allow(dead_code)]
+
+use naga::proc::Layouter;
+use naga::valid::{Capabilities, ModuleInfo, ValidationFlags, Validator};
+use std::num::NonZeroU64;
+
+use crate::Error;
+
+/// Resource bound at `@group(group) @binding(binding)` of a shader.
+#[derive(Debug, Clone)]
+pub struct BindingInfo {
+    pub group: u32,
+    pub binding: u32,
+    /// Name of the global variable.
+    pub name: String,
+    pub ty: wgpu::BindingType,
+    /// Stages of entry points which use the variable.
+    pub visibility: wgpu::ShaderStages,
+    /// Size of binding array, None for a single resource.
+    pub count: Option<std::num::NonZeroU32>,
+}
+
+impl BindingInfo {
+    #[must_use]
+    pub const fn layout_entry(&self) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: self.binding,
+            visibility: self.visibility,
+            ty: self.ty,
+            count: self.count,
+        }
+    }
+}
+
+pub struct ShaderReflection {
+    name: String,
+    module: naga::Module,
+    info: ModuleInfo,
+    layouter: Layouter,
+}
+
+impl ShaderReflection {
+    /// Parse and validate WGSL `source`, `name` is used in error messages.
+    pub fn parse(source: &str, name: &str) -> Result<Self, Error> {
+        let module = naga::front::wgsl::parse_str(source)
+            .map_err(|err| Error::Others(err.emit_to_string_with_path(source, name)))?;
+        let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+            .validate(&module)
+            .map_err(|err| Error::Others(err.emit_to_string_with_path(source, name)))?;
+        let mut layouter = Layouter::default();
+        layouter
+            .update(module.to_ctx())
+            .map_err(|err| Error::Others(format!("{name}: {err}")))?;
+        Ok(Self {
+            name: name.to_owned(),
+            module,
+            info,
+            layouter,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub const fn module(&self) -> &naga::Module {
+        &self.module
+    }
+
+    pub const fn layouter(&self) -> &Layouter {
+        &self.layouter
+    }
+
+    /// All resource bindings, sorted by group and binding.
+    ///
+    /// Float textures are assumed to be filterable, as shaders do not tell
+    /// which sampler they are used with.
+    pub fn bindings(&self) -> Result<Vec<BindingInfo>, Error> {
+        let mut bindings = Vec::new();
+        for (handle, var) in self.module.global_variables.iter() {
+            let Some(binding) = &var.binding else {
+                continue;
+            };
+            let name = var.name.clone().unwrap_or_default();
+            let error = |msg: String| {
+                Error::Others(format!(
+                    "{}: {name} at group {} binding {}: {msg}",
+                    self.name, binding.group, binding.binding
+                ))
+            };
+
+            let (ty, count) = match self.module.types[var.ty].inner {
+                naga::TypeInner::BindingArray { base, size } => match size {
+                    naga::ArraySize::Constant(count) => (base, Some(count)),
+                    _ => return Err(error("binding array shall have constant size".to_owned())),
+                },
+                _ => (var.ty, None),
+            };
+            let ty = self.binding_type(var.space, ty).map_err(error)?;
+
+            let mut visibility = wgpu::ShaderStages::NONE;
+            for (index, entry_point) in self.module.entry_points.iter().enumerate() {
+                if !self.info.get_entry_point(index)[handle].is_empty() {
+                    visibility |= match entry_point.stage {
+                        naga::ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+                        naga::ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+                        naga::ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+                        _ => wgpu::ShaderStages::NONE,
+                    };
+                }
+            }
+
+            bindings.push(BindingInfo {
+                group: binding.group,
+                binding: binding.binding,
+                name,
+                ty,
+                visibility,
+                count,
+            });
+        }
+        bindings.sort_by_key(|info| (info.group, info.binding));
+        Ok(bindings)
+    }
+
+    /// Bindings of `group`, sorted by binding.
+    pub fn group_bindings(&self, group: u32) -> Result<Vec<BindingInfo>, Error> {
+        let mut bindings = self.bindings()?;
+        bindings.retain(|info| info.group == group);
+        Ok(bindings)
+    }
+
+    fn binding_type(
+        &self,
+        space: naga::AddressSpace,
+        ty: naga::Handle<naga::Type>,
+    ) -> Result<wgpu::BindingType, String> {
+        let binding_type = match space {
+            naga::AddressSpace::Uniform => wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: NonZeroU64::new(u64::from(self.layouter[ty].size)),
+            },
+            naga::AddressSpace::Storage { access } => wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage {
+                    read_only: !access.contains(naga::StorageAccess::STORE),
+                },
+                has_dynamic_offset: false,
+                // Depends on length of runtime sized arrays, checked by wgpu.
+                min_binding_size: None,
+            },
+            naga::AddressSpace::Handle => match self.module.types[ty].inner {
+                naga::TypeInner::Sampler { comparison } => {
+                    wgpu::BindingType::Sampler(if comparison {
+                        wgpu::SamplerBindingType::Comparison
+                    } else {
+                        wgpu::SamplerBindingType::Filtering
+                    })
+                }
+                naga::TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class,
+                } => image_binding_type(dim, arrayed, class)?,
+                ref inner => return Err(format!("unsupported handle type {inner:?}")),
+            },
+            space => return Err(format!("unsupported address space {space:?}")),
+        };
+        Ok(binding_type)
+    }
+}
+
+fn image_binding_type(
+    dim: naga::ImageDimension,
+    arrayed: bool,
+    class: naga::ImageClass,
+) -> Result<wgpu::BindingType, String> {
+    let view_dimension = match (dim, arrayed) {
+        (naga::ImageDimension::D1, false) => wgpu::TextureViewDimension::D1,
+        (naga::ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+        (naga::ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+        (naga::ImageDimension::D3, false) => wgpu::TextureViewDimension::D3,
+        (naga::ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+        (naga::ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+        (dim, arrayed) => return Err(format!("unsupported texture {dim:?}, arrayed: {arrayed}")),
+    };
+
+    let binding_type = match class {
+        naga::ImageClass::Sampled { kind, multi } => wgpu::BindingType::Texture {
+            sample_type: match kind {
+                naga::ScalarKind::Float => wgpu::TextureSampleType::Float { filterable: true },
+                naga::ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                naga::ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                kind => return Err(format!("unsupported texture sample type {kind:?}")),
+            },
+            view_dimension,
+            multisampled: multi,
+        },
+        naga::ImageClass::Depth { multi } => wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Depth,
+            view_dimension,
+            multisampled: multi,
+        },
+        naga::ImageClass::Storage { format, access } => wgpu::BindingType::StorageTexture {
+            access: match (
+                access.contains(naga::StorageAccess::LOAD),
+                access.contains(naga::StorageAccess::STORE),
+            ) {
+                (true, true) => wgpu::StorageTextureAccess::ReadWrite,
+                (true, false) => wgpu::StorageTextureAccess::ReadOnly,
+                _ => wgpu::StorageTextureAccess::WriteOnly,
+            },
+            format: storage_format(format)?,
+            view_dimension,
+        },
+    };
+    Ok(binding_type)
+}
+
+/// Formats of storage textures in WebGPU without extra features.
+fn storage_format(format: naga::StorageFormat) -> Result<wgpu::TextureFormat, String> {
+    use naga::StorageFormat as Sf;
+    use wgpu::TextureFormat as Tf;
+    let format = match format {
+        Sf::Rgba8Unorm => Tf::Rgba8Unorm,
+        Sf::Rgba8Snorm => Tf::Rgba8Snorm,
+        Sf::Rgba8Uint => Tf::Rgba8Uint,
+        Sf::Rgba8Sint => Tf::Rgba8Sint,
+        Sf::Bgra8Unorm => Tf::Bgra8Unorm,
+        Sf::Rgba16Uint => Tf::Rgba16Uint,
+        Sf::Rgba16Sint => Tf::Rgba16Sint,
+        Sf::Rgba16Float => Tf::Rgba16Float,
+        Sf::R32Uint => Tf::R32Uint,
+        Sf::R32Sint => Tf::R32Sint,
+        Sf::R32Float => Tf::R32Float,
+        Sf::Rg32Uint => Tf::Rg32Uint,
+        Sf::Rg32Sint => Tf::Rg32Sint,
+        Sf::Rg32Float => Tf::Rg32Float,
+        Sf::Rgba32Uint => Tf::Rgba32Uint,
+        Sf::Rgba32Sint => Tf::Rgba32Sint,
+        Sf::Rgba32Float => Tf::Rgba32Float,
+        format => return Err(format!("unsupported storage texture format {format:?}")),
+    };
+    Ok(format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::box_scene::BoxScene;
+    use crate::light_scene::LightScene;
+
+    fn binding_of(bindings: &[BindingInfo], group: u32, binding: u32) -> &BindingInfo {
+        bindings
+            .iter()
+            .find(|info| info.group == group && info.binding == binding)
+            .unwrap_or_else(|| panic!("No binding {binding} in group {group}"))
+    }
+
+    #[test]
+    fn box_shader_bindings() {
+        let shader = ShaderReflection::parse(BoxScene::SHADER, "box.wgsl").unwrap();
+        let bindings = shader.bindings().unwrap();
+        assert_eq!(bindings.len(), 7);
+
+        let camera = binding_of(&bindings, 0, 0);
+        assert_eq!(camera.name, "camera_uniform");
+        assert_eq!(camera.visibility, wgpu::ShaderStages::VERTEX);
+
+        let light = binding_of(&bindings, 1, 1);
+        assert_eq!(light.visibility, wgpu::ShaderStages::FRAGMENT);
+        assert_eq!(
+            light.ty,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: NonZeroU64::new(96),
+            }
+        );
+
+        assert!(matches!(
+            binding_of(&bindings, 2, 2).ty,
+            wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            }
+        ));
+        assert_eq!(
+            binding_of(&bindings, 2, 3).ty,
+            wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering)
+        );
+    }
+
+    #[test]
+    fn light_shader_bindings() {
+        let shader = ShaderReflection::parse(LightScene::SHADER, "light.wgsl").unwrap();
+        let groups: Vec<(u32, u32)> = shader
+            .bindings()
+            .unwrap()
+            .iter()
+            .map(|info| (info.group, info.binding))
+            .collect();
+        assert_eq!(groups, [(0, 0), (1, 0)]);
+        assert!(shader.group_bindings(2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn storage_and_depth_bindings() {
+        let shader = ShaderReflection::parse(
+            "@group(0) @binding(0) var<storage, read> points: array<vec4<f32>>;
+            @group(0) @binding(1) var shadow_map: texture_depth_2d;
+            @group(0) @binding(2) var shadow_sampler: sampler_comparison;
+            @group(0) @binding(3) var output: texture_storage_2d<rgba8unorm, write>;
+            @compute @workgroup_size(1)
+            fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+                let depth = textureSampleCompareLevel(shadow_map, shadow_sampler, points[id.x].xy, 0.5);
+                textureStore(output, id.xy, vec4<f32>(depth));
+            }",
+            "shadow.wgsl",
+        )
+        .unwrap();
+        let types: Vec<wgpu::BindingType> = shader
+            .bindings()
+            .unwrap()
+            .iter()
+            .inspect(|info| assert_eq!(info.visibility, wgpu::ShaderStages::COMPUTE))
+            .map(|info| info.ty)
+            .collect();
+        assert_eq!(
+            types,
+            [
+                wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                },
+            ]
+        );
+    }
+}
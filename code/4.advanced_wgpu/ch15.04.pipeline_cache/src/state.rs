@@ -0,0 +1,577 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::Vector3;
+use egui_wgpu_backend::{RenderPass, ScreenDescriptor};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use instant::Instant;
+use std::path::Path;
+use std::sync::Arc;
+use wgpu::util::DeviceExt;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::window::Window;
+
+use crate::bind_group::BindGroupLayout;
+use crate::box_scene::{BoxScene, CONSTANT_BLENDING};
+use crate::camera::Camera;
+use crate::frames::{BoxUniformWindow, PipelineSettings, PipelineWindow};
+use crate::light_scene::LightScene;
+use crate::pipeline::{DiskCache, PipelineCache, PipelineId, RenderTarget};
+use crate::reflection::ShaderReflection;
+use crate::texture::Texture;
+use crate::uniform_buffer::std140_bytes;
+use crate::Error;
+
+const MSAA_SAMPLES: u32 = 4;
+
+pub struct State {
+    surface: wgpu::Surface<'static>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    size: PhysicalSize<u32>,
+    window: Arc<Window>,
+
+    box_scene: BoxScene,
+    light_scene: LightScene,
+
+    pipelines: PipelineCache,
+    pipeline_settings: PipelineSettings,
+    box_pipeline: PipelineId,
+    light_pipeline: PipelineId,
+
+    camera: Camera,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group_layout: BindGroupLayout,
+    camera_bind_group: wgpu::BindGroup,
+
+    depth_texture: Texture,
+    msaa_view: Option<wgpu::TextureView>,
+
+    start_time: Instant,
+    egui_platform: Platform,
+    egui_render_pass: RenderPass,
+    color_window: BoxUniformWindow,
+    pipeline_window: PipelineWindow,
+}
+
+impl State {
+    /// Pipeline cache feature is requested with `disk_cache`, if supported
+    /// by adapter.
+    async fn create_surface(
+        window: Arc<Window>,
+        disk_cache: bool,
+    ) -> Result<
+        (
+            wgpu::Surface<'static>,
+            wgpu::Device,
+            wgpu::Queue,
+            wgpu::SurfaceConfiguration,
+            PhysicalSize<u32>,
+            wgpu::TextureFormat,
+            wgpu::AdapterInfo,
+        ),
+        Error,
+    > {
+        let size = window.inner_size();
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        let surface = instance.create_surface(window)?;
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or_else(|| {
+                Error::Others("Failed to get an approprivate wgpu adapter".to_owned())
+            })?;
+
+        let mut required_features = if cfg!(target_arch = "wasm32") {
+            wgpu::Features::empty()
+        } else {
+            wgpu::Features::POLYGON_MODE_LINE
+        };
+        if disk_cache {
+            if adapter.features().contains(DiskCache::FEATURES) {
+                required_features |= DiskCache::FEATURES;
+            } else {
+                log::warn!("Pipeline cache is not supported by adapter, kept in memory");
+            }
+        }
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features,
+                    required_limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::default(),
+                },
+                None,
+            )
+            .await?;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(wgpu::TextureFormat::is_srgb)
+            .unwrap_or(surface_caps.formats[0]);
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &config);
+
+        Ok((
+            surface,
+            device,
+            queue,
+            config,
+            size,
+            surface_format,
+            adapter.get_info(),
+        ))
+    }
+
+    /// Camera at group 0 is shared by box and light pipelines, its layout
+    /// is visible in stages of both shaders.
+    fn create_camera(
+        device: &wgpu::Device,
+        size: PhysicalSize<u32>,
+        shaders: &[&ShaderReflection],
+    ) -> Result<(Camera, wgpu::Buffer, BindGroupLayout, wgpu::BindGroup), Error> {
+        let eye_pos = (0.0, 1.0, 2.0).into();
+        let aspect = size.width as f32 / size.height as f32;
+        let camera = Camera::new(eye_pos, aspect);
+
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Uniform Buffer"),
+            contents: &std140_bytes(camera.uniform()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let camera_bind_group_layout =
+            BindGroupLayout::from_shaders(device, shaders, 0, "camera_bind_group_layout")?;
+        let camera_bind_group = camera_bind_group_layout
+            .bind_group()
+            .buffer(0, &camera_buffer)
+            .build(device, "camera_bind_group")?;
+
+        Ok((
+            camera,
+            camera_buffer,
+            camera_bind_group_layout,
+            camera_bind_group,
+        ))
+    }
+
+    const fn sample_count(settings: PipelineSettings) -> u32 {
+        if settings.msaa {
+            MSAA_SAMPLES
+        } else {
+            1
+        }
+    }
+
+    fn render_target(
+        config: &wgpu::SurfaceConfiguration,
+        settings: PipelineSettings,
+    ) -> RenderTarget {
+        RenderTarget::new(
+            config,
+            Some(Texture::DEPTH_FORMAT),
+            Self::sample_count(settings),
+        )
+    }
+
+    /// Multisampled color attachment, resolved to the surface texture.
+    fn create_msaa_view(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+    ) -> Option<wgpu::TextureView> {
+        if sample_count == 1 {
+            return None;
+        }
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MSAA Texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+    }
+
+    /// Pipelines of box and light scenes for `settings`, only created if
+    /// not found in `pipelines`.
+    fn prepare_pipelines(
+        device: &wgpu::Device,
+        pipelines: &mut PipelineCache,
+        box_scene: &BoxScene,
+        light_scene: &LightScene,
+        camera_bind_group_layout: &BindGroupLayout,
+        target: RenderTarget,
+        settings: PipelineSettings,
+    ) -> Result<(PipelineId, PipelineId), Error> {
+        let mut box_pipeline = box_scene.pipeline(camera_bind_group_layout, target);
+        if settings.wireframe {
+            box_pipeline = box_pipeline.wireframe();
+        }
+        if settings.transparent {
+            box_pipeline = box_pipeline.transparent().blend(Some(CONSTANT_BLENDING));
+        }
+        let box_id = pipelines.get_or_create(device, &box_pipeline)?;
+
+        let light_pipeline = light_scene.pipeline(camera_bind_group_layout, target);
+        let light_id = pipelines.get_or_create(device, &light_pipeline)?;
+
+        Ok((box_id, light_id))
+    }
+
+    /// Switch to pipelines of `settings`, and keep the current ones if they
+    /// cannot be created.
+    fn set_pipeline_settings(&mut self, settings: PipelineSettings) {
+        let target = Self::render_target(&self.config, settings);
+        match Self::prepare_pipelines(
+            &self.device,
+            &mut self.pipelines,
+            &self.box_scene,
+            &self.light_scene,
+            &self.camera_bind_group_layout,
+            target,
+            settings,
+        ) {
+            Ok((box_pipeline, light_pipeline)) => {
+                self.box_pipeline = box_pipeline;
+                self.light_pipeline = light_pipeline;
+                let msaa_changed = settings.msaa != self.pipeline_settings.msaa;
+                self.pipeline_settings = settings;
+                if msaa_changed {
+                    self.create_render_textures();
+                }
+            }
+            Err(err) => {
+                log::error!("Failed to switch pipeline: {err:?}");
+                self.pipeline_window.settings = self.pipeline_settings;
+            }
+        }
+        self.pipeline_window
+            .set_pipeline_count(self.pipelines.len());
+    }
+
+    /// Depth and MSAA textures, which have size of surface and sample count
+    /// of pipelines.
+    fn create_render_textures(&mut self) {
+        let sample_count = Self::sample_count(self.pipeline_settings);
+        self.depth_texture = Texture::create_depth_texture(
+            &self.device,
+            self.size,
+            sample_count,
+            Some("Depth Texture"),
+        );
+        self.msaa_view = Self::create_msaa_view(&self.device, &self.config, sample_count);
+    }
+
+    fn create_egui_platform(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        scale_factor: f64,
+    ) -> (Platform, RenderPass, BoxUniformWindow, PipelineWindow) {
+        let platform = Platform::new(PlatformDescriptor {
+            physical_width: size.width,
+            physical_height: size.height,
+            scale_factor,
+            ..Default::default()
+        });
+
+        let render_pass = RenderPass::new(device, surface_format, 1);
+        let color_window = BoxUniformWindow::default();
+        let wireframe_supported = device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE);
+        let pipeline_window = PipelineWindow::new(wireframe_supported);
+
+        (platform, render_pass, color_window, pipeline_window)
+    }
+
+    /// Compiled pipelines are loaded from and saved to `cache_dir` if given.
+    pub async fn new(window: Arc<Window>, cache_dir: Option<&Path>) -> Result<Self, Error> {
+        let (surface, device, queue, config, size, surface_format, adapter_info) =
+            Self::create_surface(Arc::clone(&window), cache_dir.is_some()).await?;
+
+        let (egui_platform, egui_render_pass, color_window, mut pipeline_window) =
+            Self::create_egui_platform(&device, surface_format, size, window.scale_factor());
+
+        let box_shader = ShaderReflection::parse(BoxScene::SHADER, "box.wgsl")?;
+        let light_shader = ShaderReflection::parse(LightScene::SHADER, "light.wgsl")?;
+
+        let (camera, camera_buffer, camera_bind_group_layout, camera_bind_group) =
+            Self::create_camera(&device, size, &[&box_shader, &light_shader])?;
+
+        let box_scene = BoxScene::new(&device, &queue, &box_shader)?;
+        let light_scene = LightScene::new(&device, &light_shader)?;
+
+        let pipeline_settings = pipeline_window.settings;
+        let disk_cache = cache_dir.and_then(|dir| DiskCache::load(&device, &adapter_info, dir));
+        let mut pipelines = PipelineCache::with_disk_cache(disk_cache);
+        let (box_pipeline, light_pipeline) = Self::prepare_pipelines(
+            &device,
+            &mut pipelines,
+            &box_scene,
+            &light_scene,
+            &camera_bind_group_layout,
+            Self::render_target(&config, pipeline_settings),
+            pipeline_settings,
+        )?;
+        pipeline_window.set_pipeline_count(pipelines.len());
+
+        let sample_count = Self::sample_count(pipeline_settings);
+        let depth_texture =
+            Texture::create_depth_texture(&device, size, sample_count, Some("Depth Texture"));
+        let msaa_view = Self::create_msaa_view(&device, &config, sample_count);
+
+        Ok(Self {
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            size,
+
+            box_scene,
+            light_scene,
+
+            pipelines,
+            pipeline_settings,
+            box_pipeline,
+            light_pipeline,
+
+            camera,
+            camera_buffer,
+            camera_bind_group_layout,
+            camera_bind_group,
+
+            depth_texture,
+            msaa_view,
+
+            start_time: Instant::now(),
+            egui_platform,
+            egui_render_pass,
+            color_window,
+            pipeline_window,
+        })
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn platform_mut(&mut self) -> &mut Platform {
+        &mut self.egui_platform
+    }
+
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.surface.configure(&self.device, &self.config);
+
+            self.create_render_textures();
+        }
+    }
+
+    /// Save compiled pipelines before exit.
+    pub fn save_pipeline_cache(&self) {
+        if let Err(err) = self.pipelines.save() {
+            log::error!("Failed to save pipeline cache: {err:?}");
+        }
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        self.camera.process_event(event)
+    }
+
+    pub fn update(&mut self) {
+        let dt = self.start_time.elapsed().as_secs_f64();
+        self.egui_platform.update_time(dt);
+
+        let settings = self.pipeline_window.settings;
+        if settings != self.pipeline_settings {
+            self.set_pipeline_settings(settings);
+        }
+
+        self.queue.write_buffer(
+            &self.box_scene.material_buffer,
+            0,
+            &std140_bytes(&self.box_scene.material),
+        );
+
+        let light_pos = self.color_window.light_pos;
+        let light = &mut self.box_scene.light;
+        let light_color = Vector3::new(1.0, 1.0, 1.0);
+        light.position = self.camera.position();
+        light.direction = self.camera.front();
+        light.diffuse = light_color * 0.5;
+        light.ambient = light.diffuse * 0.2;
+        self.queue.write_buffer(
+            &self.box_scene.light_buffer,
+            0,
+            &std140_bytes(&self.box_scene.light),
+        );
+
+        self.light_scene.uniform.reset();
+        self.light_scene.uniform.set_position(light_pos);
+        self.light_scene.uniform.scale((0.2, 0.2, 0.2).into());
+
+        self.queue.write_buffer(
+            &self.light_scene.uniform_buffer,
+            0,
+            &std140_bytes(&self.light_scene.uniform),
+        );
+
+        self.queue
+            .write_buffer(&self.camera_buffer, 0, &std140_bytes(self.camera.uniform()));
+    }
+
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: self.msaa_view.as_ref().unwrap_or(&view),
+                    resolve_target: self.msaa_view.as_ref().map(|_| &view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.1,
+                            g: 0.1,
+                            b: 0.1,
+                            a: 1.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_texture.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            // Light is drawn first, as the box may be transparent.
+            render_pass.set_pipeline(&self.pipelines[self.light_pipeline]);
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            render_pass.set_bind_group(1, &self.light_scene.uniform_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, self.light_scene.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(
+                self.light_scene.index_buffer.slice(..),
+                wgpu::IndexFormat::Uint16,
+            );
+            render_pass.draw_indexed(0..self.light_scene.num_indices, 0, 0..1);
+
+            // Only used by the transparent pipeline.
+            let opacity = f64::from(self.pipeline_window.opacity);
+            render_pass.set_blend_constant(wgpu::Color {
+                r: opacity,
+                g: opacity,
+                b: opacity,
+                a: opacity,
+            });
+            render_pass.set_pipeline(&self.pipelines[self.box_pipeline]);
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            render_pass.set_bind_group(1, &self.box_scene.uniform_bind_group, &[]);
+            render_pass.set_bind_group(2, &self.box_scene.texture_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, self.box_scene.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(
+                self.box_scene.index_buffer.slice(..),
+                wgpu::IndexFormat::Uint16,
+            );
+            render_pass.draw_indexed(0..self.box_scene.num_indices, 0, 0..1);
+        }
+
+        // Draw the egui UI frame.
+        {
+            self.egui_platform.begin_pass();
+            self.color_window.ui(&self.egui_platform.context());
+            self.pipeline_window.ui(&self.egui_platform.context());
+            let full_output = self.egui_platform.end_pass(Some(&self.window));
+            let paint_jobs = self
+                .egui_platform
+                .context()
+                .tessellate(full_output.shapes, full_output.pixels_per_point);
+            // Upload all resources for the GPU.
+            let screen_descriptor = ScreenDescriptor {
+                physical_width: self.config.width,
+                physical_height: self.config.height,
+                scale_factor: self.window.scale_factor() as f32,
+            };
+            let tdelta: egui::TexturesDelta = full_output.textures_delta;
+            self.egui_render_pass
+                .add_textures(&self.device, &self.queue, &tdelta)
+                .expect("add texture ok");
+            self.egui_render_pass.update_buffers(
+                &self.device,
+                &self.queue,
+                &paint_jobs,
+                &screen_descriptor,
+            );
+
+            // Record all render passes.
+            self.egui_render_pass
+                .execute(&mut encoder, &view, &paint_jobs, &screen_descriptor, None)
+                .unwrap();
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+// Copyright (c) 2023 Xu Shaohua <shaohua@biofan.org>. All rights reserved.
+// Use of this source is governed by General Public License that can be found
+// in the LICENSE file.
+
+use cgmath::{Matrix4, One};
+use encase::{ShaderSize, ShaderType};
+
+#[derive(Debug, Clone, PartialEq, ShaderType)]
+pub struct Uniforms {
+    pub model: Matrix4<f32>,
+    pub view: Matrix4<f32>,
+    pub projection: Matrix4<f32>,
+}
+
+// Three mat4x4<f32>, checked at compile time.
+const _: () = assert!(Uniforms::SHADER_SIZE.get() == 192);
+
+impl Default for Uniforms {
+    fn default() -> Self {
+        Self {
+            model: Matrix4::one(),
+            view: Matrix4::one(),
+            projection: Matrix4::one(),
+        }
+    }
+}